@@ -1,19 +1,25 @@
 extern crate clap;
 
-use clap::{Arg, ArgAction, ColorChoice, Command};
+use clap::{Arg, ArgAction, ArgMatches, ColorChoice, Command};
 use clap::error::ErrorKind;
+use std::collections::HashMap;
 use std::ffi::OsString;
 use std::env;
 use users::{get_user_by_uid, get_current_uid};
 use urlencoding::encode;
 
+// application_name of the connections, used to find the client sessions hit by --fault. The
+// process id keeps the sessions of the other pgmtr instances out of reach.
+pub fn application_name() -> String {
+    format!("pgmtr-{}", std::process::id())
+}
 
 // run sub-command arguments
+#[derive(Clone)]
 pub struct RunArgs {
-    // Number of concurrent client connected to the database
+    // Number of concurrent client connected to the database. With several stages, this is the
+    // highest number of clients.
     pub client: u16,
-    // Test duration, in second
-    pub time: u16,
     // Rampup duration, in second
     pub rampup: u16,
     // Minimum object ID value we want to hit during the benchmark. In TPCC context, the object ID is a warehouse id.
@@ -21,24 +27,318 @@ pub struct RunArgs {
     pub min_id: u32,
     // Max object ID value
     pub max_id: u32,
+    // Random seed. When set, each client uses its own deterministic random generator
+    pub seed: Option<u64>,
+    // Pick customers, items and last names uniformly instead of using NURand
+    pub uniform: bool,
+    // Print the transactions SQL templates instead of running the workload
+    pub dry_run: bool,
+    // Do not check the dataset before starting the clients
+    pub skip_data_check: bool,
+    // Load the tables and indexes of the object IDs range into shared buffers before the run
+    pub prewarm: bool,
+    // Load schedule: number of clients and duration of each stage. A run without --stages has one
+    // stage using client and time.
+    pub stages: Vec<RunStage>,
+    // Search for the highest number of clients meeting the SLOs, using measurement windows of
+    // the stage duration
+    pub find_max: bool,
+    // Response time objectives used by the search
+    pub slos: Vec<Slo>,
+    // Transaction mix: name and weight of each transaction. Empty when the default mix is used.
+    pub mix: Vec<(String, u16)>,
+    // Bind each client to a home object ID instead of picking a random one for each transaction
+    pub affinity: bool,
+    // How the read/write transactions are executed
+    pub mode: TransactionMode,
+    // Query protocol used to send the statements
+    pub protocol: QueryProtocol,
+    // Isolation level of the client sessions, None for the server default
+    pub isolation: Option<IsolationLevel>,
+    // Settings applied to the client sessions: name and value
+    pub settings: Vec<(String, String)>,
+    // Time each statement of the transactions
+    pub statement_timing: bool,
+    // Number of transactions after which the clients reconnect, None to keep the connections
+    pub reconnect_every: Option<u32>,
+    // Number of connections shared by the clients, None for one connection per client
+    pub pool_size: Option<u32>,
+    // Run the read-only transactions on the standbys
+    pub read_routing: bool,
+    // Reconnect the clients after connection errors and measure the outages
+    pub failover: bool,
+    // Faults injected during the run
+    pub faults: Vec<Fault>,
+}
+
+// Transaction isolation level
+#[derive(Clone, Copy, PartialEq)]
+pub enum IsolationLevel {
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    pub fn sql(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+// Execution mode of the read/write transactions
+#[derive(Clone, Copy, PartialEq)]
+pub enum TransactionMode {
+    // Each statement is sent by the client, in an explicit transaction
+    Statement,
+    // Each transaction is one call of a PL/pgSQL function installed by the procedures init step
+    Procedure,
+}
+
+// Properties of the server to connect to, like libpq's target_session_attrs
+#[derive(Clone, Copy, PartialEq)]
+pub enum TargetSessionAttrs {
+    Any,
+    ReadWrite,
+    ReadOnly,
+    Primary,
+    Standby,
+    PreferStandby,
+}
+
+impl TargetSessionAttrs {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TargetSessionAttrs::Any => "any",
+            TargetSessionAttrs::ReadWrite => "read-write",
+            TargetSessionAttrs::ReadOnly => "read-only",
+            TargetSessionAttrs::Primary => "primary",
+            TargetSessionAttrs::Standby => "standby",
+            TargetSessionAttrs::PreferStandby => "prefer-standby",
+        }
+    }
+}
+
+// Query protocol used to send the statements of the read/write transactions
+#[derive(Clone, Copy, PartialEq)]
+pub enum QueryProtocol {
+    // Simple query protocol, parameters are inlined as literals
+    Simple,
+    // Extended query protocol with unnamed statements, parsed for each execution
+    Extended,
+    // Extended query protocol with named statements, prepared once per connection
+    Prepared,
+}
+
+// Response time objective: the given percentile of the transaction response times must stay
+// below max_ms
+#[derive(Clone)]
+pub struct Slo {
+    pub transaction: String,
+    pub percentile: f64,
+    pub max_ms: f64,
+}
+
+// Fault injected during the run, at_ms milliseconds after the start of the clients
+#[derive(Clone)]
+pub struct Fault {
+    pub at_ms: u64,
+    pub kind: FaultKind,
+}
+
+#[derive(Clone)]
+pub enum FaultKind {
+    // pg_terminate_backend() on the given percentage of the client sessions
+    Terminate(f64),
+    // pg_cancel_backend() on the given percentage of the client sessions
+    Cancel(f64),
+    Checkpoint,
+    // ACCESS EXCLUSIVE lock on the table, held for the given number of milliseconds
+    Lock(String, u64),
+}
+
+impl FaultKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FaultKind::Terminate(_) => "terminate",
+            FaultKind::Cancel(_) => "cancel",
+            FaultKind::Checkpoint => "checkpoint",
+            FaultKind::Lock(_, _) => "lock",
+        }
+    }
+}
+
+// One stage of the load schedule
+#[derive(Clone, Copy)]
+pub struct RunStage {
+    // Number of concurrent clients during the stage
+    pub clients: u16,
+    // Stage duration, in second
+    pub time: u16,
 }
 
 // init sub-command arguments
+#[derive(Clone)]
 pub struct InitArgs {
     // Database scale factor. In TPC-C context, this is the number of warehouses
     pub scalefactor: u32,
     // Number of concurrent jobs used to populate the database
     pub jobs: u32,
-    // Do not create foreign keys
-    pub no_fkey: bool,
+    // Ordered list of the init steps to execute
+    pub steps: Vec<InitStep>,
+    // Tables and indexes storage options
+    pub storage: StorageArgs,
+    // COPY format used to load data
+    pub copy_format: CopyFormat,
+    // Where data is generated: client or server side
+    pub generate: DataGeneration,
+    // Random seed. When set, data of each warehouse is generated using its own deterministic random
+    // generator, the same seed always producing the same dataset
+    pub seed: Option<u64>,
+    // Pick customers last names uniformly instead of using NURand
+    pub uniform: bool,
+    // Print the statements of the init steps instead of executing them
+    pub dry_run: bool,
+    // Do not compare the estimated dataset size with the free disk space before loading data
+    pub skip_space_check: bool,
+}
+
+// COPY format used to load data
+#[derive(Clone, Copy, PartialEq)]
+pub enum CopyFormat {
+    Text,
+    Binary,
+}
+
+// Where benchmark data is generated
+#[derive(Clone, Copy, PartialEq)]
+pub enum DataGeneration {
+    // Rows are generated by pgmtr and sent using COPY
+    Client,
+    // Rows are generated by the database server using INSERT .. SELECT
+    Server,
+}
+
+// Init steps
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InitStep {
+    // Drop and create the tables
+    Schema,
+    // Empty the existing tables
+    Truncate,
+    Load,
+    Pkeys,
+    Fkeys,
+    Indexes,
+    // Create the functions used by the procedure transaction mode
+    Procedures,
+    Vacuum,
+    Checkpoint,
+}
+
+// Steps executed by default, in this order
+const DEFAULT_INIT_STEPS: [InitStep; 8] = [
+    InitStep::Schema,
+    InitStep::Load,
+    InitStep::Pkeys,
+    InitStep::Fkeys,
+    InitStep::Indexes,
+    InitStep::Procedures,
+    InitStep::Vacuum,
+    InitStep::Checkpoint,
+];
+
+impl InitStep {
+    pub fn name(&self) -> &'static str {
+        match self {
+            InitStep::Schema => "schema",
+            InitStep::Truncate => "truncate",
+            InitStep::Load => "load",
+            InitStep::Pkeys => "pkeys",
+            InitStep::Fkeys => "fkeys",
+            InitStep::Indexes => "indexes",
+            InitStep::Procedures => "procedures",
+            InitStep::Vacuum => "vacuum",
+            InitStep::Checkpoint => "checkpoint",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<InitStep> {
+        match name {
+            "schema" => Some(InitStep::Schema),
+            "truncate" => Some(InitStep::Truncate),
+            "load" => Some(InitStep::Load),
+            "pkeys" => Some(InitStep::Pkeys),
+            "fkeys" => Some(InitStep::Fkeys),
+            "indexes" => Some(InitStep::Indexes),
+            "procedures" => Some(InitStep::Procedures),
+            "vacuum" => Some(InitStep::Vacuum),
+            "checkpoint" => Some(InitStep::Checkpoint),
+            _ => None,
+        }
+    }
+}
+
+// Storage options of one table
+#[derive(Clone, Default)]
+pub struct TableStorage {
+    // Table fillfactor, from 10 to 100
+    pub fillfactor: Option<u8>,
+    // Create the table as UNLOGGED
+    pub unlogged: bool,
+    // Tablespace where the table is created
+    pub tablespace: Option<String>,
+    // Table access method
+    pub access_method: Option<String>,
+}
+
+// Storage options of one class of indexes: primary keys or additional indexes
+#[derive(Clone, Default)]
+pub struct IndexStorage {
+    // Index fillfactor, from 10 to 100
+    pub fillfactor: Option<u8>,
+    // Tablespace where the indexes are created
+    pub tablespace: Option<String>,
+}
+
+// Storage options used when creating tables and indexes
+#[derive(Clone, Default)]
+pub struct StorageArgs {
+    // Options applied to all the tables
+    pub all_tables: TableStorage,
+    // Per-table options, taking precedence over all_tables
+    pub tables: HashMap<String, TableStorage>,
+    // Primary keys options
+    pub pkeys: IndexStorage,
+    // Additional indexes options
+    pub indexes: IndexStorage,
+}
+
+impl StorageArgs {
+    // Returns the storage options of the given table: per-table options first, then the options
+    // applied to all the tables.
+    pub fn table(&self, name: &str) -> TableStorage {
+        let default = TableStorage::default();
+        let table = self.tables.get(name).unwrap_or(&default);
+
+        TableStorage {
+            fillfactor: table.fillfactor.or(self.all_tables.fillfactor),
+            unlogged: table.unlogged || self.all_tables.unlogged,
+            tablespace: table.tablespace.clone().or_else(|| self.all_tables.tablespace.clone()),
+            access_method: table.access_method.clone().or_else(|| self.all_tables.access_method.clone()),
+        }
+    }
 }
 
 // CLI arguments
 pub struct PgMtrArgs {
-    // Database host address
-    pub host: String,
-    // TCP connection port
-    pub port: u16,
+    // Database hosts and their TCP connection port
+    pub hosts: Vec<(String, u16)>,
+    // Properties of the host to connect to, when several hosts are given
+    pub target_session_attrs: TargetSessionAttrs,
     // Database username
     pub username: String,
     // Username password
@@ -58,14 +358,14 @@ pub struct PgMtrArgs {
 // Implementation of RunArgs::empty()
 impl RunArgs {
     fn empty() -> Self {
-        RunArgs {client: 0, time: 0, rampup: 0, min_id: 0, max_id: 0}
+        RunArgs {client: 0, rampup: 0, min_id: 0, max_id: 0, seed: None, uniform: false, dry_run: false, skip_data_check: false, prewarm: false, stages: Vec::new(), find_max: false, slos: Vec::new(), mix: Vec::new(), affinity: false, mode: TransactionMode::Statement, protocol: QueryProtocol::Prepared, isolation: None, settings: Vec::new(), statement_timing: false, reconnect_every: None, pool_size: None, read_routing: false, failover: false, faults: Vec::new()}
     }
 }
 
 // Implementation of InitArgs::empty()
 impl InitArgs {
     fn empty() -> Self {
-        InitArgs {scalefactor: 0, jobs: 0, no_fkey: false}
+        InitArgs {scalefactor: 0, jobs: 0, steps: Vec::new(), storage: StorageArgs::default(), copy_format: CopyFormat::Text, generate: DataGeneration::Client, seed: None, uniform: false, dry_run: false, skip_space_check: false}
     }
 }
 
@@ -103,6 +403,395 @@ fn parse_string_arg_to_u32(value: &str, error_msg: String) -> Result<u32, clap::
     Ok(u32_value)
 }
 
+// Parse a string argument and convert it to u64
+fn parse_string_arg_to_u64(value: &str, error_msg: String) -> Result<u64, clap::Error> {
+    match value.parse::<u64>() {
+        Ok(v) => Ok(v),
+        Err(_) => Err(
+            clap::Error::raw(
+                ErrorKind::InvalidValue,
+                format!("{}: \"{}\"\n", error_msg, value)
+            )
+        ),
+    }
+}
+
+// Parse the optional --seed argument
+fn parse_seed_arg(matches: &ArgMatches) -> Result<Option<u64>, clap::Error> {
+    match matches.get_one::<String>("seed") {
+        Some(seed_str) => Ok(Some(parse_string_arg_to_u64(seed_str, "invalid seed value".to_string())?)),
+        None => Ok(None),
+    }
+}
+
+// Split a "[KEY=]VALUE" argument into its optional key and its value
+fn split_keyed_arg(value: &str) -> (Option<&str>, &str) {
+    match value.split_once('=') {
+        Some((key, value)) => (Some(key), value),
+        None => (None, value),
+    }
+}
+
+// Check that the value is a plain SQL identifier: tablespaces and access methods names are
+// injected as is in the DDLs.
+fn parse_identifier_arg(value: &str, error_msg: &str) -> Result<String, clap::Error> {
+    let valid = !value.is_empty()
+        && !value.starts_with(|c: char| c.is_ascii_digit())
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(
+            clap::Error::raw(
+                ErrorKind::InvalidValue,
+                format!("{}: \"{}\"\n", error_msg, value)
+            )
+        )
+    }
+
+    Ok(value.to_lowercase())
+}
+
+// Parse a fillfactor value, which must be between 10 and 100
+fn parse_fillfactor_arg(value: &str) -> Result<u8, clap::Error> {
+    match value.parse::<u8>() {
+        Ok(v) if (10..=100).contains(&v) => Ok(v),
+        _ => Err(
+            clap::Error::raw(
+                ErrorKind::InvalidValue,
+                format!("invalid fillfactor value, must be between 10 and 100: \"{}\"\n", value)
+            )
+        ),
+    }
+}
+
+// Returns the IndexStorage objects targeted by an index class name: "pkey", "index" or all of
+// them when the class is not specified.
+fn get_index_storages<'a>(storage: &'a mut StorageArgs, class: Option<&str>) -> Result<Vec<&'a mut IndexStorage>, clap::Error> {
+    match class {
+        None => Ok(vec![&mut storage.pkeys, &mut storage.indexes]),
+        Some("pkey") => Ok(vec![&mut storage.pkeys]),
+        Some("index") => Ok(vec![&mut storage.indexes]),
+        Some(class) => Err(
+            clap::Error::raw(
+                ErrorKind::InvalidValue,
+                format!("invalid index class, must be pkey or index: \"{}\"\n", class)
+            )
+        ),
+    }
+}
+
+// Check the init steps order. Steps missing from the list are expected to have been executed
+// by a previous init.
+fn validate_init_steps(steps: &[InitStep]) -> Result<(), String> {
+    let position = |step: InitStep| steps.iter().position(|s| *s == step);
+
+    for (i, step) in steps.iter().enumerate() {
+        if position(*step) != Some(i) {
+            return Err(format!("step {} is listed more than once", step.name()));
+        }
+    }
+    // Creating the schema drops the tables: it must come first, and nothing is left to truncate
+    if let Some(schema) = position(InitStep::Schema) {
+        if position(InitStep::Truncate).is_some() {
+            return Err("steps schema and truncate cannot be used together".to_string());
+        }
+        if let Some(step) = steps[..schema].iter().find(|s| **s != InitStep::Checkpoint) {
+            return Err(format!("step {} must come after schema", step.name()));
+        }
+        // Foreign keys reference primary keys
+        if position(InitStep::Fkeys).is_some() && position(InitStep::Pkeys).is_none() {
+            return Err("step fkeys requires pkeys when the schema is created".to_string());
+        }
+    }
+    let before = |first: InitStep, second: InitStep| -> Result<(), String> {
+        match (position(first), position(second)) {
+            (Some(f), Some(s)) if f > s => Err(format!("step {} must come after {}", second.name(), first.name())),
+            _ => Ok(()),
+        }
+    };
+    before(InitStep::Truncate, InitStep::Load)?;
+    before(InitStep::Pkeys, InitStep::Fkeys)?;
+    // Tables are not loaded in the foreign keys order
+    before(InitStep::Load, InitStep::Fkeys)?;
+    before(InitStep::Load, InitStep::Vacuum)?;
+
+    Ok(())
+}
+
+// Build the load schedule from --stages, formatted as CLIENTS:SECONDS[,CLIENTS:SECONDS...], or
+// from --client and --time when not set
+fn parse_stages_arg(matches: &ArgMatches, client: u16, time: u16) -> Result<Vec<RunStage>, clap::Error> {
+    let value = match matches.get_one::<String>("stages") {
+        Some(value) => value,
+        None => return Ok(vec![RunStage {clients: client, time}]),
+    };
+
+    let mut stages = Vec::new();
+    for stage in value.split(',').map(|stage| stage.trim()) {
+        let (clients_str, time_str) = match stage.split_once(':') {
+            Some(v) => v,
+            None => return Err(
+                clap::Error::raw(ErrorKind::InvalidValue, format!("invalid stage, expected CLIENTS:SECONDS: \"{}\"\n", stage))
+            ),
+        };
+        let clients = parse_string_arg_to_u16(clients_str, "invalid stage client number".to_string())?;
+        let time = parse_string_arg_to_u16(time_str, "invalid stage time value".to_string())?;
+        if clients == 0 || time == 0 {
+            return Err(
+                clap::Error::raw(ErrorKind::InvalidValue, format!("stage client number and time must be greater than 0: \"{}\"\n", stage))
+            );
+        }
+        stages.push(RunStage {clients, time});
+    }
+
+    Ok(stages)
+}
+
+// Parse the --mix value, formatted as NAME=WEIGHT[,NAME=WEIGHT...]
+fn parse_mix_arg(matches: &ArgMatches) -> Result<Vec<(String, u16)>, clap::Error> {
+    let value = match matches.get_one::<String>("mix") {
+        Some(value) => value,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut mix: Vec<(String, u16)> = Vec::new();
+    for item in value.split(',').map(|item| item.trim()) {
+        let (name, weight_str) = match item.split_once('=') {
+            Some((name, weight_str)) => (name.trim(), weight_str.trim()),
+            None => return Err(
+                clap::Error::raw(ErrorKind::InvalidValue, format!("invalid transaction weight, expected NAME=WEIGHT: \"{}\"\n", item))
+            ),
+        };
+        let weight = parse_string_arg_to_u16(weight_str, "invalid transaction weight".to_string())?;
+        if mix.iter().any(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            return Err(
+                clap::Error::raw(ErrorKind::InvalidValue, format!("transaction listed more than once: \"{}\"\n", name))
+            );
+        }
+        mix.push((name.to_string(), weight));
+    }
+
+    Ok(mix)
+}
+
+// Parse the --set values, formatted as NAME=VALUE. Names are checked here, values by the server.
+fn parse_set_args(matches: &ArgMatches) -> Result<Vec<(String, String)>, clap::Error> {
+    let mut settings = Vec::new();
+    for value in matches.get_many::<String>("set").unwrap_or_default() {
+        let (name, setting) = match value.split_once('=') {
+            Some((name, setting)) => (name.trim(), setting.trim()),
+            None => return Err(
+                clap::Error::raw(ErrorKind::InvalidValue, format!("invalid setting, expected NAME=VALUE: \"{}\"\n", value))
+            ),
+        };
+        // Extension settings are prefixed by the extension name
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+        if !valid {
+            return Err(
+                clap::Error::raw(ErrorKind::InvalidValue, format!("invalid setting name: \"{}\"\n", name))
+            );
+        }
+        settings.push((name.to_string(), setting.to_string()));
+    }
+
+    Ok(settings)
+}
+
+// Parse the comma separated lists of hosts and ports. Like libpq, a single port applies to all the
+// hosts.
+fn parse_hosts_arg(host: &str, port: &str) -> Result<Vec<(String, u16)>, clap::Error> {
+    let hosts: Vec<&str> = host.split(',').map(|h| h.trim()).collect();
+    let mut ports = Vec::new();
+    for port_str in port.split(',') {
+        // Convert port ton u16
+        ports.push(parse_string_arg_to_u16(port_str.trim(), "invalid port number".to_string())?);
+    }
+    if hosts.iter().any(|h| h.is_empty()) {
+        return Err(clap::Error::raw(ErrorKind::InvalidValue, format!("invalid host list: \"{}\"\n", host)));
+    }
+    if ports.len() != 1 && ports.len() != hosts.len() {
+        return Err(
+            clap::Error::raw(ErrorKind::InvalidValue, format!("{} ports given for {} hosts\n", ports.len(), hosts.len()))
+        );
+    }
+
+    Ok(
+        hosts
+            .iter()
+            .enumerate()
+            .map(|(i, h)| (h.to_string(), if ports.len() == 1 { ports[0] } else { ports[i] }))
+            .collect()
+    )
+}
+
+// Parse one --slo value, formatted as TRANSACTION:pPERCENTILE<VALUE(ms|s), for example
+// New-Order:p90<50ms
+fn parse_slo_arg(value: &str) -> Result<Slo, clap::Error> {
+    let invalid = || clap::Error::raw(
+        ErrorKind::InvalidValue,
+        format!("invalid SLO, expected TRANSACTION:pPERCENTILE<VALUE(ms|s): \"{}\"\n", value)
+    );
+
+    let (transaction, objective) = value.rsplit_once(':').ok_or_else(invalid)?;
+    let (percentile_str, max_str) = objective.split_once('<').ok_or_else(invalid)?;
+    let percentile = match percentile_str.trim().strip_prefix('p').map(|p| p.parse::<f64>()) {
+        Some(Ok(p)) if p > 0.0 && p < 100.0 => p,
+        _ => return Err(invalid()),
+    };
+    let max_ms = match parse_duration_ms(max_str) {
+        Some(v) if v > 0.0 => v,
+        _ => return Err(invalid()),
+    };
+
+    Ok(Slo {transaction: transaction.trim().to_string(), percentile, max_ms})
+}
+
+// Parse a duration with its unit, ms or s, and returns it in milliseconds
+fn parse_duration_ms(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let (number, unit_ms) = match value.strip_suffix("ms") {
+        Some(number) => (number, 1.0),
+        None => (value.strip_suffix('s')?, 1000.0),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(v) if v >= 0.0 => Some(v * unit_ms),
+        _ => None,
+    }
+}
+
+// Parse one --fault value, formatted as terminate:TIME[:PERCENT%], cancel:TIME[:PERCENT%],
+// checkpoint:TIME or lock:TIME:DURATION:TABLE, for example terminate:30s:25%
+fn parse_fault_arg(value: &str) -> Result<Fault, clap::Error> {
+    let invalid = || clap::Error::raw(
+        ErrorKind::InvalidValue,
+        format!("invalid fault, expected terminate|cancel:TIME[:PERCENT%], checkpoint:TIME or lock:TIME:DURATION:TABLE: \"{}\"\n", value)
+    );
+    let parse_percent = |percent_str: &str| match percent_str.trim().strip_suffix('%').map(|p| p.trim().parse::<f64>()) {
+        Some(Ok(p)) if p > 0.0 && p <= 100.0 => Ok(p),
+        _ => Err(invalid()),
+    };
+
+    let parts: Vec<&str> = value.split(':').collect();
+    let at_ms = parts.get(1).and_then(|at| parse_duration_ms(at)).ok_or_else(invalid)? as u64;
+    let kind = match parts.as_slice() {
+        ["terminate", _] => FaultKind::Terminate(100.0),
+        ["terminate", _, percent_str] => FaultKind::Terminate(parse_percent(percent_str)?),
+        ["cancel", _] => FaultKind::Cancel(100.0),
+        ["cancel", _, percent_str] => FaultKind::Cancel(parse_percent(percent_str)?),
+        ["checkpoint", _] => FaultKind::Checkpoint,
+        ["lock", _, duration_str, table] => {
+            let duration_ms = match parse_duration_ms(duration_str) {
+                Some(v) if v > 0.0 => v as u64,
+                _ => return Err(invalid()),
+            };
+            // The table name, optionally schema qualified, is part of the LOCK statement
+            let table = table.trim();
+            if table.is_empty() || !table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+                return Err(
+                    clap::Error::raw(ErrorKind::InvalidValue, format!("invalid table name: \"{}\"\n", table))
+                );
+            }
+            FaultKind::Lock(table.to_string(), duration_ms)
+        },
+        _ => return Err(invalid()),
+    };
+
+    Ok(Fault {at_ms, kind})
+}
+
+// Build the init steps list from --steps, or from the default steps when not set
+fn parse_steps_arg(matches: &ArgMatches) -> Result<Vec<InitStep>, clap::Error> {
+    let no_fkey = matches.get_flag("no_fkey");
+    let steps = match matches.get_one::<String>("steps") {
+        Some(value) => {
+            let mut steps = Vec::new();
+            for name in value.split(',').map(|name| name.trim()) {
+                match InitStep::from_name(name) {
+                    Some(step) => steps.push(step),
+                    None => return Err(
+                        clap::Error::raw(ErrorKind::InvalidValue, format!("invalid init step: \"{}\"\n", name))
+                    ),
+                }
+            }
+            if no_fkey && steps.contains(&InitStep::Fkeys) {
+                return Err(
+                    clap::Error::raw(ErrorKind::ArgumentConflict, "--no-fkey conflicts with the fkeys step\n")
+                );
+            }
+            steps
+        },
+        None => DEFAULT_INIT_STEPS
+            .iter()
+            .filter(|step| !(no_fkey && **step == InitStep::Fkeys))
+            .cloned()
+            .collect(),
+    };
+
+    match validate_init_steps(&steps) {
+        Ok(_) => Ok(steps),
+        Err(e) => Err(clap::Error::raw(ErrorKind::InvalidValue, format!("invalid init steps: {}\n", e))),
+    }
+}
+
+// Build the storage options from the init sub-command matches
+fn parse_storage_args(matches: &ArgMatches) -> Result<StorageArgs, clap::Error> {
+    let mut storage = StorageArgs::default();
+
+    storage.all_tables.unlogged = matches.get_flag("unlogged");
+    for table in matches.get_many::<String>("unlogged_table").unwrap_or_default() {
+        let table = parse_identifier_arg(table, "invalid table name")?;
+        storage.tables.entry(table).or_default().unlogged = true;
+    }
+    for value in matches.get_many::<String>("fillfactor").unwrap_or_default() {
+        let (table, fillfactor) = split_keyed_arg(value);
+        let fillfactor = parse_fillfactor_arg(fillfactor)?;
+        match table {
+            Some(table) => {
+                let table = parse_identifier_arg(table, "invalid table name")?;
+                storage.tables.entry(table).or_default().fillfactor = Some(fillfactor);
+            },
+            None => storage.all_tables.fillfactor = Some(fillfactor),
+        }
+    }
+    for value in matches.get_many::<String>("tablespace").unwrap_or_default() {
+        let (table, tablespace) = split_keyed_arg(value);
+        let tablespace = parse_identifier_arg(tablespace, "invalid tablespace name")?;
+        match table {
+            Some(table) => {
+                let table = parse_identifier_arg(table, "invalid table name")?;
+                storage.tables.entry(table).or_default().tablespace = Some(tablespace);
+            },
+            None => storage.all_tables.tablespace = Some(tablespace),
+        }
+    }
+    for value in matches.get_many::<String>("table_am").unwrap_or_default() {
+        let (table, access_method) = split_keyed_arg(value);
+        let access_method = parse_identifier_arg(access_method, "invalid table access method name")?;
+        match table {
+            Some(table) => {
+                let table = parse_identifier_arg(table, "invalid table name")?;
+                storage.tables.entry(table).or_default().access_method = Some(access_method);
+            },
+            None => storage.all_tables.access_method = Some(access_method),
+        }
+    }
+    for value in matches.get_many::<String>("index_fillfactor").unwrap_or_default() {
+        let (class, fillfactor) = split_keyed_arg(value);
+        let fillfactor = parse_fillfactor_arg(fillfactor)?;
+        for index_storage in get_index_storages(&mut storage, class)? {
+            index_storage.fillfactor = Some(fillfactor);
+        }
+    }
+    for value in matches.get_many::<String>("index_tablespace").unwrap_or_default() {
+        let (class, tablespace) = split_keyed_arg(value);
+        let tablespace = parse_identifier_arg(tablespace, "invalid tablespace name")?;
+        for index_storage in get_index_storages(&mut storage, class)? {
+            index_storage.tablespace = Some(tablespace.clone());
+        }
+    }
+
+    Ok(storage)
+}
+
 impl PgMtrArgs {
     pub fn new(username: String, password: String) -> Self {
         Self::new_from(username, password, std::env::args_os().into_iter()).unwrap_or_else(|e| e.exit())
@@ -121,7 +810,7 @@ impl PgMtrArgs {
             .action(ArgAction::Set)
             .env("PGHOST")
             .short('h') // allow -h
-            .help("Database server host or socket directory")
+            .help("Database server host or socket directory. Several hosts can be given, separated by commas")
             .required(false)
             .value_name("HOSTNAME")
             .default_value("localhost");
@@ -132,7 +821,7 @@ impl PgMtrArgs {
             .action(ArgAction::Set)
             .env("PGPORT")
             .short('p') // allow -p
-            .help("Database server port")
+            .help("Database server port. One port by host can be given, separated by commas")
             .required(false)
             .value_name("PORT")
             .default_value("5432");
@@ -148,6 +837,17 @@ impl PgMtrArgs {
             .value_name("USERNAME")
             .default_value(&username);
 
+        // Define the global --target-session-attrs command line option
+        let target_session_attrs_option = Arg::new("target-session-attrs")
+            .long("target-session-attrs") // allow --target-session-attrs
+            .action(ArgAction::Set)
+            .env("PGTARGETSESSIONATTRS")
+            .help("Properties of the host to connect to, when several hosts are given")
+            .required(false)
+            .value_name("ATTRS")
+            .value_parser(["any", "read-write", "read-only", "primary", "standby", "prefer-standby"])
+            .default_value("any");
+
         // Define the global --dbname/-d command line option
         let dbname_option = Arg::new("dbname")
             .long("dbname") // allow --dbname
@@ -208,6 +908,167 @@ impl PgMtrArgs {
             .value_name("NUM")
             .default_value("0");
 
+        // run: Define the --seed command line option
+        let run_seed_option = Arg::new("seed")
+            .long("seed") // allow --seed
+            .action(ArgAction::Set)
+            .help("Random seed. Each client derives its own generator from it, making transaction parameters reproducible")
+            .required(false)
+            .value_name("NUM");
+
+        // run: Define the --uniform command line option
+        let run_uniform_option = Arg::new("uniform")
+            .long("uniform") // allow --uniform
+            .action(ArgAction::SetTrue)
+            .help("Pick customers, items and last names uniformly instead of using NURand");
+
+        // run: Define the --dry-run command line option
+        let run_dry_run_option = Arg::new("dry_run")
+            .long("dry-run") // allow --dry-run
+            .action(ArgAction::SetTrue)
+            .help("Print the SQL templates of every transaction, without connecting to the database");
+
+        // run: Define the --skip-data-check command line option
+        let skip_data_check_option = Arg::new("skip_data_check")
+            .long("skip-data-check") // allow --skip-data-check
+            .action(ArgAction::SetTrue)
+            .help("Do not check tables, primary keys, indexes and object IDs before starting the clients");
+
+        // run: Define the --stages command line option
+        let stages_option = Arg::new("stages")
+            .long("stages") // allow --stages
+            .action(ArgAction::Set)
+            .help("Load schedule as a comma separated list of CLIENTS:SECONDS stages, run one after the other. Clients are added or stopped between stages. Example: 8:60,16:60,32:60")
+            .required(false)
+            .value_name("STAGES")
+            .conflicts_with_all(["client", "time"]);
+
+        // run: Define the --find-max command line option
+        let find_max_option = Arg::new("find_max")
+            .long("find-max") // allow --find-max
+            .action(ArgAction::SetTrue)
+            .help("Search for the highest number of clients, up to --client, meeting the SLOs. Each level is measured for --time seconds, the number of clients is doubled and then bisected")
+            .requires("slo")
+            .conflicts_with("stages");
+
+        // run: Define the --slo command line option
+        let slo_option = Arg::new("slo")
+            .long("slo") // allow --slo
+            .action(ArgAction::Append)
+            .help("Response time objective used by --find-max, as TRANSACTION:pPERCENTILE<VALUE(ms|s). Example: New-Order:p90<50ms")
+            .required(false)
+            .value_name("SLO")
+            .requires("find_max");
+
+        // run: Define the --affinity command line option
+        let affinity_option = Arg::new("affinity")
+            .long("affinity") // allow --affinity
+            .action(ArgAction::SetTrue)
+            .help("Bind each client to a home warehouse and district, assigned round-robin over the ID range, instead of picking a random warehouse for each transaction");
+
+        // run: Define the --mix command line option
+        let mix_option = Arg::new("mix")
+            .long("mix") // allow --mix
+            .action(ArgAction::Set)
+            .help("Transaction weights as a comma separated list of NAME=WEIGHT, overriding the default weights of the listed transactions. A weight of 0 disables the transaction. Example: Order-Status=0,Stock-Level=10")
+            .required(false)
+            .value_name("MIX");
+
+        // run: Define the --prewarm command line option
+        let prewarm_option = Arg::new("prewarm")
+            .long("prewarm") // allow --prewarm
+            .action(ArgAction::SetTrue)
+            .help("Load the tables and indexes of the object IDs range into shared buffers before starting the clients, using pg_prewarm when installed");
+
+        // run: Define the --mode command line option
+        let mode_option = Arg::new("mode")
+            .long("mode") // allow --mode
+            .action(ArgAction::Set)
+            .help("Transaction execution mode. statement sends each statement from the client, procedure calls the PL/pgSQL functions created by the procedures init step, one SELECT per transaction")
+            .required(false)
+            .value_name("MODE")
+            .value_parser(["statement", "procedure"])
+            .default_value("statement");
+
+        // run: Define the --protocol command line option
+        let protocol_option = Arg::new("protocol")
+            .long("protocol") // allow --protocol
+            .action(ArgAction::Set)
+            .help("Query protocol. simple inlines the parameters, extended parses each statement, prepared prepares each statement once per connection. pipeline is not supported: the driver sends a Sync after each statement")
+            .required(false)
+            .value_name("PROTOCOL")
+            .value_parser(["simple", "extended", "prepared", "pipeline"])
+            .default_value("prepared");
+
+        // run: Define the --isolation command line option
+        let isolation_option = Arg::new("isolation")
+            .long("isolation") // allow --isolation
+            .action(ArgAction::Set)
+            .help("Isolation level of the client sessions. Default: the server default_transaction_isolation")
+            .required(false)
+            .value_name("LEVEL")
+            .value_parser(["read-committed", "repeatable-read", "serializable"]);
+
+        // run: Define the --set command line option
+        let set_option = Arg::new("set")
+            .long("set") // allow --set
+            .action(ArgAction::Append)
+            .help("Setting applied to each client session once connected, as NAME=VALUE. Example: synchronous_commit=off")
+            .required(false)
+            .value_name("NAME=VALUE");
+
+        // run: Define the --statement-timing command line option
+        let statement_timing_option = Arg::new("statement-timing")
+            .long("statement-timing") // allow --statement-timing
+            .action(ArgAction::SetTrue)
+            .help("Time each statement of the transactions and report the response times by statement");
+
+        // run: Define the --connect-per-transaction command line option
+        let connect_per_transaction_option = Arg::new("connect-per-transaction")
+            .long("connect-per-transaction") // allow --connect-per-transaction
+            .action(ArgAction::SetTrue)
+            .help("Open a new connection for each transaction, and report the connection times")
+            .conflicts_with("reconnect-every");
+
+        // run: Define the --reconnect-every command line option
+        let reconnect_every_option = Arg::new("reconnect-every")
+            .long("reconnect-every") // allow --reconnect-every
+            .action(ArgAction::Set)
+            .help("Reconnect the clients every NUM transactions, and report the connection times")
+            .required(false)
+            .value_name("NUM");
+
+        // run: Define the --pool-size command line option
+        let pool_size_option = Arg::new("pool-size")
+            .long("pool-size") // allow --pool-size
+            .action(ArgAction::Set)
+            .help("Share a pool of NUM connections between the clients, which borrow a connection for each transaction")
+            .required(false)
+            .value_name("NUM")
+            .conflicts_with_all(["connect-per-transaction", "reconnect-every"]);
+
+        // run: Define the --read-routing command line option
+        let read_routing_option = Arg::new("read-routing")
+            .long("read-routing") // allow --read-routing
+            .action(ArgAction::SetTrue)
+            .help("Run the read-only transactions on the standbys of the host list, and the read/write transactions on the primary")
+            .conflicts_with("pool-size");
+
+        // run: Define the --failover command line option
+        let failover_option = Arg::new("failover")
+            .long("failover") // allow --failover
+            .action(ArgAction::SetTrue)
+            .help("Reconnect the clients after connection errors, re-resolving the host list, and report the outages")
+            .conflicts_with("pool-size");
+
+        // run: Define the --fault command line option
+        let fault_option = Arg::new("fault")
+            .long("fault") // allow --fault
+            .action(ArgAction::Append)
+            .help("Fault injected TIME after the start of the clients: terminate|cancel:TIME[:PERCENT%] signals a share of the client sessions, found by the application_name of this run, checkpoint:TIME runs a CHECKPOINT, lock:TIME:DURATION:TABLE holds an ACCESS EXCLUSIVE lock. terminate requires --failover or --pool-size. Example: terminate:30s:25%")
+            .required(false)
+            .value_name("FAULT");
+
         // init: Define the --scalefactor/-s command line option
         let scalefactor_option = Arg::new("scalefactor")
             .long("scalefactor") // allow --scalefactor
@@ -234,6 +1095,117 @@ impl PgMtrArgs {
             .action(ArgAction::SetTrue)
             .help("Do not create foreign keys");
 
+        // init: Define the --steps command line option
+        let steps_option = Arg::new("steps")
+            .long("steps") // allow --steps
+            .action(ArgAction::Set)
+            .help("Comma separated list of init steps, executed in this order: schema, truncate, load, pkeys, fkeys, indexes, procedures, vacuum, checkpoint. Default: schema,load,pkeys,fkeys,indexes,procedures,vacuum,checkpoint")
+            .required(false)
+            .value_name("STEPS");
+
+        // init: Define the --fillfactor command line option
+        let fillfactor_option = Arg::new("fillfactor")
+            .long("fillfactor") // allow --fillfactor
+            .action(ArgAction::Append)
+            .help("Tables fillfactor, for all the tables or only one table if prefixed by TABLE=")
+            .required(false)
+            .value_name("[TABLE=]NUM");
+
+        // init: Define the --unlogged command line option
+        let unlogged_option = Arg::new("unlogged")
+            .long("unlogged") // allow --unlogged
+            .action(ArgAction::SetTrue)
+            .help("Create all the tables as UNLOGGED");
+
+        // init: Define the --unlogged-table command line option
+        let unlogged_table_option = Arg::new("unlogged_table")
+            .long("unlogged-table") // allow --unlogged-table
+            .action(ArgAction::Append)
+            .help("Create this table as UNLOGGED. Requires --no-fkey, or the fkeys step left out of --steps, unless all the tables are unlogged")
+            .required(false)
+            .value_name("TABLE");
+
+        // init: Define the --tablespace command line option
+        let tablespace_option = Arg::new("tablespace")
+            .long("tablespace") // allow --tablespace
+            .action(ArgAction::Append)
+            .help("Tables tablespace, for all the tables or only one table if prefixed by TABLE=")
+            .required(false)
+            .value_name("[TABLE=]NAME");
+
+        // init: Define the --table-am command line option
+        let table_am_option = Arg::new("table_am")
+            .long("table-am") // allow --table-am
+            .action(ArgAction::Append)
+            .help("Table access method, for all the tables or only one table if prefixed by TABLE=")
+            .required(false)
+            .value_name("[TABLE=]NAME");
+
+        // init: Define the --index-fillfactor command line option
+        let index_fillfactor_option = Arg::new("index_fillfactor")
+            .long("index-fillfactor") // allow --index-fillfactor
+            .action(ArgAction::Append)
+            .help("Indexes fillfactor, for all the indexes or only one class of index if prefixed by pkey= or index=")
+            .required(false)
+            .value_name("[CLASS=]NUM");
+
+        // init: Define the --index-tablespace command line option
+        let index_tablespace_option = Arg::new("index_tablespace")
+            .long("index-tablespace") // allow --index-tablespace
+            .action(ArgAction::Append)
+            .help("Indexes tablespace, for all the indexes or only one class of index if prefixed by pkey= or index=")
+            .required(false)
+            .value_name("[CLASS=]NAME");
+
+        // init: Define the --copy-format command line option
+        let copy_format_option = Arg::new("copy_format")
+            .long("copy-format") // allow --copy-format
+            .action(ArgAction::Set)
+            .help("COPY format used to load data. Binary COPY generates rows in a separate thread for each job")
+            .required(false)
+            .value_name("FORMAT")
+            .value_parser(["text", "binary"])
+            .default_value("text");
+
+        // init: Define the --generate command line option
+        let generate_option = Arg::new("generate")
+            .long("generate") // allow --generate
+            .action(ArgAction::Set)
+            .help("Where data is generated. Server side generation uses INSERT .. SELECT for the largest tables")
+            .required(false)
+            .value_name("SIDE")
+            .value_parser(["client", "server"])
+            .default_value("client");
+
+        // init: Define the --seed command line option
+        let init_seed_option = Arg::new("seed")
+            .long("seed") // allow --seed
+            .action(ArgAction::Set)
+            .help("Random seed. Each warehouse derives its own generator from it, making the dataset reproducible")
+            .required(false)
+            .value_name("NUM");
+
+        // init: Define the --uniform command line option
+        let init_uniform_option = Arg::new("uniform")
+            .long("uniform") // allow --uniform
+            .action(ArgAction::SetTrue)
+            .help("Generate customers last names uniformly instead of using NURand");
+
+        // init: Define the --dry-run command line option
+        let init_dry_run_option = Arg::new("dry_run")
+            .long("dry-run") // allow --dry-run
+            .action(ArgAction::SetTrue)
+            .help("Print the statements of the init steps and the estimated row counts, without connecting to the database");
+
+        // init: Define the --skip-space-check command line option
+        let skip_space_check_option = Arg::new("skip_space_check")
+            .long("skip-space-check") // allow --skip-space-check
+            .action(ArgAction::SetTrue)
+            .help("Do not compare the estimated dataset size with the free disk space before loading data");
+
+        // estimate: Define the --scalefactor/-s command line option
+        let estimate_scalefactor_option = scalefactor_option.clone();
+
         // Sub-commands
         // run tpcc <OPTIONS>
         let run_tpcc = Command::new("tpcc")
@@ -242,14 +1214,54 @@ impl PgMtrArgs {
             .arg(time_option)
             .arg(rampup_option)
             .arg(min_id_option)
-            .arg(max_id_option);
+            .arg(max_id_option)
+            .arg(run_seed_option)
+            .arg(run_uniform_option)
+            .arg(run_dry_run_option)
+            .arg(skip_data_check_option)
+            .arg(prewarm_option)
+            .arg(stages_option)
+            .arg(find_max_option)
+            .arg(slo_option)
+            .arg(mix_option)
+            .arg(affinity_option)
+            .arg(mode_option)
+            .arg(protocol_option)
+            .arg(isolation_option)
+            .arg(set_option)
+            .arg(statement_timing_option)
+            .arg(connect_per_transaction_option)
+            .arg(reconnect_every_option)
+            .arg(pool_size_option)
+            .arg(read_routing_option)
+            .arg(failover_option)
+            .arg(fault_option);
 
         // init tpcc <OPTIONS>
         let init_tpcc = Command::new("tpcc")
             .about("Initialize TPC-C-like benchmark data")
             .arg(scalefactor_option)
             .arg(jobs_option)
-            .arg(no_fkey_option);
+            .arg(no_fkey_option)
+            .arg(steps_option)
+            .arg(fillfactor_option)
+            .arg(unlogged_option)
+            .arg(unlogged_table_option)
+            .arg(tablespace_option)
+            .arg(table_am_option)
+            .arg(index_fillfactor_option)
+            .arg(index_tablespace_option)
+            .arg(copy_format_option)
+            .arg(generate_option)
+            .arg(init_seed_option)
+            .arg(init_uniform_option)
+            .arg(init_dry_run_option)
+            .arg(skip_space_check_option);
+
+        // estimate tpcc <OPTIONS>
+        let estimate_tpcc = Command::new("tpcc")
+            .about("Estimate TPC-C-like benchmark dataset size")
+            .arg(estimate_scalefactor_option);
 
         // init <SUBCOMMAND> <OPTIONS>
         let init = Command::new("init")
@@ -258,6 +1270,13 @@ impl PgMtrArgs {
             .subcommand_required(true)
             .subcommand(init_tpcc);
 
+        // estimate <SUBCOMMAND> <OPTIONS>
+        let estimate = Command::new("estimate")
+            .about("Estimate the size of the benchmark data, without connecting to the database")
+            .arg_required_else_help(true)
+            .subcommand_required(true)
+            .subcommand(estimate_tpcc);
+
         // run <SUBCOMMAND> <OPTIONS>
         let run = Command::new("run")
             .about("Run benchmark")
@@ -273,6 +1292,7 @@ impl PgMtrArgs {
             .arg_required_else_help(true)
             .subcommand_required(true)
             .subcommand(init)
+            .subcommand(estimate)
             .subcommand(run);
 
         // Add in the arguments we want to parse
@@ -280,7 +1300,8 @@ impl PgMtrArgs {
             .arg(host_option)
             .arg(port_option)
             .arg(username_option)
-            .arg(dbname_option);
+            .arg(dbname_option)
+            .arg(target_session_attrs_option);
 
         // Extract the matches
         let matches = cmd.try_get_matches_from(args)?;
@@ -299,8 +1320,15 @@ impl PgMtrArgs {
             .get_one::<String>("dbname")
             .unwrap();
 
-        // Convert port ton u16
-        let port = parse_string_arg_to_u16(port_str, "invalid port number".to_string())?;
+        let hosts = parse_hosts_arg(host, port_str)?;
+        let target_session_attrs = match matches.get_one::<String>("target-session-attrs").map(|s| s.as_str()) {
+            Some("read-write") => TargetSessionAttrs::ReadWrite,
+            Some("read-only") => TargetSessionAttrs::ReadOnly,
+            Some("primary") => TargetSessionAttrs::Primary,
+            Some("standby") => TargetSessionAttrs::Standby,
+            Some("prefer-standby") => TargetSessionAttrs::PreferStandby,
+            _ => TargetSessionAttrs::Any,
+        };
 
         // Extract subcommand options
         let (run_args, init_args, action, benchmark_type) = match matches.subcommand_name() {
@@ -308,7 +1336,7 @@ impl PgMtrArgs {
                 let init_m = matches.subcommand_matches("init").unwrap();
                 let (run_args, init_args, benchmark_type) = match init_m.subcommand_name() {
                     Some("tpcc") => {
-                        let (scalefactor, jobs, no_fkey) = match init_m.subcommand_matches("tpcc") {
+                        let init_args = match init_m.subcommand_matches("tpcc") {
                             Some(tpcc_m) => {
                                 let scalefactor_str = tpcc_m
                                     .get_one::<String>("scalefactor")
@@ -316,30 +1344,70 @@ impl PgMtrArgs {
                                 let jobs_str = tpcc_m
                                     .get_one::<String>("jobs")
                                     .unwrap();
-                                let no_fkey = tpcc_m.get_flag("no_fkey");
+                                let steps = parse_steps_arg(tpcc_m)?;
 
                                 // Convert scalefactor to u32
                                 let scalefactor = parse_string_arg_to_u32(scalefactor_str, "invalid scale factor number".to_string())?;
                                 // Convert jobs to u32
                                 let jobs = parse_string_arg_to_u32(jobs_str, "invalid jobs number".to_string())?;
+                                // Tables and indexes storage options
+                                let storage = parse_storage_args(tpcc_m)?;
+                                // A permanent table cannot reference an unlogged one, the foreign keys
+                                // can only be created when all the tables are unlogged
+                                let some_unlogged = storage.tables.values().any(|table| table.unlogged);
+                                if some_unlogged && !storage.all_tables.unlogged && steps.contains(&InitStep::Fkeys) {
+                                    return Err(
+                                        clap::Error::raw(ErrorKind::ArgumentConflict, "--unlogged-table conflicts with the fkeys step, use --unlogged or --no-fkey\n")
+                                    );
+                                }
+                                let copy_format = match tpcc_m.get_one::<String>("copy_format").map(|s| s.as_str()) {
+                                    Some("binary") => CopyFormat::Binary,
+                                    _ => CopyFormat::Text,
+                                };
+                                let generate = match tpcc_m.get_one::<String>("generate").map(|s| s.as_str()) {
+                                    Some("server") => DataGeneration::Server,
+                                    _ => DataGeneration::Client,
+                                };
 
-                                (scalefactor, jobs, no_fkey)
+                                let seed = parse_seed_arg(tpcc_m)?;
+                                let uniform = tpcc_m.get_flag("uniform");
+                                let dry_run = tpcc_m.get_flag("dry_run");
+                                let skip_space_check = tpcc_m.get_flag("skip_space_check");
+
+                                InitArgs {scalefactor, jobs, steps, storage, copy_format, generate, seed, uniform, dry_run, skip_space_check}
                             },
-                            _ => (0, 0, false)
+                            _ => InitArgs::empty(),
                         };
 
-                        (RunArgs::empty(), InitArgs {scalefactor: scalefactor, jobs: jobs, no_fkey: no_fkey}, "tpcc".to_string())
+                        (RunArgs::empty(), init_args, "tpcc".to_string())
                     },
                     _ => (RunArgs::empty(), InitArgs::empty(), "undefined".to_string()),
                 };
 
                 (run_args, init_args, "init".to_string(), benchmark_type)
             },
+            Some("estimate") => {
+                let estimate_m = matches.subcommand_matches("estimate").unwrap();
+                let (init_args, benchmark_type) = match estimate_m.subcommand_matches("tpcc") {
+                    Some(tpcc_m) => {
+                        let scalefactor_str = tpcc_m
+                            .get_one::<String>("scalefactor")
+                            .unwrap();
+                        // Convert scalefactor to u32
+                        let scalefactor = parse_string_arg_to_u32(scalefactor_str, "invalid scale factor number".to_string())?;
+
+                        (InitArgs {scalefactor, ..InitArgs::empty()}, "tpcc".to_string())
+                    },
+                    _ => (InitArgs::empty(), "undefined".to_string()),
+                };
+
+                (RunArgs::empty(), init_args, "estimate".to_string(), benchmark_type)
+            },
             Some("run") => {
                 let run_m = matches.subcommand_matches("run").unwrap();
                 let (run_args, init_args, benchmark_type) = match run_m.subcommand_name() {
                     Some("tpcc") => {
-                        let (client, time, rampup, min_id, max_id) = match run_m.subcommand_matches("tpcc") {
+                        let run_args = match run_m.subcommand_matches("tpcc") {
                             Some(tpcc_m) => {
                                 let client_str = tpcc_m
                                     .get_one::<String>("client")
@@ -360,6 +1428,8 @@ impl PgMtrArgs {
                                 let client = parse_string_arg_to_u16(client_str, "invalid client number".to_string())?;
                                 // Convert time to u16
                                 let time = parse_string_arg_to_u16(time_str, "invalid time value".to_string())?;
+                                let stages = parse_stages_arg(tpcc_m, client, time)?;
+                                let client = stages.iter().map(|stage| stage.clients).max().unwrap_or(client);
                                 // Convert rampup to u16
                                 let rampup = parse_string_arg_to_u16(rampup_str, "invalid rampup value".to_string())?;
                                 // Convert min_id to u32
@@ -367,12 +1437,72 @@ impl PgMtrArgs {
                                 // Convert max_id to u32
                                 let max_id = parse_string_arg_to_u32(max_id_str, "invalid end id value".to_string())?;
 
-                                (client, time, rampup, min_id, max_id)
+                                let seed = parse_seed_arg(tpcc_m)?;
+                                let uniform = tpcc_m.get_flag("uniform");
+                                let dry_run = tpcc_m.get_flag("dry_run");
+                                let skip_data_check = tpcc_m.get_flag("skip_data_check");
+                                let prewarm = tpcc_m.get_flag("prewarm");
+                                let find_max = tpcc_m.get_flag("find_max");
+                                let mix = parse_mix_arg(tpcc_m)?;
+                                let affinity = tpcc_m.get_flag("affinity");
+                                let mode = match tpcc_m.get_one::<String>("mode").map(|s| s.as_str()) {
+                                    Some("procedure") => TransactionMode::Procedure,
+                                    _ => TransactionMode::Statement,
+                                };
+                                let protocol = match tpcc_m.get_one::<String>("protocol").map(|s| s.as_str()) {
+                                    Some("simple") => QueryProtocol::Simple,
+                                    Some("extended") => QueryProtocol::Extended,
+                                    // sqlx 0.6 cannot queue several Bind/Execute messages before one Sync
+                                    Some("pipeline") => return Err(clap::Error::raw(ErrorKind::InvalidValue, "--protocol pipeline is not supported: the statements cannot be queued before one Sync with sqlx 0.6\n")),
+                                    _ => QueryProtocol::Prepared,
+                                };
+                                let isolation = match tpcc_m.get_one::<String>("isolation").map(|s| s.as_str()) {
+                                    Some("read-committed") => Some(IsolationLevel::ReadCommitted),
+                                    Some("repeatable-read") => Some(IsolationLevel::RepeatableRead),
+                                    Some("serializable") => Some(IsolationLevel::Serializable),
+                                    _ => None,
+                                };
+                                let settings = parse_set_args(tpcc_m)?;
+                                let statement_timing = tpcc_m.get_flag("statement-timing");
+                                let reconnect_every = match tpcc_m.get_one::<String>("reconnect-every") {
+                                    Some(value) => match parse_string_arg_to_u32(value, "invalid reconnect-every value".to_string())? {
+                                        0 => return Err(clap::Error::raw(ErrorKind::InvalidValue, "reconnect-every must be greater than 0\n")),
+                                        n => Some(n),
+                                    },
+                                    None if tpcc_m.get_flag("connect-per-transaction") => Some(1),
+                                    None => None,
+                                };
+                                let read_routing = tpcc_m.get_flag("read-routing");
+                                let failover = tpcc_m.get_flag("failover");
+                                let mut faults = Vec::new();
+                                for value in tpcc_m.get_many::<String>("fault").unwrap_or_default() {
+                                    faults.push(parse_fault_arg(value)?);
+                                }
+                                let pool_size = match tpcc_m.get_one::<String>("pool-size") {
+                                    Some(value) => match parse_string_arg_to_u32(value, "invalid pool size value".to_string())? {
+                                        0 => return Err(clap::Error::raw(ErrorKind::InvalidValue, "pool size must be greater than 0\n")),
+                                        n => Some(n),
+                                    },
+                                    None => None,
+                                };
+                                // A terminated client only gets a new connection from the failover
+                                // reconnection or from the pool
+                                if !failover && pool_size.is_none() && faults.iter().any(|fault| matches!(fault.kind, FaultKind::Terminate(_))) {
+                                    return Err(clap::Error::raw(ErrorKind::ArgumentConflict, "terminate faults require --failover or --pool-size\n"));
+                                }
+                                let mut slos = Vec::new();
+                                if let Some(values) = tpcc_m.get_many::<String>("slo") {
+                                    for value in values {
+                                        slos.push(parse_slo_arg(value)?);
+                                    }
+                                }
+
+                                RunArgs {client, rampup, min_id, max_id, seed, uniform, dry_run, skip_data_check, prewarm, stages, find_max, slos, mix, affinity, mode, protocol, isolation, settings, statement_timing, reconnect_every, pool_size, read_routing, failover, faults}
                             },
-                            _ => (0, 0, 0, 0, 0),
+                            _ => RunArgs::empty(),
                         };
 
-                        (RunArgs {client: client, time: time, rampup: rampup, min_id: min_id, max_id: max_id}, InitArgs::empty(), "tpcc".to_string())
+                        (run_args, InitArgs::empty(), "tpcc".to_string())
                     },
                     _ => (RunArgs::empty(), InitArgs::empty(), "undefined".to_string()),
                 };
@@ -384,8 +1514,8 @@ impl PgMtrArgs {
 
         Ok(
             PgMtrArgs {
-                host: host.to_string(),
-                port: port,
+                hosts,
+                target_session_attrs,
                 username: username.to_string(),
                 password: password,
                 dbname: dbname.to_string(),
@@ -406,9 +1536,15 @@ pub fn get_os_username() -> String {
     String::from(os_username)
 }
 
-// Returns the database connection string based on CLI args
-pub fn get_dsn(args: &PgMtrArgs) -> String {
-    format!("postgresql://{}:\"{}\"@{}:{}/{}", args.username, args.password, encode(&args.host), args.port, args.dbname)
+// Returns the database connection string of each host, named host:port, based on CLI args
+pub fn get_dsns(args: &PgMtrArgs) -> Vec<(String, String)> {
+    args.hosts
+        .iter()
+        .map(|(host, port)| (
+            format!("{}:{}", host, port),
+            format!("postgresql://{}:\"{}\"@{}:{}/{}?application_name={}", args.username, args.password, encode(host), port, args.dbname, application_name()),
+        ))
+        .collect()
 }
 
 // Returns the database password by looking up into multiple places: environment variable, .pgpass
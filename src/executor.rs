@@ -6,6 +6,9 @@ use std::io::{BufWriter, Write};
 use std::collections::{HashMap, BTreeMap};
 use std::env::current_dir;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
 
 use chrono::{DateTime, Utc};
 use crossbeam_channel::{Sender, Receiver, unbounded};
@@ -13,7 +16,8 @@ use postgres::{Client, NoTls};
 use rand::prelude::*;
 use tokio::runtime::Runtime;
 use sqlx::PgConnection;
-use sqlx::Connection;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use sqlx::{ConnectOptions, Connection};
 use itoa;
 use ryu;
 
@@ -22,31 +26,141 @@ mod txmessage;
 mod tpcc;
 mod terminal;
 mod data_agg;
+mod copy_data;
+mod progress;
+mod disk_space;
+mod find_max;
+mod protocol;
+mod hosts;
 
 use benchmark::{
     Benchmark,
     BenchmarkStmt,
     BenchmarkTransaction,
     Counter,
+    FaultSummary,
+    LoadSummary,
+    OutageSummary,
     ReadWrite,
     ResponseTimeStatistics,
+    SizeEstimate,
+    SizeSummary,
+    HostSummary,
+    StageSummary,
+    StatementStatistics,
     TransactionSummary,
+    stream_rng,
 };
+use find_max::{SaturationSearch, WindowSamples};
+use progress::{LoadProgress, ProgressReporter};
+use protocol::StmtTimer;
+use hosts::{TargetHost, connect_first, select_host, standby_hosts};
 use txmessage::{TXMessage, TXMessageKind};
-use super::args::{RunArgs};
+use super::args::{application_name, Fault, FaultKind, InitArgs, InitStep, RunArgs, RunStage, StorageArgs, TargetSessionAttrs, TransactionMode};
+
+// Random streams of the clients start after the warehouse streams used during data loading
+const CLIENT_RNG_STREAM_BASE: u64 = 1 << 32;
+// Client states. A paused client keeps its connection open, the executor waits for pausing
+// clients to finish their current transaction.
+const CLIENT_RUNNING: u8 = 0;
+const CLIENT_PAUSING: u8 = 1;
+const CLIENT_PAUSED: u8 = 2;
+const CLIENT_STOPPED: u8 = 3;
+// Time between two state checks of a paused client, or of the executor waiting for pausing clients
+const CLIENT_PAUSE_POLL_MS: u64 = 10;
+// Time between two reconnection attempts of a client, with --failover
+const FAILOVER_RETRY_MS: u64 = 100;
+// application_name of the fault connections, which are not signaled by the faults
+const FAULT_APPLICATION_NAME: &str = "pgmtr-fault";
 
 pub struct Executor {
+    // Connection string of the host used by the init steps and the read/write clients
     dsn: String,
+    // Hosts given by --host, with the index of the one used for dsn
+    hosts: Vec<TargetHost>,
+    write_host: usize,
+    // Standbys running the read-only transactions, with --read-routing
+    read_routing: bool,
+    read_hosts: Vec<usize>,
+    target_session_attrs: TargetSessionAttrs,
+    // Clients reconnect after connection errors, with --failover
+    failover: bool,
+    // Faults injected during the run, with --fault
+    faults: Vec<Fault>,
     benchmark_type: String,
     counters: HashMap<u16, Counter>,
+    // Counters of all the transactions executed during each stage, by stage number
+    stage_counters: BTreeMap<u16, Counter>,
+    // Counters of all the transactions executed on each host, by host index
+    host_counters: BTreeMap<u16, Counter>,
+    // Load schedule of the run. With --find-max, one stage per measurement window.
+    stages: Vec<RunStage>,
+    // Saturation search, with --find-max
+    search: Option<SaturationSearch>,
+    // Transaction mix set by --mix, empty for the default mix
+    mix: Vec<(String, u16)>,
+    // Execution mode of the read/write transactions, set by --mode
+    mode: TransactionMode,
+    // Statements are timed, with --statement-timing
+    statement_timing: bool,
+    // Clients reconnect, with --connect-per-transaction or --reconnect-every
+    reconnect: bool,
+    // Connections shared by the clients, with --pool-size
+    pool: Option<PgPool>,
+    pool_size: Option<u32>,
     rampup_time_ms: u128,
     total_time_ms: u128,
     // Target directory used to store collected and aggregated data
     target_dir: PathBuf,
+    // Executed init steps: name, start time and duration in ms
+    init_report: Vec<(&'static str, DateTime<Utc>, f64)>,
 }
 
 const LOG_FILE: &str = "transaction.log";
+// Counters sent back by the data collector: by transaction, by stage and by host
+type CollectedCounters = (HashMap<u16, Counter>, BTreeMap<u16, Counter>, BTreeMap<u16, Counter>);
 const ERROR_FILE: &str = "error.log";
+// Statement durations, with --statement-timing
+const STATEMENT_LOG_FILE: &str = "statement.log";
+// Connection times, with --connect-per-transaction or --reconnect-every
+const CONNECTION_LOG_FILE: &str = "connection.log";
+// Pool wait times, with --pool-size
+const POOL_LOG_FILE: &str = "pool.log";
+// Client outages, with --failover
+const OUTAGE_LOG_FILE: &str = "outage.log";
+// Injected faults, with --fault
+const FAULT_LOG_FILE: &str = "fault.log";
+const INIT_REPORT_FILE: &str = "pgmtr-init-report.csv";
+// Number of clients bound to each object ID, with --affinity
+const AFFINITY_FILE: &str = "pgmtr-affinity.csv";
+// SQLSTATE of the serialization failures
+const SERIALIZATION_FAILURE_SQLSTATE: &str = "40001";
+// SQLSTATEs of the server shutdowns and of the writes on a host turned read-only, in addition to
+// the connection exceptions of class 08
+const CONNECTION_FAILURE_SQLSTATES: [&str; 4] = ["57P01", "57P02", "57P03", "25006"];
+// Free space ratio above which the estimated dataset size raises a warning
+const SPACE_WARNING_RATIO: f64 = 0.9;
+
+// Serialization failures are expected with the repeatable read and serializable isolation levels
+fn is_serialization_failure(error: &(dyn std::error::Error + 'static)) -> bool {
+    match error.downcast_ref::<sqlx::Error>().and_then(|e| e.as_database_error()) {
+        Some(db_error) => db_error.code().as_deref() == Some(SERIALIZATION_FAILURE_SQLSTATE),
+        None => false,
+    }
+}
+
+// Errors after which the connection cannot run the read/write transactions anymore: lost
+// connection, server shutdown, or host demoted by a failover
+fn is_connection_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    match error.downcast_ref::<sqlx::Error>() {
+        Some(sqlx::Error::Database(db_error)) => match db_error.code() {
+            Some(code) => code.starts_with("08") || CONNECTION_FAILURE_SQLSTATES.contains(&code.as_ref()),
+            None => false,
+        },
+        Some(sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::Protocol(_)) => true,
+        _ => false,
+    }
+}
 
 pub fn get_target_dir_path() -> PathBuf {
     let current_dir = match current_dir() {
@@ -66,32 +180,488 @@ impl Executor {
     pub fn new(dsn: String, benchmark_type: String) -> Executor {
         Executor {
             dsn: dsn,
+            hosts: Vec::new(),
+            write_host: 0,
+            read_routing: false,
+            read_hosts: Vec::new(),
+            target_session_attrs: TargetSessionAttrs::Any,
+            failover: false,
+            faults: Vec::new(),
             benchmark_type: benchmark_type,
             counters: HashMap::new(),
+            stage_counters: BTreeMap::new(),
+            host_counters: BTreeMap::new(),
+            stages: Vec::new(),
+            search: None,
+            mix: Vec::new(),
+            mode: TransactionMode::Statement,
+            statement_timing: false,
+            reconnect: false,
+            pool: None,
+            pool_size: None,
             total_time_ms: 0,
             rampup_time_ms: 0,
             target_dir: get_target_dir_path(),
+            init_report: Vec::new(),
+        }
+    }
+
+    // Execute the init steps, in the given order
+    pub fn run_init_steps(&mut self, args: &InitArgs) -> &mut Self {
+        if args.steps.contains(&InitStep::Load) && !args.skip_space_check {
+            self.check_disk_space(args);
+        }
+
+        for step in args.steps.iter() {
+            let started_at = Utc::now();
+            let start = Instant::now();
+
+            match step {
+                InitStep::Schema => self.init_db_schema(&args.storage),
+                InitStep::Truncate => self.truncate_tables(),
+                InitStep::Load => self.load_data(args),
+                InitStep::Pkeys => self.add_primary_keys(args.jobs, &args.storage),
+                InitStep::Fkeys => self.add_foreign_keys(args.jobs),
+                InitStep::Indexes => self.add_indexes(args.jobs, &args.storage),
+                InitStep::Procedures => self.add_procedures(),
+                InitStep::Vacuum => self.vacuum(args.jobs),
+                InitStep::Checkpoint => self.checkpoint(),
+            };
+
+            self.init_report.push((step.name(), started_at, start.elapsed().as_micros() as f64 / 1000_f64));
+        }
+
+        self
+    }
+
+    // Write the duration of each init step into the init report file
+    pub fn write_init_report(&mut self) -> &mut Self {
+        let start = Instant::now();
+        let target_dir_name = match self.target_dir.file_name() {
+            Some(dir_name) => dir_name.to_str().unwrap(),
+            None => "Should not happen",
+        };
+        let message = format!("Writing the init report. {}", target_dir_name);
+        terminal::start_msg("INIT", message.as_str());
+
+        let mut report = String::from("step,start,duration_ms\n");
+        for (step, started_at, duration_ms) in self.init_report.iter() {
+            report.push_str(&format!("{},{},{:.3}\n", step, started_at.format("%Y-%m-%dT%H:%M:%S%.3f"), duration_ms));
+        }
+        let written = create_dir_all(&self.target_dir)
+            .and_then(|_| File::create(self.target_dir.join(INIT_REPORT_FILE)))
+            .and_then(|mut file| file.write_all(report.as_bytes()));
+        match written {
+            Ok(_) => (),
+            Err(error) => {
+                terminal::err_msg(format!("{}", error).as_str());
+                std::process::exit(1);
+            }
+        };
+        terminal::done_msg(start.elapsed().as_micros() as f64 / 1000_f64);
+
+        self
+    }
+
+    // Print the estimated size of the tables and indexes, without connecting to the database
+    pub fn print_estimate(&mut self, args: &InitArgs) -> &mut Self {
+        let benchmark = self.get_benchmark(args.scalefactor, 0, 0);
+        let to_mb = |bytes: u64| bytes as f64 / 1024_f64 / 1024_f64;
+
+        let mut size_summary = Vec::new();
+        let mut total = SizeEstimate {table: "Total".to_string(), rows: 0, table_bytes: 0, pkey_bytes: 0, index_bytes: 0};
+        for estimate in benchmark.estimate_sizes(&args.storage) {
+            total.rows += estimate.rows;
+            total.table_bytes += estimate.table_bytes;
+            total.pkey_bytes += estimate.pkey_bytes;
+            total.index_bytes += estimate.index_bytes;
+            size_summary.push(estimate);
+        }
+        size_summary.push(total);
+
+        println!("Estimated dataset size, scale factor {}:", args.scalefactor);
+        let size_summary: Vec<SizeSummary> = size_summary
+            .into_iter()
+            .map(|estimate| SizeSummary {
+                table: estimate.table,
+                rows: estimate.rows,
+                table_mb: to_mb(estimate.table_bytes),
+                pkey_mb: to_mb(estimate.pkey_bytes),
+                index_mb: to_mb(estimate.index_bytes),
+                total_mb: to_mb(estimate.table_bytes + estimate.pkey_bytes + estimate.index_bytes),
+            })
+            .collect();
+        data_agg::print_size_summary(&size_summary);
+
+        self
+    }
+
+    // Compare the estimated size of the data created by the init steps with the free space of the
+    // target tablespaces. Tables dropped or truncated beforehand give their space back. Free space
+    // is only known when the server runs on this host: exits when it is too small, warns when it
+    // is almost used up or cannot be checked.
+    pub fn check_disk_space(&mut self, args: &InitArgs) -> &mut Self {
+        let start = Instant::now();
+        terminal::start_msg("INIT", "Checking disk space");
+
+        let benchmark = self.get_benchmark(args.scalefactor, 0, 0);
+        let mut client = Executor::connect(self.dsn.clone());
+        let fail = |error: postgres::Error| -> ! {
+            terminal::err_msg(format!("{}", error).as_str());
+            std::process::exit(1);
+        };
+
+        // Tablespace used when none is set
+        let default_tablespace: String = match client.query_one(r"
+            SELECT t.spcname
+            FROM pg_tablespace t
+            JOIN pg_database d ON d.dattablespace = t.oid
+            WHERE d.datname = current_database()
+            ", &[]) {
+            Ok(row) => row.get(0),
+            Err(error) => fail(error),
+        };
+        let tablespace = |name: &Option<String>| name.clone().unwrap_or_else(|| default_tablespace.clone());
+
+        // Estimated size per tablespace
+        let mut required: BTreeMap<String, u64> = BTreeMap::new();
+        for estimate in benchmark.estimate_sizes(&args.storage) {
+            *required.entry(tablespace(&args.storage.table(&estimate.table).tablespace)).or_default() += estimate.table_bytes;
+            if args.steps.contains(&InitStep::Pkeys) {
+                *required.entry(tablespace(&args.storage.pkeys.tablespace)).or_default() += estimate.pkey_bytes;
+            }
+            if args.steps.contains(&InitStep::Indexes) {
+                *required.entry(tablespace(&args.storage.indexes.tablespace)).or_default() += estimate.index_bytes;
+            }
+        }
+
+        // Space used by the existing tables and indexes, released by DROP TABLE or TRUNCATE
+        if args.steps.contains(&InitStep::Schema) || args.steps.contains(&InitStep::Truncate) {
+            let tables: Vec<String> = benchmark.get_tables().into_iter().map(|table| table.name).collect();
+            let rows = match client.query(r"
+                WITH tables AS (
+                    SELECT oid FROM pg_class
+                    WHERE relname = ANY($1) AND relkind = 'r' AND pg_table_is_visible(oid)
+                ), relations AS (
+                    SELECT oid, pg_table_size(oid) AS size FROM tables
+                    UNION ALL
+                    SELECT indexrelid, pg_relation_size(indexrelid) FROM pg_index
+                    WHERE indrelid IN (SELECT oid FROM tables)
+                )
+                SELECT t.spcname, sum(r.size)::BIGINT
+                FROM relations r
+                JOIN pg_class c ON c.oid = r.oid
+                JOIN pg_tablespace t ON t.oid = COALESCE(NULLIF(c.reltablespace, 0), (SELECT dattablespace FROM pg_database WHERE datname = current_database()))
+                GROUP BY t.spcname
+                ", &[&tables]) {
+                Ok(rows) => rows,
+                Err(error) => fail(error),
+            };
+            for row in rows {
+                let name: String = row.get(0);
+                let released: i64 = row.get(1);
+                if let Some(bytes) = required.get_mut(&name) {
+                    *bytes = bytes.saturating_sub(released as u64);
+                }
+            }
+        }
+
+        // Unix socket and loopback connections mean the server runs on this host
+        let local: bool = match client.query_one("SELECT COALESCE(host(inet_server_addr()) IN ('127.0.0.1', '::1'), true)", &[]) {
+            Ok(row) => row.get(0),
+            Err(error) => fail(error),
+        };
+        // Only readable by superusers and pg_read_all_settings members
+        let data_directory: Option<String> = client
+            .query_one("SELECT current_setting('data_directory')", &[])
+            .ok()
+            .map(|row| row.get(0));
+
+        let to_mb = |bytes: u64| bytes as f64 / 1024_f64 / 1024_f64;
+        let mut warnings = Vec::new();
+        // Required and free space per file system, with the tablespaces it holds
+        let mut file_systems: BTreeMap<u64, (u64, u64, Vec<String>)> = BTreeMap::new();
+        for (name, bytes) in required.iter() {
+            let row = match client.query_one(r"
+                SELECT pg_tablespace_location(oid), pg_tablespace_size(oid)
+                FROM pg_tablespace
+                WHERE spcname = $1
+                ", &[name]) {
+                Ok(row) => row,
+                Err(error) => fail(error),
+            };
+            let location: String = row.get(0);
+            let used: i64 = row.get(1);
+            // Built-in tablespaces live in the data directory
+            let location = match location.is_empty() {
+                true => data_directory.clone(),
+                false => Some(location),
+            };
+            let free_space = match (local, location) {
+                (true, Some(location)) => disk_space::free_space(&location),
+                (true, None) => Err("data directory location unknown".to_string()),
+                (false, _) => Err("remote server".to_string()),
+            };
+            match free_space {
+                Ok((fsid, free)) => {
+                    let file_system = file_systems.entry(fsid).or_insert((0, free, Vec::new()));
+                    file_system.0 += bytes;
+                    file_system.2.push(name.clone());
+                },
+                Err(error) => warnings.push(format!(
+                    "could not check free space of tablespace {} ({}): {:.0} MB estimated, {:.0} MB used",
+                    name, error, to_mb(*bytes), to_mb(used as u64),
+                )),
+            }
+        }
+
+        for (required, free, names) in file_systems.values() {
+            let message = format!(
+                "{:.0} MB estimated, {:.0} MB free for tablespace {}",
+                to_mb(*required), to_mb(*free), names.join(", "),
+            );
+            if required > free {
+                terminal::err_msg(format!("not enough disk space: {}. Use --skip-space-check to load data anyway", message).as_str());
+                std::process::exit(1);
+            }
+            if *required as f64 > *free as f64 * SPACE_WARNING_RATIO {
+                warnings.push(format!("disk space almost used up: {}", message));
+            }
+        }
+
+        if warnings.is_empty() {
+            terminal::done_msg(start.elapsed().as_micros() as f64 / 1000_f64);
+        }
+        else {
+            terminal::warn_msg(warnings.join("\n").as_str());
+        }
+
+        self
+    }
+
+    // Print the statements of the init steps, in the given order, without connecting to the
+    // database. The output is a SQL script where each step starts with a comment.
+    pub fn print_init_steps(&mut self, args: &InitArgs) -> &mut Self {
+        let benchmark = self.get_benchmark(args.scalefactor, 0, 0);
+
+        println!("-- Dry run: init steps of the {} benchmark, scale factor {}", self.benchmark_type, args.scalefactor);
+        for step in args.steps.iter() {
+            println!();
+            println!("-- Step: {}", step.name());
+            let stmts = match step {
+                InitStep::Schema => benchmark.get_table_ddls(&args.storage),
+                InitStep::Truncate => vec![Executor::truncate_stmt(&benchmark)],
+                InitStep::Load => {
+                    // Load statements come with their estimated row counts
+                    println!("-- Data is loaded by {} jobs, per-warehouse statements are executed once per warehouse", args.jobs);
+                    let mut total_rows: u64 = 0;
+                    for load_stmt in benchmark.get_load_stmts(args) {
+                        if let Some(table) = &load_stmt.table {
+                            println!("-- {}: {} rows (estimated)", table, load_stmt.rows);
+                            total_rows += load_stmt.rows;
+                        }
+                        println!("{}", Executor::format_stmt(&load_stmt.stmt.sql));
+                    }
+                    println!("-- Total: {} rows (estimated)", total_rows);
+                    Vec::new()
+                },
+                InitStep::Pkeys => benchmark.get_pkey_ddls(&args.storage),
+                InitStep::Fkeys => benchmark.get_fkey_ddls(),
+                InitStep::Indexes => benchmark.get_index_ddls(&args.storage),
+                InitStep::Procedures => benchmark.get_procedure_ddls(),
+                InitStep::Vacuum => benchmark.get_vacuum_stmts(),
+                InitStep::Checkpoint => vec![BenchmarkStmt { sql: "CHECKPOINT".to_string() }],
+            };
+            for stmt in stmts.iter() {
+                println!("{}", Executor::format_stmt(&stmt.sql));
+            }
+        }
+
+        self
+    }
+
+    // Replace the transaction mix of the read/write workload. Unknown transaction names are
+    // rejected before anything else is done.
+    pub fn set_mix(&mut self, mix: &[(String, u16)]) -> &mut Self {
+        self.mix = mix.to_vec();
+        self.get_benchmark(0, 0, 0);
+
+        self
+    }
+
+    // Set the hosts given by --host. With several hosts, or a target_session_attrs other than
+    // any, the role of each host is probed to select the one to connect to.
+    pub fn set_hosts(&mut self, hosts: Vec<(String, String)>, attrs: TargetSessionAttrs) -> &mut Self {
+        self.hosts = hosts.into_iter().map(|(name, dsn)| TargetHost::new(name, dsn)).collect();
+        self.target_session_attrs = attrs;
+        if self.hosts.len() > 1 || attrs != TargetSessionAttrs::Any {
+            self.probe_hosts();
+        }
+        match select_host(&self.hosts, attrs) {
+            Some(i) => {
+                self.write_host = i;
+                self.dsn = self.hosts[i].dsn.clone();
+            },
+            None => {
+                eprintln!("ERROR: no host matches target_session_attrs={}", attrs.name());
+                std::process::exit(1);
+            }
+        }
+
+        self
+    }
+
+    fn probe_hosts(&mut self) {
+        for host in self.hosts.iter_mut() {
+            host.probe();
+        }
+    }
+
+    // Send the read/write transactions to the first primary, and spread the clients over the
+    // standbys for the read-only transactions
+    fn route_reads(&mut self) {
+        terminal::start_msg("RUN", "Routing the read-only transactions to the standbys");
+        let start = Instant::now();
+        if self.hosts.iter().any(|host| host.role.is_none()) {
+            self.probe_hosts();
+        }
+        match select_host(&self.hosts, TargetSessionAttrs::Primary) {
+            Some(i) => {
+                self.write_host = i;
+                self.dsn = self.hosts[i].dsn.clone();
+            },
+            None => {
+                terminal::err_msg("no primary found in the host list");
+                std::process::exit(1);
+            }
+        }
+        self.read_hosts = standby_hosts(&self.hosts);
+        if self.read_hosts.is_empty() {
+            terminal::warn_msg("no standby found in the host list, the read-only transactions run on the primary");
+        }
+        else {
+            terminal::done_msg(start.elapsed().as_micros() as f64 / 1000_f64);
+        }
+    }
+
+    // Set the execution mode of the read/write transactions
+    pub fn set_mode(&mut self, mode: TransactionMode) -> &mut Self {
+        self.mode = mode;
+
+        self
+    }
+
+    // Print the SQL templates of every read/write transaction, without connecting to the database
+    pub fn print_transactions(&mut self) -> &mut Self {
+        let benchmark = self.get_benchmark(0, 0, 0);
+        // A function call is a transaction on its own
+        let explicit_transaction = self.mode == TransactionMode::Statement;
+
+        println!("-- Dry run: read/write transactions of the {} benchmark", self.benchmark_type);
+        if explicit_transaction {
+            println!("-- Statements are listed in execution order, some of them are conditional or repeated");
+        }
+        else {
+            println!("-- Each transaction calls the function created by the procedures init step");
+        }
+        for transaction in benchmark.get_transactions_rw().iter() {
+            println!();
+            println!("-- {}: {}, weight {}", transaction.name, transaction.description, transaction.weight);
+            if explicit_transaction {
+                println!("BEGIN;");
+            }
+            for stmt in benchmark.get_transaction_stmts(transaction).iter() {
+                println!("{}", Executor::format_stmt(&stmt.sql));
+            }
+            if explicit_transaction {
+                println!("COMMIT;");
+            }
+        }
+
+        self
+    }
+
+    // Format one statement for printing: leading and trailing blank lines are removed, the
+    // following lines are shifted left as much as possible and a semicolon ends the statement.
+    fn format_stmt(sql: &str) -> String {
+        let sql = sql.trim();
+        let mut lines = sql.lines();
+        let first = lines.next().unwrap_or_default();
+        let rest: Vec<&str> = lines.collect();
+        let indent = rest
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+
+        let mut formatted = String::from(first);
+        for line in rest {
+            formatted.push('\n');
+            formatted.push_str(line.get(indent..).unwrap_or_default());
         }
+        formatted.push(';');
+
+        formatted
     }
 
     // Execute read/write mixed workload
     pub fn run_benchmark(&mut self, args :RunArgs) -> &mut Self {
         let rampup_ms = args.rampup as u64 * 1000;
-        let time_ms = args.time as u64 * 1000;
-        // Nap time before starting a new client
-        let sleep_ms = rampup_ms / args.client as u64;
+        self.statement_timing = args.statement_timing;
+        self.reconnect = args.reconnect_every.is_some();
+        self.pool_size = args.pool_size;
+        self.failover = args.failover;
+        self.faults = args.faults.clone();
+        // Duration of the measurement windows of the saturation search
+        let window_time = args.stages[0].time;
+        if args.find_max {
+            let transactions = self.get_benchmark(0, 0, 0).get_transactions_rw();
+            match SaturationSearch::new(&args.slos, &transactions, args.client) {
+                Ok(search) => self.search = Some(search),
+                Err(error) => {
+                    terminal::err_msg(error.as_str());
+                    std::process::exit(1);
+                }
+            }
+            self.stages = Vec::new();
+        }
+        else {
+            self.stages = args.stages.clone();
+        }
+        // Number of clients started during the rampup
+        let first_clients = match self.search.as_ref() {
+            Some(search) => search.next_clients().unwrap_or(1),
+            None => self.stages[0].clients,
+        };
+        // Nap time before starting a new client of the first stage
+        let sleep_ms = rampup_ms / first_clients as u64;
 
         // Channels used to communicate transactions states: id, duration, committed?, etc..
         let (tx, rx): (Sender<TXMessage>, Receiver<TXMessage>) = unbounded();
-        // Channels used to send back the counters once data collector has finished its work.
-        let (tx_counters, rx_counters): (Sender<HashMap<u16, Counter>>, Receiver<HashMap<u16, Counter>>) = unbounded();
+        // Channels used to send back the counters, by transaction and by stage, once data
+        // collector has finished its work.
+        let (tx_counters, rx_counters): (Sender<CollectedCounters>, Receiver<CollectedCounters>) = unbounded();
+
+        // Channel used by the data collector to send back the response times of each measurement
+        // window of the saturation search
+        let (tx_samples, rx_samples): (Sender<WindowSamples>, Receiver<WindowSamples>) = unbounded();
 
-        let mut benchmark_clients = Vec::new();
+        // Started clients, with their state
+        let mut benchmark_clients: Vec<(tokio::task::JoinHandle<()>, Arc<AtomicU8>)> = Vec::new();
 
         // Track total execution time in ms
         let start = Instant::now();
         let command = "RUN";
 
+        self.read_routing = args.read_routing;
+        if args.read_routing {
+            self.route_reads();
+        }
+        if !args.skip_data_check {
+            self.check_dataset(args.mode);
+        }
+
         // Create the target dir
         let target_dir_name = match self.target_dir.file_name() {
             Some(dir_name) => dir_name.to_str().unwrap(),
@@ -110,7 +680,11 @@ impl Executor {
 
         // Start data collector
         let dc_tx_counters = tx_counters.clone();
-        let data_collector = self.start_data_collector(rx, dc_tx_counters);
+        let dc_tx_samples = match args.find_max {
+            true => Some(tx_samples),
+            false => None,
+        };
+        let data_collector = self.start_data_collector(rx, dc_tx_counters, dc_tx_samples);
         // Let's find the maximum object id if --max-id is set to 0 (default behavior)
         let max_id :u32 = match args.max_id {
             0 => {
@@ -133,43 +707,145 @@ impl Executor {
             _default => args.max_id.clone(),
         };
 
-        let message = format!("Starting {} client(s) in {} seconds", args.client, args.rampup);
+        if !args.skip_data_check {
+            let start_ids = Instant::now();
+            let message = format!("Checking object IDs from {} to {}", args.min_id, max_id);
+            terminal::start_msg(command, message.as_str());
+            let mut client = Executor::connect(self.dsn.clone());
+            let benchmark_client = self.get_benchmark(0, 0, 0);
+
+            match benchmark_client.check_ids(&mut client, args.min_id, max_id) {
+                Ok(_) => (),
+                Err(error) => {
+                    terminal::err_msg(format!("{}. Use --skip-data-check to run anyway", error).as_str());
+                    std::process::exit(1);
+                }
+            }
+            terminal::done_msg(start_ids.elapsed().as_micros() as f64 / 1000_f64);
+        }
+
+        if args.prewarm {
+            self.prewarm(args.client as u32, args.min_id, max_id);
+        }
+        if args.affinity {
+            self.report_affinity(args.client as u32, args.min_id, max_id);
+        }
+        // Prewarming can take a while, keep it out of the workload execution time
+        let start = Instant::now();
+
+        let message = format!("Starting {} client(s) in {} seconds", first_clients, args.rampup);
         terminal::start_msg(command, message.as_str());
 
         // Create the tokio runtime
         let rt = Runtime::new().unwrap();
+        let stages = self.stages.clone();
 
         rt.block_on(async {
-            // Start the clients
-            for client_id in 1..=args.client {
-                // Test duration calculated by taking in consideration the rampup time and the
-                // remaining duration before the end of rampup stage.
-                let duration_ms = time_ms + rampup_ms - client_id as u64 * sleep_ms;
-
+            if let Some(size) = args.pool_size {
+                self.open_pool(&args, size).await;
+            }
+            // The times of the faults are counted from the start of the clients
+            let fault_injector = match self.faults.is_empty() {
+                true => None,
+                false => {
+                    let state = Arc::new(AtomicU8::new(CLIENT_RUNNING));
+                    Some((self.start_fault_injector(tx.clone(), state.clone()), state))
+                },
+            };
+            // Start the clients of the first stage
+            for _ in 0..first_clients {
                 // Sleep accordingly to the rampup time and the number of clients
                 sleep(Duration::from_millis(sleep_ms));
 
                 // Start one new client
-                let benchmark_client = self.start_rw_client(duration_ms, self.dsn.clone(), args.min_id.clone(), max_id.clone(), tx.clone(), client_id as u32).await;
-
-                benchmark_clients.push(benchmark_client);
+                let client_id = benchmark_clients.len() as u32 + 1;
+                let state = Arc::new(AtomicU8::new(CLIENT_RUNNING));
+                let benchmark_client = self.start_rw_client(&args, max_id, tx.clone(), client_id, state.clone()).await;
+                benchmark_clients.push((benchmark_client, state));
             }
             // All clients have been started
             terminal::done_msg(start.elapsed().as_micros() as f64 / 1000 as f64);
 
-            let message2 = format!("Running the workload for {} seconds", args.time);
-            terminal::start_msg(command, message2.as_str());
-            let start2 = Instant::now();
-
             // Send end-of-rampup message to the data collector
             tx.send(TXMessage::end_of_rampup()).unwrap();
             self.rampup_time_ms = start.elapsed().as_millis();
 
-            for benchmark_client in benchmark_clients {
-                benchmark_client.await.expect("the client thread panicked");
+            let mut stage_id: u16 = 0;
+            loop {
+                // Number of clients and duration of the next stage, or of the next measurement
+                // window of the saturation search
+                let (clients, time) = match self.search.as_ref() {
+                    Some(search) => match search.next_clients() {
+                        Some(clients) => (clients, window_time),
+                        None => break,
+                    },
+                    None => match stages.get(stage_id as usize) {
+                        Some(stage) => (stage.clients, stage.time),
+                        None => break,
+                    },
+                };
+                stage_id += 1;
+
+                let start_stage = Instant::now();
+                let message = if self.search.is_some() {
+                    format!("Measuring window {} with {} client(s) for {} seconds", stage_id, clients, time)
+                }
+                else if stages.len() == 1 {
+                    format!("Running the workload for {} seconds", time)
+                }
+                else {
+                    format!("Running stage {}/{} with {} client(s) for {} seconds", stage_id, stages.len(), clients, time)
+                };
+                terminal::start_msg(command, message.as_str());
+
+                // Resume, pause or start clients. Pausing clients finish their current transaction
+                // first, paused clients keep their connection for the next stages.
+                for (i, (_, state)) in benchmark_clients.iter().enumerate() {
+                    if i < clients as usize {
+                        state.store(CLIENT_RUNNING, Ordering::Relaxed);
+                    }
+                    else if state.load(Ordering::Relaxed) == CLIENT_RUNNING {
+                        state.store(CLIENT_PAUSING, Ordering::Relaxed);
+                    }
+                }
+                while benchmark_clients.len() < clients as usize {
+                    let client_id = benchmark_clients.len() as u32 + 1;
+                    let state = Arc::new(AtomicU8::new(CLIENT_RUNNING));
+                    let benchmark_client = self.start_rw_client(&args, max_id, tx.clone(), client_id, state.clone()).await;
+                    benchmark_clients.push((benchmark_client, state));
+                }
+                while benchmark_clients.iter().any(|(_, state)| state.load(Ordering::Relaxed) == CLIENT_PAUSING) {
+                    tokio::time::sleep(Duration::from_millis(CLIENT_PAUSE_POLL_MS)).await;
+                }
+
+                // Transactions are tagged with the stage number until the end of the stage
+                tx.send(TXMessage::start_of_stage(stage_id, clients as u32)).unwrap();
+                sleep(Duration::from_millis(time as u64 * 1000));
+                tx.send(TXMessage::end_of_stage()).unwrap();
+
+                if let Some(search) = self.search.as_mut() {
+                    let mut samples = rx_samples.recv().unwrap();
+                    search.record(clients, &mut samples, time as f64);
+                    self.stages.push(RunStage {clients, time});
+                }
+
+                terminal::done_msg(start_stage.elapsed().as_micros() as f64 / 1000_f64);
             }
 
-            terminal::done_msg(start2.elapsed().as_micros() as f64 / 1000 as f64);
+            // Stop the fault injector first, the clients may be waiting for one of its locks
+            if let Some((fault_injector, state)) = fault_injector {
+                state.store(CLIENT_STOPPED, Ordering::Relaxed);
+                fault_injector.await.expect("the fault injector panicked");
+            }
+            for (_, state) in benchmark_clients.iter() {
+                state.store(CLIENT_STOPPED, Ordering::Relaxed);
+            }
+            for (benchmark_client, _) in benchmark_clients {
+                benchmark_client.await.expect("the client thread panicked");
+            }
+            if let Some(pool) = self.pool.take() {
+                pool.close().await;
+            }
         });
 
         // Proceed total execution time
@@ -181,65 +857,607 @@ impl Executor {
         data_collector.join().expect("the data collector thread panicked");
 
         // Receive counters from the data collector
-        self.counters = rx_counters.recv().unwrap();
+        (self.counters, self.stage_counters, self.host_counters) = rx_counters.recv().unwrap();
 
         self
     }
 
-    // Start a new read/write benchmark client in its own thread
-    async fn start_rw_client(&mut self, duration_ms: u64, dsn: String, min_id: u32, max_id: u32, tx: Sender<TXMessage>, client_id: u32) -> tokio::task::JoinHandle<()>
+    // Report how many clients are bound to each object ID of the range with --affinity. Clients
+    // added by later stages are included. The details are written to AFFINITY_FILE.
+    fn report_affinity(&mut self, n_clients: u32, min_id: u32, max_id: u32) {
+        let start = Instant::now();
+        let message = format!("Binding {} client(s) to object IDs from {} to {}", n_clients, min_id, max_id);
+        terminal::start_msg("RUN", message.as_str());
+
+        let benchmark = self.get_benchmark(0, min_id, max_id);
+        let mut clients_by_id: BTreeMap<u32, u32> = (min_id..=max_id).map(|id| (id, 0)).collect();
+        for client_id in 1..=n_clients {
+            let (id, _) = benchmark.get_client_home(client_id);
+            *clients_by_id.entry(id).or_insert(0) += 1;
+        }
+
+        let mut report = String::from("object_id,clients\n");
+        for (id, clients) in clients_by_id.iter() {
+            report.push_str(&format!("{},{}\n", id, clients));
+        }
+        match File::create(self.target_dir.join(AFFINITY_FILE)).and_then(|mut file| file.write_all(report.as_bytes())) {
+            Ok(_) => (),
+            Err(error) => {
+                terminal::err_msg(format!("could not write {}: {}", AFFINITY_FILE, error).as_str());
+                std::process::exit(1);
+            }
+        }
+        terminal::done_msg(start.elapsed().as_micros() as f64 / 1000_f64);
+
+        // Number of object IDs by number of bound clients, most loaded first
+        let mut ids_by_clients: BTreeMap<u32, u32> = BTreeMap::new();
+        for clients in clients_by_id.values() {
+            *ids_by_clients.entry(*clients).or_insert(0) += 1;
+        }
+        let summary: Vec<String> = ids_by_clients
+            .iter()
+            .rev()
+            .map(|(clients, ids)| format!("{} object ID(s) with {} client(s)", ids, clients))
+            .collect();
+        println!("Clients per object ID: {}", summary.join(", "));
+    }
+
+    // Load the tables and indexes of the object IDs range into shared buffers, using n_jobs
+    // connections. pg_prewarm loads whole relations, so it is only used when the range covers
+    // every object ID, table and index scans are used otherwise.
+    fn prewarm(&mut self, n_jobs: u32, min_id: u32, max_id: u32) {
+        let start = Instant::now();
+        let mut client = Executor::connect(self.dsn.clone());
+        let benchmark = self.get_benchmark(0, 0, 0);
+
+        let default_max_id = match benchmark.get_default_max_id(&mut client) {
+            Ok(max_id) => max_id,
+            Err(error) => {
+                terminal::err_msg(format!("{}", error).as_str());
+                std::process::exit(1);
+            }
+        };
+        let pg_prewarm = match client.query_one("SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'pg_prewarm')", &[]) {
+            Ok(row) => row.get::<_, bool>(0),
+            Err(error) => {
+                terminal::err_msg(format!("{}", error).as_str());
+                std::process::exit(1);
+            }
+        };
+
+        let (method, stmts) = if pg_prewarm && min_id <= 1 && max_id >= default_max_id {
+            let stmts = benchmark.get_tables()
+                .iter()
+                .map(|table| BenchmarkStmt {
+                    sql: format!(
+                        "SELECT pg_prewarm('{0}'), (SELECT sum(pg_prewarm(indexrelid)) FROM pg_index WHERE indrelid = '{0}'::regclass)",
+                        table.name,
+                    ),
+                })
+                .collect();
+            ("pg_prewarm", stmts)
+        }
+        else {
+            ("table and index scans", benchmark.get_prewarm_stmts(min_id, max_id))
+        };
+
+        let message = format!("Prewarming object IDs from {} to {} using {}", min_id, max_id, method);
+        terminal::start_msg("RUN", message.as_str());
+        let n_jobs = n_jobs.min(stmts.len() as u32).max(1);
+        self.exec_stmts(n_jobs, stmts, true);
+        terminal::done_msg(start.elapsed().as_micros() as f64 / 1000_f64);
+    }
+
+    // Check that the tables, primary keys and additional indexes exist before starting the
+    // clients: without them, clients would fail or crawl. In procedure mode, the transaction
+    // functions must exist too. Warns about tables whose statistics are missing or outdated since
+    // the data has been loaded.
+    fn check_dataset(&mut self, mode: TransactionMode) {
+        let start = Instant::now();
+        terminal::start_msg("RUN", "Checking the dataset");
+
+        let benchmark = self.get_benchmark(0, 0, 0);
+        let mut client = Executor::connect(self.dsn.clone());
+        let fail = |error: postgres::Error| -> ! {
+            terminal::err_msg(format!("{}", error).as_str());
+            std::process::exit(1);
+        };
+        // Names found in the catalog, from the list of expected names
+        let mut found = |sql: &str, names: &Vec<String>| -> Vec<String> {
+            match client.query(sql, &[names]) {
+                Ok(rows) => rows.iter().map(|row| row.get(0)).collect(),
+                Err(error) => fail(error),
+            }
+        };
+        let missing = |names: &Vec<String>, found: &Vec<String>| -> Vec<String> {
+            names.iter().filter(|name| !found.contains(name)).cloned().collect()
+        };
+
+        let tables: Vec<String> = benchmark.get_tables().into_iter().map(|table| table.name).collect();
+        let pkey_tables: Vec<String> = benchmark.get_pkeys().into_iter().map(|pkey| pkey.table).collect();
+        let indexes: Vec<String> = benchmark.get_indexes().into_iter().map(|index| index.name).collect();
+        let procedures: Vec<String> = match mode {
+            TransactionMode::Procedure => benchmark.get_procedure_names(),
+            TransactionMode::Statement => Vec::new(),
+        };
+
+        let found_tables = found(r"
+            SELECT relname::TEXT FROM pg_class
+            WHERE relname = ANY($1) AND relkind IN ('r', 'p') AND pg_table_is_visible(oid)
+            ", &tables);
+        // Primary keys are looked up by table, their name does not matter
+        let found_pkey_tables = found(r"
+            SELECT c.relname::TEXT FROM pg_index i
+            JOIN pg_class c ON c.oid = i.indrelid
+            WHERE c.relname = ANY($1) AND i.indisprimary AND i.indisvalid AND pg_table_is_visible(c.oid)
+            ", &pkey_tables);
+        let found_indexes = found(r"
+            SELECT c.relname::TEXT FROM pg_index i
+            JOIN pg_class c ON c.oid = i.indexrelid
+            WHERE c.relname = ANY($1) AND i.indisvalid AND pg_table_is_visible(c.oid)
+            ", &indexes);
+        let found_procedures = found(r"
+            SELECT proname::TEXT FROM pg_proc
+            WHERE proname = ANY($1) AND pg_function_is_visible(oid)
+            ", &procedures);
+        // Statistics are outdated when autovacuum would analyze the table
+        let outdated_tables = found(r"
+            SELECT s.relname::TEXT FROM pg_stat_user_tables s
+            JOIN pg_class c ON c.oid = s.relid
+            WHERE
+                s.relname = ANY($1)
+                AND pg_table_is_visible(s.relid)
+                AND (
+                    GREATEST(s.last_analyze, s.last_autoanalyze) IS NULL
+                    OR s.n_mod_since_analyze > current_setting('autovacuum_analyze_threshold')::FLOAT8
+                        + current_setting('autovacuum_analyze_scale_factor')::FLOAT8 * GREATEST(c.reltuples, 0)
+                )
+            ORDER BY s.relname
+            ", &tables);
+
+        let mut errors = Vec::new();
+        for (kind, names) in [
+            ("tables", missing(&tables, &found_tables)),
+            ("primary keys on tables", missing(&pkey_tables, &found_pkey_tables)),
+            ("indexes", missing(&indexes, &found_indexes)),
+            ("functions (created by the procedures init step)", missing(&procedures, &found_procedures)),
+        ] {
+            if !names.is_empty() {
+                errors.push(format!("missing {}: {}", kind, names.join(", ")));
+            }
+        }
+        if !errors.is_empty() {
+            terminal::err_msg(format!("{}. Use --skip-data-check to run anyway", errors.join("; ")).as_str());
+            std::process::exit(1);
+        }
+
+        if outdated_tables.is_empty() {
+            terminal::done_msg(start.elapsed().as_micros() as f64 / 1000_f64);
+        }
+        else {
+            terminal::warn_msg(format!("tables not analyzed since their data was loaded or modified: {}", outdated_tables.join(", ")).as_str());
+        }
+    }
+
+    // Start a new read/write benchmark client in its own thread. The client runs until its state
+    // is set to CLIENT_STOPPED.
+    async fn start_rw_client(&mut self, args: &RunArgs, max_id: u32, tx: Sender<TXMessage>, client_id: u32, state: Arc<AtomicU8>) -> tokio::task::JoinHandle<()>
     {
         // Create a new benchmark object by thread because we don't want to share a such
         // complex structure between all the client threads
-        let benchmark_client = self.get_benchmark(0, min_id, max_id);
+        let mut benchmark_client = self.get_benchmark(0, args.min_id, max_id);
+        let mut dsn = self.dsn.clone();
+        let args = args.clone();
+        let pool = self.pool.clone();
+        let mut write_host = self.write_host as u16;
+        // Host list resolved again when the clients reconnect after a connection error. The
+        // read/write transactions need a writable host.
+        let dsns: Vec<String> = self.hosts.iter().map(|host| host.dsn.clone()).collect();
+        let write_attrs = match self.target_session_attrs {
+            _ if self.read_routing => TargetSessionAttrs::Primary,
+            TargetSessionAttrs::Any if dsns.len() > 1 => TargetSessionAttrs::ReadWrite,
+            attrs => attrs,
+        };
+        // Standby of the client, with --read-routing
+        let mut read_host: Option<(u16, String)> = match self.read_hosts.len() {
+            0 => None,
+            n => {
+                let i = self.read_hosts[(client_id as usize - 1) % n];
+                Some((i as u16, self.hosts[i].dsn.clone()))
+            },
+        };
+
+        tokio::spawn(async move {
+            // New database connection, unless the client borrows the connections of the pool
+            let mut connection = match pool {
+                Some(_) => None,
+                None => Some(Executor::open_client_connection(&dsn, &args, &tx, client_id).await),
+            };
+            let mut read_connection = match read_host.as_ref() {
+                Some((_, read_dsn)) => Some(Executor::open_client_connection(read_dsn, &args, &tx, client_id).await),
+                None => None,
+            };
+            // Client side preparation, like loading the benchmark run-time constants
+            // The error is not Send, it must not be kept across the awaits of the loop
+            let prepared = match (pool.as_ref(), connection.as_mut()) {
+                (Some(pool), _) => match pool.acquire().await {
+                    Ok(mut pooled) => benchmark_client.prepare_rw(&mut pooled, &args, client_id).await,
+                    Err(error) => Err(error.into()),
+                },
+                (None, Some(connection)) => benchmark_client.prepare_rw(connection, &args, client_id).await,
+                (None, None) => unreachable!(),
+            }.map_err(|error| error.to_string());
+            if let Err(error) = prepared {
+                terminal::err_msg(error.as_str());
+                std::process::exit(1);
+            }
+            let transactions = benchmark_client.get_transactions_rw();
+            // Transactions and their parameters are picked up using the client's random stream
+            let mut rng = stream_rng(args.seed, CLIENT_RNG_STREAM_BASE + client_id as u64);
+            let mut timer = StmtTimer::new(args.statement_timing);
+
+            let mut transaction: &BenchmarkTransaction;
+            // Transactions executed on the current connection
+            let mut n_transactions: u32 = 0;
+            // Write outage of the client, with --failover: start timestamp in ms, start time and
+            // number of failed transactions
+            let mut outage: Option<(i64, Instant, u32)> = None;
+            loop {
+                match state.load(Ordering::Relaxed) {
+                    CLIENT_STOPPED => break,
+                    CLIENT_PAUSING | CLIENT_PAUSED => {
+                        // Let the executor know that the current transaction is over
+                        let _ = state.compare_exchange(CLIENT_PAUSING, CLIENT_PAUSED, Ordering::Relaxed, Ordering::Relaxed);
+                        tokio::time::sleep(Duration::from_millis(CLIENT_PAUSE_POLL_MS)).await;
+                        continue;
+                    },
+                    _ => (),
+                }
+                // Replace the connection once it has executed the requested number of transactions.
+                // With --failover, a failed connection starts the outage of the client, and the
+                // hosts are resolved again like after a connection error.
+                if args.reconnect_every.is_some_and(|every| n_transactions >= every) {
+                    if let Some(old_connection) = connection.take() {
+                        let _ = old_connection.close().await;
+                    }
+                    match Executor::connect_client(&dsn, &args, &tx, client_id).await {
+                        Ok(new_connection) => connection = Some(new_connection),
+                        Err(_) if args.failover => {
+                            if outage.is_none() {
+                                outage = Some((Utc::now().timestamp_millis(), Instant::now(), 0));
+                            }
+                            match Executor::reconnect_client(&dsns, write_attrs, &args, &state).await {
+                                Some((i, new_connection)) => {
+                                    write_host = i as u16;
+                                    dsn = dsns[i].clone();
+                                    connection = Some(new_connection);
+                                },
+                                None => break,
+                            }
+                        },
+                        Err(error) => {
+                            terminal::err_msg(error.as_str());
+                            std::process::exit(1);
+                        },
+                    }
+                    if let (Some(old_connection), Some((_, read_dsn))) = (read_connection.take(), read_host.clone()) {
+                        let _ = old_connection.close().await;
+                        match Executor::connect_client(&read_dsn, &args, &tx, client_id).await {
+                            Ok(new_connection) => read_connection = Some(new_connection),
+                            Err(_) if args.failover => {
+                                match Executor::reconnect_client(&dsns, TargetSessionAttrs::PreferStandby, &args, &state).await {
+                                    Some((i, new_connection)) => {
+                                        read_host = Some((i as u16, dsns[i].clone()));
+                                        read_connection = Some(new_connection);
+                                    },
+                                    None => break,
+                                }
+                            },
+                            Err(error) => {
+                                terminal::err_msg(error.as_str());
+                                std::process::exit(1);
+                            },
+                        }
+                    }
+                    n_transactions = 0;
+                }
+                n_transactions += 1;
+                // Pickup a transaction, randomly and weight based.
+                transaction = transactions.choose_weighted(&mut rng, |item| item.weight).unwrap();
+                // Execute the database transactions. A pool connection is borrowed for the
+                // transaction only, the wait time is not part of the response time.
+                // Read-only transactions run on the standby of the client, with --read-routing
+                let routed = transaction.read_only && read_connection.is_some();
+                let (host, target_connection) = match (transaction.read_only, read_host.as_ref(), read_connection.as_mut()) {
+                    (true, Some((read_host_id, _)), Some(read_connection)) => (*read_host_id, Some(read_connection)),
+                    _ => (write_host, connection.as_mut()),
+                };
+                let result = match (pool.as_ref(), target_connection) {
+                    (Some(pool), _) => {
+                        let saturated = pool.num_idle() == 0;
+                        let start = Instant::now();
+                        match pool.acquire().await {
+                            Ok(mut pooled) => {
+                                tx.send(TXMessage::acquired(client_id, Utc::now().timestamp(), start.elapsed().as_micros(), saturated)).unwrap();
+                                benchmark_client.execute_rw_transaction(&mut pooled, transaction, &mut rng, &mut timer).await
+                            },
+                            Err(error) => Err(error.into()),
+                        }
+                    },
+                    (None, Some(connection)) => benchmark_client.execute_rw_transaction(connection, transaction, &mut rng, &mut timer).await,
+                    (None, None) => unreachable!(),
+                };
+                // The error is not Send, it must not be kept across the reconnection
+                let result = result.map_err(|error| (format!("{}", error), is_serialization_failure(error.as_ref()), is_connection_error(error.as_ref())));
+                let timestamp = Utc::now().timestamp();
+                // Send the statements executed by the transaction, including those of failed ones
+                for (statement, duration) in timer.timings.drain(..) {
+                    tx.send(TXMessage::statement(transaction.id, client_id, timestamp, statement, duration)).unwrap();
+                }
+                let connection_error = match result {
+                    Ok((duration, commit_duration)) => {
+                        // Send committed message
+                        let m = TXMessage::committed(transaction.id, client_id, timestamp, duration, commit_duration, host);
+                        tx.send(m).unwrap();
+                        // The first commit on the primary ends the outage of the client
+                        if !routed {
+                            if let Some((start_ms, outage_start, n_lost)) = outage.take() {
+                                tx.send(TXMessage::outage(client_id, start_ms, outage_start.elapsed().as_micros(), n_lost)).unwrap();
+                            }
+                        }
+                        false
+                    },
+                    Err((error, serialization_failure, connection_error)) => {
+                        // Send error message
+                        let m = TXMessage::error(transaction.id, client_id, timestamp, error, serialization_failure, host);
+                        tx.send(m).unwrap();
+                        let connection_error = args.failover && connection_error;
+                        // Transactions failing during the outage are lost
+                        match outage.as_mut() {
+                            Some((_, _, n_lost)) => *n_lost += 1,
+                            None if connection_error && !routed => outage = Some((Utc::now().timestamp_millis(), Instant::now(), 1)),
+                            None => (),
+                        }
+                        connection_error
+                    },
+                };
+                // Replace the failed connection by a connection to the host now matching its
+                // target_session_attrs
+                if connection_error {
+                    let attrs = match routed {
+                        true => TargetSessionAttrs::PreferStandby,
+                        false => write_attrs,
+                    };
+                    match Executor::reconnect_client(&dsns, attrs, &args, &state).await {
+                        Some((i, new_connection)) if routed => {
+                            read_host = Some((i as u16, dsns[i].clone()));
+                            read_connection = Some(new_connection);
+                        },
+                        Some((i, new_connection)) => {
+                            write_host = i as u16;
+                            dsn = dsns[i].clone();
+                            connection = Some(new_connection);
+                        },
+                        None => break,
+                    }
+                    n_transactions = 0;
+                }
+            }
+            // The run has ended during the outage
+            if let Some((start_ms, outage_start, n_lost)) = outage {
+                tx.send(TXMessage::outage(client_id, start_ms, outage_start.elapsed().as_micros(), n_lost)).unwrap();
+            }
+        })
+    }
+
+    // Reconnect a client after a connection error, with --failover. The roles of the hosts are
+    // read again until a host matching attrs accepts the connection, or the client is stopped.
+    async fn reconnect_client(dsns: &[String], attrs: TargetSessionAttrs, args: &RunArgs, state: &AtomicU8) -> Option<(usize, PgConnection)> {
+        loop {
+            match state.load(Ordering::Relaxed) {
+                CLIENT_STOPPED => return None,
+                // No transaction is running, the client can be paused while reconnecting
+                CLIENT_PAUSING => {
+                    let _ = state.compare_exchange(CLIENT_PAUSING, CLIENT_PAUSED, Ordering::Relaxed, Ordering::Relaxed);
+                },
+                _ => (),
+            }
+            if let Some((i, mut connection)) = connect_first(dsns, attrs).await {
+                if Executor::set_session(&mut connection, args).await.is_ok() {
+                    return Some((i, connection));
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(FAILOVER_RETRY_MS)).await;
+        }
+    }
+
+    // Start the fault injector, which injects the faults of --fault at their time. The locks are
+    // held by tasks of their own, so that they do not delay the next faults. The injector and the
+    // lock holders release the locks and exit once the state is set to CLIENT_STOPPED.
+    fn start_fault_injector(&self, tx: Sender<TXMessage>, state: Arc<AtomicU8>) -> tokio::task::JoinHandle<()> {
+        let dsn = self.dsn.clone();
+        let mut faults = self.faults.clone();
+        faults.sort_by_key(|fault| fault.at_ms);
+
+        tokio::spawn(async move {
+            let start = Instant::now();
+            let mut lock_holders: Vec<tokio::task::JoinHandle<()>> = Vec::new();
+            'faults: for fault in faults {
+                // Wait for the time of the fault, unless the run ends first
+                while (start.elapsed().as_millis() as u64) < fault.at_ms {
+                    if state.load(Ordering::Relaxed) == CLIENT_STOPPED {
+                        break 'faults;
+                    }
+                    tokio::time::sleep(Duration::from_millis(CLIENT_PAUSE_POLL_MS)).await;
+                }
+                match fault.kind {
+                    FaultKind::Lock(table, duration_ms) => {
+                        lock_holders.push(tokio::spawn(Executor::hold_lock(dsn.clone(), table, duration_ms, tx.clone(), state.clone())));
+                    },
+                    kind => tx.send(Executor::inject_fault(&dsn, &kind).await).unwrap(),
+                }
+            }
+            for lock_holder in lock_holders {
+                lock_holder.await.expect("the lock holder panicked");
+            }
+        })
+    }
+
+    // Run a terminate, cancel or checkpoint fault, and return its FAULT message
+    async fn inject_fault(dsn: &str, kind: &FaultKind) -> TXMessage {
+        let timestamp_ms = Utc::now().timestamp_millis();
+        let start = Instant::now();
+        let result = match Executor::connect_fault(dsn).await {
+            Ok(mut connection) => match kind {
+                FaultKind::Terminate(percent) => Executor::signal_clients(&mut connection, "pg_terminate_backend", *percent).await,
+                FaultKind::Cancel(percent) => Executor::signal_clients(&mut connection, "pg_cancel_backend", *percent).await,
+                FaultKind::Checkpoint => sqlx::query("CHECKPOINT").execute(&mut connection).await.map(|_| 0),
+                FaultKind::Lock(_, _) => unreachable!(),
+            }.map_err(|error| error.to_string()),
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok(n_sessions) => TXMessage::fault(kind.name(), timestamp_ms, start.elapsed().as_micros(), n_sessions, String::new()),
+            Err(error) => TXMessage::fault(kind.name(), timestamp_ms, 0, 0, error),
+        }
+    }
+
+    // Send the signal of the given function, pg_terminate_backend or pg_cancel_backend, to a random
+    // share of the client sessions. Returns the number of signaled sessions.
+    async fn signal_clients(connection: &mut PgConnection, function: &str, percent: f64) -> Result<u32, sqlx::Error> {
+        let sql = format!(
+            "WITH clients AS (SELECT pid FROM pg_stat_activity WHERE application_name = $1) \
+             SELECT count(*) FILTER (WHERE {}(pid)) FROM ( \
+                 SELECT pid FROM clients ORDER BY random() \
+                 LIMIT (SELECT ceil(count(*) * $2 / 100)::BIGINT FROM clients) \
+             ) AS signaled",
+            function
+        );
+        let n_sessions: i64 = sqlx::query_scalar(&sql)
+            .bind(application_name())
+            .bind(percent)
+            .fetch_one(&mut *connection)
+            .await?;
+
+        Ok(n_sessions as u32)
+    }
+
+    // Hold an ACCESS EXCLUSIVE lock on the table for duration_ms milliseconds, or until the run
+    // ends. The FAULT message is sent once the lock is released.
+    async fn hold_lock(dsn: String, table: String, duration_ms: u64, tx: Sender<TXMessage>, state: Arc<AtomicU8>) {
+        let mut connection = match Executor::connect_fault(&dsn).await {
+            Ok(connection) => connection,
+            Err(error) => {
+                tx.send(TXMessage::fault("lock", Utc::now().timestamp_millis(), 0, 0, error)).unwrap();
+                return;
+            }
+        };
+        let sql = format!("LOCK TABLE {} IN ACCESS EXCLUSIVE MODE", table);
+        let locked = match sqlx::query("BEGIN").execute(&mut connection).await {
+            Ok(_) => sqlx::query(&sql).execute(&mut connection).await,
+            Err(error) => Err(error),
+        };
+        let timestamp_ms = Utc::now().timestamp_millis();
+        if let Err(error) = locked {
+            tx.send(TXMessage::fault("lock", timestamp_ms, 0, 0, error.to_string())).unwrap();
+            return;
+        }
+
+        let start = Instant::now();
+        while (start.elapsed().as_millis() as u64) < duration_ms && state.load(Ordering::Relaxed) != CLIENT_STOPPED {
+            tokio::time::sleep(Duration::from_millis(CLIENT_PAUSE_POLL_MS)).await;
+        }
+        let _ = sqlx::query("COMMIT").execute(&mut connection).await;
+        tx.send(TXMessage::fault("lock", timestamp_ms, start.elapsed().as_micros(), 0, String::new())).unwrap();
+    }
+
+    // Open a fault connection, named after FAULT_APPLICATION_NAME
+    async fn connect_fault(dsn: &str) -> Result<PgConnection, String> {
+        match PgConnectOptions::from_str(dsn) {
+            Ok(options) => options
+                .application_name(FAULT_APPLICATION_NAME)
+                .connect()
+                .await
+                .map_err(|error| format!("could not connect: {}", error)),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    // Open the connection of a client, exiting on failure
+    async fn open_client_connection(dsn: &str, args: &RunArgs, tx: &Sender<TXMessage>, client_id: u32) -> PgConnection {
+        match Executor::connect_client(dsn, args, tx, client_id).await {
+            Ok(connection) => connection,
+            Err(error) => {
+                terminal::err_msg(error.as_str());
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Open the connection of a client and apply the session settings. The connection time is sent
+    // to the data collector when the clients reconnect.
+    async fn connect_client(dsn: &str, args: &RunArgs, tx: &Sender<TXMessage>, client_id: u32) -> Result<PgConnection, String> {
+        let start = Instant::now();
+        let mut connection = PgConnection::connect(dsn).await.map_err(|error| format!("{}", error))?;
+        // Session settings, applied to every transaction of the client
+        Executor::set_session(&mut connection, args).await?;
+        if args.reconnect_every.is_some() {
+            tx.send(TXMessage::connected(client_id, Utc::now().timestamp(), start.elapsed().as_micros())).unwrap();
+        }
 
-        tokio::spawn(async move {
-            // New database connection
-            let mut connection = match PgConnection::connect(&dsn).await {
-                Ok(connection) => connection,
-                Err(error) => {
-                    terminal::err_msg(format!("{}", error).as_str());
-                    std::process::exit(1);
-                }
-            };
-            let transactions = benchmark_client.get_transactions_rw();
+        Ok(connection)
+    }
 
-            // Used for tracking client execution time
-            let start = Instant::now();
-            let mut transaction: &BenchmarkTransaction;
-            loop {
-                // Pickup a transaction, randomly and weight based.
-                transaction = {
-                    let mut rng = thread_rng();
+    // Open the connection pool shared by the clients, with --pool-size. The session settings are
+    // applied to each new connection of the pool.
+    async fn open_pool(&mut self, args: &RunArgs, size: u32) {
+        let session_args = args.clone();
+        let pool = PgPoolOptions::new()
+            .max_connections(size)
+            .min_connections(size)
+            .after_connect(move |connection, _| {
+                let args = session_args.clone();
+                Box::pin(async move {
+                    Executor::set_session(connection, &args).await.map_err(sqlx::Error::Protocol)
+                })
+            })
+            .connect(&self.dsn)
+            .await;
+        match pool {
+            Ok(pool) => self.pool = Some(pool),
+            Err(error) => {
+                terminal::err_msg(format!("{}", error).as_str());
+                std::process::exit(1);
+            }
+        }
+    }
 
-                    transactions.choose_weighted(&mut rng, |item| item.weight).unwrap()
-                };
-                // Execute the database transactions
-                match benchmark_client.execute_rw_transaction(&mut connection, &transaction).await {
-                    Ok(duration) => {
-                        // Send committed message
-                        let m = TXMessage::committed(transaction.id, client_id, Utc::now().timestamp(), duration);
-                        tx.send(m).unwrap();
-                    },
-                    Err(error) => {
-                        // Send error message
-                        let m = TXMessage::error(transaction.id, client_id, Utc::now().timestamp(), format!("{}", error));
-                        tx.send(m).unwrap();
-                    },
-                }
-                // Break the loop if we reach the time limit
-                if start.elapsed().as_millis() >= duration_ms as u128 {
-                    break;
-                }
+    // Apply --isolation and --set to a client session
+    async fn set_session(connection: &mut PgConnection, args: &RunArgs) -> Result<(), String> {
+        if let Some(isolation) = args.isolation {
+            let sql = format!("SET SESSION CHARACTERISTICS AS TRANSACTION ISOLATION LEVEL {}", isolation.sql());
+            match sqlx::query(&sql).execute(&mut *connection).await {
+                Ok(_) => (),
+                Err(error) => return Err(format!("could not set the isolation level: {}", error)),
             }
-        })
+        }
+        for (name, value) in args.settings.iter() {
+            match sqlx::query("SELECT set_config($1, $2, false)").bind(name).bind(value).execute(&mut *connection).await {
+                Ok(_) => (),
+                Err(error) => return Err(format!("could not set {}: {}", name, error)),
+            }
+        }
+
+        Ok(())
     }
 
     // Start the data collector thread. Data collector is in charge of storing transaction
     // informations into the log file and incrementing counters.
     // Once the data collector has received the shutdown order (message with id=0), then
     // the counters are sent back to the main process through the tx_counters channel.
-    fn start_data_collector(&mut self, rx: Receiver<TXMessage>, tx_counters: Sender<HashMap<u16, Counter>>) -> JoinHandle<()> {
+    // When tx_samples is set, the response times of each stage are sent back at the end of the stage.
+    fn start_data_collector(&mut self, rx: Receiver<TXMessage>, tx_counters: Sender<CollectedCounters>, tx_samples: Option<Sender<WindowSamples>>) -> JoinHandle<()> {
         let target_dir = self.target_dir.clone();
         thread::spawn(move || {
             // Create the file where transaction logs are written
@@ -260,10 +1478,22 @@ impl Executor {
                 },
             };
             let mut error_file = BufWriter::new(error_file);
+            // The statement log file is created by the first statement message
+            let mut statement_file: Option<BufWriter<File>> = None;
+            // Same for the connection and pool log files
+            let mut connection_file: Option<BufWriter<File>> = None;
+            let mut pool_file: Option<BufWriter<File>> = None;
+            let mut outage_file: Option<BufWriter<File>> = None;
+            let mut fault_file: Option<BufWriter<File>> = None;
 
             // Initialize the counters
             let mut counters: HashMap<u16, Counter> = HashMap::new();
+            let mut stage_counters: BTreeMap<u16, Counter> = BTreeMap::new();
+            let mut host_counters: BTreeMap<u16, Counter> = BTreeMap::new();
             let mut client_ids = BTreeMap::new();
+            // Current stage number, 0 during the rampup and between stages
+            let mut stage: u16 = 0;
+            let mut samples: WindowSamples = HashMap::new();
 
             let mut ramping_up :bool = true;
             let mut buffer_i = itoa::Buffer::new();
@@ -281,15 +1511,18 @@ impl Executor {
                     },
                     // Committed transaction
                     TXMessageKind::COMMITTED => {
-                        // Keep a track of the client_ids and increment n_client if this is a new client_id
-                        n_client = match client_ids.get(&msg.client_id) {
-                            None => {
-                                client_ids.insert(msg.client_id, true);
-                                n_client += 1;
-                                n_client
-                            },
-                            Some(_) => n_client,
-                        };
+                        // Keep a track of the client_ids and increment n_client if this is a new client_id.
+                        // Once the stages have started, n_client is the number of clients of the stage.
+                        if stage == 0 {
+                            n_client = match client_ids.get(&msg.client_id) {
+                                None => {
+                                    client_ids.insert(msg.client_id, true);
+                                    n_client += 1;
+                                    n_client
+                                },
+                                Some(_) => n_client,
+                            };
+                        }
                         let duration_ms = msg.tx_duration_us as f64 / 1000 as f64;
                         // Counters calculation
                         // Update counters only if the rampup stage is over
@@ -300,8 +1533,17 @@ impl Executor {
                                 (*c).total_duration_ms += duration_ms;
                             }
                             else {
-                                counters.insert(msg.tx_id, Counter {n_commits: 1, n_total: 1, total_duration_ms: duration_ms});
+                                counters.insert(msg.tx_id, Counter {n_commits: 1, n_total: 1, total_duration_ms: duration_ms, n_serialization_failures: 0});
                             }
+                            for c in [stage_counters.entry(stage), host_counters.entry(msg.host)] {
+                                let c = c.or_insert(Counter {n_commits: 0, n_total: 0, total_duration_ms: 0.0, n_serialization_failures: 0});
+                                c.n_commits += 1;
+                                c.n_total += 1;
+                                c.total_duration_ms += duration_ms;
+                            }
+                        }
+                        if stage > 0 && tx_samples.is_some() {
+                            samples.entry(msg.tx_id).or_default().push(duration_ms);
                         }
 
                         // Format and write the line to the log file
@@ -312,26 +1554,39 @@ impl Executor {
                         log_file.write(&buffer_i.format(msg.tx_id).as_bytes()).expect("Failed to write");
                         log_file.write(b" ").expect("Failed to write");
                         log_file.write(&buffer_f.format(duration_ms).as_bytes()).expect("Failed to write");
+                        log_file.write_all(b" ").expect("Failed to write");
+                        log_file.write_all(buffer_i.format(stage).as_bytes()).expect("Failed to write");
+                        log_file.write_all(b" ").expect("Failed to write");
+                        // The commit time is left empty when unknown
+                        if let Some(commit_duration_us) = msg.commit_duration_us {
+                            log_file.write_all(buffer_f.format(commit_duration_us as f64 / 1000_f64).as_bytes()).expect("Failed to write");
+                        }
+                        log_file.write_all(b" ").expect("Failed to write");
+                        log_file.write_all(buffer_i.format(msg.host).as_bytes()).expect("Failed to write");
                         log_file.write(b"\n").expect("Failed to write");
                     },
                     TXMessageKind::ERROR => {
-                        // Keep a track of the client_ids and increment n_client if this is a new client_id
-                        n_client = match client_ids.get(&msg.client_id) {
-                            None => {
-                                client_ids.insert(msg.client_id, true);
-                                n_client += 1;
-                                n_client
-                            },
-                            Some(_) => n_client,
-                        };
+                        // Keep a track of the client_ids and increment n_client if this is a new client_id.
+                        // Once the stages have started, n_client is the number of clients of the stage.
+                        if stage == 0 {
+                            n_client = match client_ids.get(&msg.client_id) {
+                                None => {
+                                    client_ids.insert(msg.client_id, true);
+                                    n_client += 1;
+                                    n_client
+                                },
+                                Some(_) => n_client,
+                            };
+                        }
                         // Counters calculation
                         if !ramping_up {
-                            if let Some(c) = counters.get_mut(&msg.tx_id) {
-                                (*c).n_total += 1;
-                            }
-                            else {
-                                counters.insert(msg.tx_id, Counter {n_commits: 0, n_total: 1, total_duration_ms: 0.0});
+                            let c = counters.entry(msg.tx_id).or_insert(Counter {n_commits: 0, n_total: 0, total_duration_ms: 0.0, n_serialization_failures: 0});
+                            c.n_total += 1;
+                            if msg.serialization_failure {
+                                c.n_serialization_failures += 1;
                             }
+                            stage_counters.entry(stage).or_insert(Counter {n_commits: 0, n_total: 0, total_duration_ms: 0.0, n_serialization_failures: 0}).n_total += 1;
+                            host_counters.entry(msg.host).or_insert(Counter {n_commits: 0, n_total: 0, total_duration_ms: 0.0, n_serialization_failures: 0}).n_total += 1;
                         }
 
                         // Format and write the line to the log file
@@ -341,19 +1596,152 @@ impl Executor {
                         error_file.write(b" ").expect("Failed to write");
                         error_file.write(&buffer_i.format(msg.tx_id).as_bytes()).expect("Failed to write");
                         error_file.write(b" ").expect("Failed to write");
+                        error_file.write_all(buffer_i.format(stage).as_bytes()).expect("Failed to write");
+                        error_file.write_all(b" ").expect("Failed to write");
                         error_file.write(msg.error.as_bytes()).expect("Failed to write");
                         error_file.write(b"\n").expect("Failed to write");
                     },
+                    TXMessageKind::STATEMENT => {
+                        let file = statement_file.get_or_insert_with(|| {
+                            match File::create(target_dir.join(STATEMENT_LOG_FILE)) {
+                                Ok(f) => BufWriter::new(f),
+                                Err(e) => {
+                                    eprintln!("ERROR: Could not create {}: {}", STATEMENT_LOG_FILE, e);
+                                    std::process::exit(1);
+                                },
+                            }
+                        });
+                        let duration_ms = msg.tx_duration_us as f64 / 1000_f64;
+
+                        // Format and write the line to the statement log file
+                        file.write_all(buffer_i.format(msg.tx_timestamp).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_i.format(msg.tx_id).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(msg.statement.as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_f.format(duration_ms).as_bytes()).expect("Failed to write");
+                        file.write_all(b"\n").expect("Failed to write");
+                    },
+                    TXMessageKind::CONNECTED => {
+                        let file = connection_file.get_or_insert_with(|| {
+                            match File::create(target_dir.join(CONNECTION_LOG_FILE)) {
+                                Ok(f) => BufWriter::new(f),
+                                Err(e) => {
+                                    eprintln!("ERROR: Could not create {}: {}", CONNECTION_LOG_FILE, e);
+                                    std::process::exit(1);
+                                },
+                            }
+                        });
+                        let duration_ms = msg.tx_duration_us as f64 / 1000_f64;
+
+                        // Format and write the line to the connection log file
+                        file.write_all(buffer_i.format(msg.tx_timestamp).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_i.format(msg.client_id).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_f.format(duration_ms).as_bytes()).expect("Failed to write");
+                        file.write_all(b"\n").expect("Failed to write");
+                    },
+                    TXMessageKind::ACQUIRED => {
+                        let file = pool_file.get_or_insert_with(|| {
+                            match File::create(target_dir.join(POOL_LOG_FILE)) {
+                                Ok(f) => BufWriter::new(f),
+                                Err(e) => {
+                                    eprintln!("ERROR: Could not create {}: {}", POOL_LOG_FILE, e);
+                                    std::process::exit(1);
+                                },
+                            }
+                        });
+                        let wait_ms = msg.tx_duration_us as f64 / 1000_f64;
+
+                        // Format and write the line to the pool log file
+                        file.write_all(buffer_i.format(msg.tx_timestamp).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_i.format(msg.client_id).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_f.format(wait_ms).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_i.format(msg.pool_saturated as u8).as_bytes()).expect("Failed to write");
+                        file.write_all(b"\n").expect("Failed to write");
+                    },
+                    TXMessageKind::OUTAGE => {
+                        let file = outage_file.get_or_insert_with(|| {
+                            match File::create(target_dir.join(OUTAGE_LOG_FILE)) {
+                                Ok(f) => BufWriter::new(f),
+                                Err(e) => {
+                                    eprintln!("ERROR: Could not create {}: {}", OUTAGE_LOG_FILE, e);
+                                    std::process::exit(1);
+                                },
+                            }
+                        });
+                        let duration_ms = msg.tx_duration_us as f64 / 1000_f64;
+
+                        // Format and write the line to the outage log file
+                        file.write_all(buffer_i.format(msg.tx_timestamp).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_i.format(msg.client_id).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_f.format(duration_ms).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_i.format(msg.n_lost).as_bytes()).expect("Failed to write");
+                        file.write_all(b"\n").expect("Failed to write");
+                    },
+                    // Failed faults are written to the error log
+                    TXMessageKind::FAULT if !msg.error.is_empty() => {
+                        error_file.write_all(buffer_i.format(msg.tx_timestamp / 1000).as_bytes()).expect("Failed to write");
+                        error_file.write_all(b" ").expect("Failed to write");
+                        error_file.write_all(buffer_i.format(n_client).as_bytes()).expect("Failed to write");
+                        error_file.write_all(b" 0 ").expect("Failed to write");
+                        error_file.write_all(buffer_i.format(stage).as_bytes()).expect("Failed to write");
+                        error_file.write_all(b" fault ").expect("Failed to write");
+                        error_file.write_all(msg.fault.as_bytes()).expect("Failed to write");
+                        error_file.write_all(b": ").expect("Failed to write");
+                        error_file.write_all(msg.error.as_bytes()).expect("Failed to write");
+                        error_file.write_all(b"\n").expect("Failed to write");
+                    },
+                    TXMessageKind::FAULT => {
+                        let file = fault_file.get_or_insert_with(|| {
+                            match File::create(target_dir.join(FAULT_LOG_FILE)) {
+                                Ok(f) => BufWriter::new(f),
+                                Err(e) => {
+                                    eprintln!("ERROR: Could not create {}: {}", FAULT_LOG_FILE, e);
+                                    std::process::exit(1);
+                                },
+                            }
+                        });
+                        let duration_ms = msg.tx_duration_us as f64 / 1000_f64;
+
+                        // Format and write the line to the fault log file
+                        file.write_all(buffer_i.format(msg.tx_timestamp).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(msg.fault.as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_i.format(msg.n_sessions).as_bytes()).expect("Failed to write");
+                        file.write_all(b" ").expect("Failed to write");
+                        file.write_all(buffer_f.format(duration_ms).as_bytes()).expect("Failed to write");
+                        file.write_all(b"\n").expect("Failed to write");
+                    },
                     TXMessageKind::ENDOFRAMPUP => {
                         ramping_up = false;
                     },
+                    TXMessageKind::STARTOFSTAGE => {
+                        stage = msg.stage;
+                        n_client = msg.n_client;
+                    },
+                    TXMessageKind::ENDOFSTAGE => {
+                        stage = 0;
+                        if let Some(tx_samples) = tx_samples.as_ref() {
+                            tx_samples.send(std::mem::take(&mut samples)).unwrap();
+                        }
+                    },
                     TXMessageKind::DEFAULT => {
                         // Should not happen
                     },
                 }
             }
             // Send counters
-            tx_counters.send(counters).unwrap();
+            tx_counters.send((counters, stage_counters, host_counters)).unwrap();
         })
     }
 
@@ -366,20 +1754,29 @@ impl Executor {
     }
 
     // Initialize database schemabenchmark: create tables
-    pub fn init_db_schema(&mut self) -> &mut Self {
+    pub fn init_db_schema(&mut self, storage: &StorageArgs) -> &mut Self {
         let command = "INIT";
         let message = "Executing database DDLs";
 
         terminal::start_msg(command, message);
 
-        // New database connection
-        let mut client = Executor::connect(self.dsn.clone());
-
         // Load the corresponding benchmark client
         let benchmark_client = self.get_benchmark(0, 0, 0);
 
+        // Per-table storage options must target existing tables
+        let tables = benchmark_client.get_tables();
+        for table_name in storage.tables.keys() {
+            if !tables.iter().any(|table| &table.name == table_name) {
+                terminal::err_msg(format!("unknown table in storage options: {}", table_name).as_str());
+                std::process::exit(1);
+            }
+        }
+
+        // New database connection
+        let mut client = Executor::connect(self.dsn.clone());
+
         // Initialize the database model/schema
-        let duration_us = match benchmark_client.initialize_schema(&mut client) {
+        let duration_us = match benchmark_client.initialize_schema(&mut client, storage) {
             Ok(duration) => duration,
             Err(error) => {
                 terminal::err_msg(format!("{}", error).as_str());
@@ -394,7 +1791,9 @@ impl Executor {
     }
 
     // Generate benchmark data
-    pub fn load_data(&mut self, scalefactor: u32, n_jobs: u32) -> &mut Self {
+    pub fn load_data(&mut self, args: &InitArgs) -> &mut Self {
+        let scalefactor = args.scalefactor;
+        let n_jobs = args.jobs;
         // Load the corresponding benchmark client
         let benchmark_client = self.get_benchmark(scalefactor, 0, 0);
 
@@ -402,21 +1801,21 @@ impl Executor {
         let message = "Pre-loading operations";
 
         terminal::start_msg(command, message);
+        let start = Instant::now();
 
         // New database connection
         let mut client = Executor::connect(self.dsn.clone());
 
         // Execute PreLoadData
-        let duration_us = match benchmark_client.pre_load_data(&mut client) {
-            Ok(duration) => duration,
+        let pre_load_stats = match benchmark_client.pre_load_data(&mut client, args) {
+            Ok(stats) => stats,
             Err(error) => {
                 terminal::err_msg(format!("{}", error).as_str());
                 std::process::exit(1);
             }
         };
-        let duration_ms = duration_us as f64 / 1000 as f64;
 
-        terminal::done_msg(duration_ms);
+        terminal::done_msg(start.elapsed().as_micros() as f64 / 1000_f64);
 
         // Execute LoadData using multiple concurrent jobs
         let mut jobs = Vec::new();
@@ -434,8 +1833,10 @@ impl Executor {
             ids[(n % n_jobs) as usize].push(n);
         }
 
-        let message2 = format!("Data loading using {} jobs", n_jobs);
-        terminal::start_msg(command, message2.as_str());
+        // Jobs report their progress to the rendering thread, the final message is printed once
+        // the progress bars have been cleared.
+        let (progress_tx, progress_rx): (Sender<LoadProgress>, Receiver<LoadProgress>) = unbounded();
+        let progress_renderer = progress::start_load_progress(progress_rx, n_jobs, scalefactor);
 
         let start = Instant::now();
 
@@ -443,6 +1844,8 @@ impl Executor {
             // Cloning values before passing them to the thread
             let job_ids = ids[(j - 1) as usize].clone();
             let dsn = self.dsn.clone();
+            let job_args = args.clone();
+            let mut job_progress = ProgressReporter::new(progress_tx.clone(), j);
 
             // Load the corresponding benchmark client
             let job_benchmark_client = self.get_benchmark(scalefactor, 0, 0);
@@ -452,25 +1855,63 @@ impl Executor {
                 // New database connection
                 let mut job_client = Executor::connect(dsn);
 
-                let _duration_us = match job_benchmark_client.load_data(&mut job_client, job_ids) {
-                    Ok(duration) => duration,
+                match job_benchmark_client.load_data(&mut job_client, job_ids, &job_args, &mut job_progress) {
+                    Ok(stats) => {
+                        job_progress.done();
+                        stats
+                    },
                     Err(error) => {
                         terminal::err_msg(format!("{}", error).as_str());
                         std::process::exit(1);
                     }
-                };
+                }
             });
 
             jobs.push(job);
         }
 
         // Wait for the end of all jobs
+        let mut jobs_stats = vec![pre_load_stats];
         for job in jobs {
-            job.join().expect("the client thread panicked");
+            jobs_stats.push(job.join().expect("the client thread panicked"));
         }
         let duration_ms = start.elapsed().as_micros() as f64 / 1000 as f64;
+        // The renderer stops once every reporter has been dropped
+        drop(progress_tx);
+        progress_renderer.join().expect("the progress thread panicked");
+
+        let message2 = format!("Data loading using {} jobs", n_jobs);
+        terminal::start_msg(command, message2.as_str());
         terminal::done_msg(duration_ms);
 
+        // Loading throughput of each table. Jobs are running concurrently, so the throughput of
+        // one table is the sum of the throughputs reached by each job on this table.
+        let mut load_summary: Vec<LoadSummary> = Vec::new();
+        for stats in jobs_stats.into_iter().flatten() {
+            let size_mb = stats.bytes as f64 / (1024 * 1024) as f64;
+            let duration_s = (stats.duration_us as f64 / 1_000_000_f64).max(f64::EPSILON);
+            match load_summary.iter_mut().find(|s| s.table == stats.table) {
+                Some(s) => {
+                    s.rows += stats.rows;
+                    s.size_mb += size_mb;
+                    s.duration_s += duration_s;
+                    s.rows_per_sec += stats.rows as f64 / duration_s;
+                    s.mb_per_sec += size_mb / duration_s;
+                },
+                None => load_summary.push(
+                    LoadSummary {
+                        table: stats.table,
+                        rows: stats.rows,
+                        size_mb,
+                        duration_s,
+                        rows_per_sec: stats.rows as f64 / duration_s,
+                        mb_per_sec: size_mb / duration_s,
+                    }
+                ),
+            }
+        }
+        data_agg::print_load_summary(&load_summary);
+
         self
     }
 
@@ -545,34 +1986,39 @@ impl Executor {
     }
 
     fn get_benchmark(&mut self, scalefactor: u32, min_id: u32, max_id: u32) -> impl Benchmark {
-        let benchmark = match self.benchmark_type.as_str() {
+        let mut benchmark = match self.benchmark_type.as_str() {
             "tpcc" => tpcc::TPCC::new(scalefactor, min_id, max_id),
             _ => tpcc::TPCC::new(scalefactor, min_id, max_id),
         };
+        if !self.mix.is_empty() {
+            match benchmark.set_transaction_weights(&self.mix) {
+                Ok(_) => (),
+                Err(error) => {
+                    terminal::err_msg(error.as_str());
+                    std::process::exit(1);
+                }
+            }
+        }
+        benchmark.set_transaction_mode(self.mode);
 
         benchmark
     }
 
     // Execute primary keys DDLs using multiple concurrent jobs
-    pub fn add_primary_keys(&mut self, n_jobs: u32) -> &mut Self {
+    pub fn add_primary_keys(&mut self, n_jobs: u32, storage: &StorageArgs) -> &mut Self {
         // Load the corresponding benchmark
         let benchmark = self.get_benchmark(0, 0, 0);
         let start = Instant::now();
 
         terminal::start_msg("INIT", "Primary keys creation");
-        self.exec_stmts(n_jobs, benchmark.get_pkey_ddls(), true);
+        self.exec_stmts(n_jobs, benchmark.get_pkey_ddls(storage), true);
         terminal::done_msg(start.elapsed().as_micros() as f64 / 1000 as f64);
 
         self
     }
 
     // Execute foreign keys DDLs using multiple concurrent jobs
-    pub fn add_foreign_keys(&mut self, n_jobs: u32, no_fkey: bool) -> &mut Self {
-        // Don't do anything if no_fkey is true
-        if no_fkey {
-            return self;
-        }
-
+    pub fn add_foreign_keys(&mut self, n_jobs: u32) -> &mut Self {
         // Load the corresponding benchmark
         let benchmark = self.get_benchmark(0, 0, 0);
         let start = Instant::now();
@@ -585,18 +2031,31 @@ impl Executor {
     }
 
     // Execute additional indexes DDLs using multiple concurrent jobs
-    pub fn add_indexes(&mut self, n_jobs: u32) -> &mut Self {
+    pub fn add_indexes(&mut self, n_jobs: u32, storage: &StorageArgs) -> &mut Self {
         // Load the corresponding benchmark
         let benchmark = self.get_benchmark(0, 0, 0);
         let start = Instant::now();
 
         terminal::start_msg("INIT", "Additional indexes creation");
-        self.exec_stmts(n_jobs, benchmark.get_index_ddls(), true);
+        self.exec_stmts(n_jobs, benchmark.get_index_ddls(storage), true);
         terminal::done_msg(start.elapsed().as_micros() as f64 / 1000 as f64);
 
         self
     }
 
+    // Create or replace the functions implementing the read/write transactions
+    pub fn add_procedures(&mut self) -> &mut Self {
+        // Load the corresponding benchmark
+        let benchmark = self.get_benchmark(0, 0, 0);
+        let start = Instant::now();
+
+        terminal::start_msg("INIT", "Transaction functions creation");
+        self.exec_stmts(1, benchmark.get_procedure_ddls(), true);
+        terminal::done_msg(start.elapsed().as_micros() as f64 / 1000_f64);
+
+        self
+    }
+
     // Execute VACUUM statementsusing multiple concurrent jobs
     pub fn vacuum(&mut self, n_jobs: u32) -> &mut Self {
         // Load the corresponding benchmark
@@ -610,6 +2069,35 @@ impl Executor {
         self
     }
 
+    // Empty all the benchmark tables, using one TRUNCATE statement
+    pub fn truncate_tables(&mut self) -> &mut Self {
+        // Load the corresponding benchmark
+        let benchmark = self.get_benchmark(0, 0, 0);
+        let start = Instant::now();
+
+        terminal::start_msg("INIT", "Truncating tables");
+        let mut client = Executor::connect(self.dsn.clone());
+
+        match client.batch_execute(&Executor::truncate_stmt(&benchmark).sql) {
+            Ok(_) => (),
+            Err(error) => {
+                terminal::err_msg(format!("{}", error).as_str());
+                std::process::exit(1);
+            }
+        }
+
+        terminal::done_msg(start.elapsed().as_micros() as f64 / 1000_f64);
+
+        self
+    }
+
+    // TRUNCATE statement emptying all the benchmark tables
+    fn truncate_stmt(benchmark: &impl Benchmark) -> BenchmarkStmt {
+        let tables: Vec<String> = benchmark.get_tables().into_iter().map(|table| table.name).collect();
+
+        BenchmarkStmt { sql: format!("TRUNCATE {}", tables.join(", ")) }
+    }
+
     // Force a checkpoint
     pub fn checkpoint(&mut self) -> &mut Self {
         let start = Instant::now();
@@ -639,13 +2127,76 @@ impl Executor {
         let transactions = self.get_benchmark(0, 0, 0)
             .get_transactions_rw();
 
-        match data_agg::aggregate_tpcc_data(LOG_FILE, &self.target_dir, &transactions) {
+        // Outages of the clients and injected faults, marked in the time series. No outage log file
+        // is written when no client has lost its connection, and no fault log file when all the
+        // faults have failed.
+        let mut events = data_agg::RunEvents::default();
+        if self.failover && self.target_dir.join(OUTAGE_LOG_FILE).exists() {
+            match data_agg::aggregate_outage_data(OUTAGE_LOG_FILE, &self.target_dir).and_then(|_| data_agg::get_outages(&self.target_dir)) {
+                Ok(client_outages) => {
+                    events.outages = client_outages.iter().map(|(start_ms, end_ms, _, _)| (start_ms / 1000, end_ms / 1000)).collect();
+                },
+                Err(error) => {
+                    terminal::err_msg(format!("{}", error).as_str());
+                    std::process::exit(1);
+                }
+            }
+        }
+        if !self.faults.is_empty() && self.target_dir.join(FAULT_LOG_FILE).exists() {
+            match data_agg::aggregate_fault_data(FAULT_LOG_FILE, &self.target_dir).and_then(|_| data_agg::get_faults(&self.target_dir)) {
+                Ok(faults) => {
+                    events.faults = faults.into_iter().map(|(time_ms, fault, _, _)| (time_ms / 1000, fault)).collect();
+                },
+                Err(error) => {
+                    terminal::err_msg(format!("{}", error).as_str());
+                    std::process::exit(1);
+                }
+            }
+        }
+        match data_agg::aggregate_tpcc_data(LOG_FILE, &self.target_dir, &transactions, &events, self.mode == TransactionMode::Statement) {
             Ok(_) => (),
             Err(error) => {
                 terminal::err_msg(format!("{}", error).as_str());
                 std::process::exit(1);
             }
         }
+        // No statement log file is written when no statement has completed
+        if self.statement_timing && self.target_dir.join(STATEMENT_LOG_FILE).exists() {
+            match data_agg::aggregate_statement_data(STATEMENT_LOG_FILE, &self.target_dir) {
+                Ok(_) => (),
+                Err(error) => {
+                    terminal::err_msg(format!("{}", error).as_str());
+                    std::process::exit(1);
+                }
+            }
+        }
+        if self.reconnect {
+            match data_agg::aggregate_connection_data(CONNECTION_LOG_FILE, &self.target_dir) {
+                Ok(_) => (),
+                Err(error) => {
+                    terminal::err_msg(format!("{}", error).as_str());
+                    std::process::exit(1);
+                }
+            }
+        }
+        if self.read_routing {
+            match data_agg::aggregate_host_data(LOG_FILE, &self.target_dir) {
+                Ok(_) => (),
+                Err(error) => {
+                    terminal::err_msg(format!("{}", error).as_str());
+                    std::process::exit(1);
+                }
+            }
+        }
+        if self.pool_size.is_some() {
+            match data_agg::aggregate_pool_data(POOL_LOG_FILE, &self.target_dir) {
+                Ok(_) => (),
+                Err(error) => {
+                    terminal::err_msg(format!("{}", error).as_str());
+                    std::process::exit(1);
+                }
+            }
+        }
 
         terminal::done_msg(start.elapsed().as_micros() as f64 / 1000 as f64);
 
@@ -665,36 +2216,56 @@ impl Executor {
                 std::process::exit(1);
             }
         };
+        // Commits are part of the transaction functions in procedure mode
+        let commit_stats_map = match self.mode {
+            TransactionMode::Statement => match data_agg::get_commit_stats(&self.target_dir, &transactions) {
+                Ok(stats_map) => stats_map,
+                Err(error) => {
+                    eprintln!("ERROR: {}", error);
+                    std::process::exit(1);
+                }
+            },
+            TransactionMode::Procedure => HashMap::new(),
+        };
 
         let mut data_stats: Vec<ResponseTimeStatistics>  = Vec::new();
+        let mut data_commit_stats: Vec<ResponseTimeStatistics>  = Vec::new();
         let mut data_summary: Vec<TransactionSummary>  = Vec::new();
 
+        let total_weight: u32 = transactions.iter().map(|t| t.weight as u32).sum();
+        let transactions_names: HashMap<u16, String> = transactions.iter().map(|t| (t.id, t.name.clone())).collect();
+
         for transaction in transactions {
-            let stats = match stats_map.get(&transaction.id) {
-                Some(stats) => stats,
-                None => {
-                    eprintln!("ERROR: no statistics found for the transaction: {} ", transaction.name);
-                    std::process::exit(1);
-                }
-            };
+            // Transactions that have not been executed, like those left out of the mix, have
+            // neither statistics nor counters
+            if let Some(stats) = stats_map.get(&transaction.id) {
+                data_stats.push(stats.clone());
+            }
+            if let Some(stats) = commit_stats_map.get(&transaction.id) {
+                data_commit_stats.push(stats.clone());
+            }
             let counters = match self.counters.get(&transaction.id) {
-                Some(counters) => counters,
-                None => {
-                    eprintln!("ERROR: no counters found for the transaction: {} ", transaction.name);
-                    std::process::exit(1);
-                }
+                Some(counters) => counters.clone(),
+                None => Counter {n_commits: 0, n_total: 0, total_duration_ms: 0.0, n_serialization_failures: 0},
+            };
+            let error_rate = match counters.n_total {
+                0 => 0.0,
+                n_total => (n_total - counters.n_commits) as f64 / n_total as f64 * 100.0,
             };
 
-            data_stats.push(stats.clone());
             data_summary.push(
                 TransactionSummary::new(
                     transaction.name,
+                    // Share of the transaction in the mix
+                    transaction.weight as f64 / total_weight as f64 * 100.0,
                     // Number of commits
                     counters.n_commits,
                     // Number of errors
                     counters.n_total - counters.n_commits,
+                    // Number of serialization failures
+                    counters.n_serialization_failures,
                     // Error rate
-                    (counters.n_total - counters.n_commits) as f64 / counters.n_total as f64 * 100.0,
+                    error_rate,
                     // Transactions per minute
                     (counters.n_commits as f64 / duration_ms.as_secs() as f64 * 60.0) as u32,
                     // Transactions per second
@@ -710,7 +2281,234 @@ impl Executor {
         // Print stats
         println!("Response times:");
         data_agg::print_transactions_stats(&data_stats);
+        // Commits are part of the transaction functions in procedure mode
+        if self.mode == TransactionMode::Statement {
+            println!("Commit times:");
+            data_agg::print_transactions_stats(&data_commit_stats);
+        }
+
+        if self.statement_timing {
+            self.print_statements_stats(&transactions_names);
+        }
+
+        if self.reconnect {
+            let data_connections = match data_agg::get_connection_stats(&self.target_dir) {
+                Ok(stats) => vec![stats],
+                Err(error) => {
+                    eprintln!("ERROR: {}", error);
+                    std::process::exit(1);
+                }
+            };
+            println!("Connection times:");
+            data_agg::print_connections_stats(&data_connections);
+        }
+
+        if let Some(size) = self.pool_size {
+            let data_pool = match data_agg::get_pool_stats(&self.target_dir, size) {
+                Ok(stats) => vec![stats],
+                Err(error) => {
+                    eprintln!("ERROR: {}", error);
+                    std::process::exit(1);
+                }
+            };
+            println!("Connection pool:");
+            data_agg::print_pool_stats(&data_pool);
+        }
+
+        if self.read_routing {
+            self.print_hosts_summary();
+        }
+
+        if self.failover {
+            self.print_outage_summary();
+        }
+
+        if !self.faults.is_empty() {
+            self.print_faults_summary();
+        }
+
+        if self.search.is_some() {
+            self.print_find_max_summary();
+        }
+        else if self.stages.len() > 1 {
+            self.print_stages_summary();
+        }
 
         self
     }
+
+    // Print the response times of each statement, by transaction
+    fn print_statements_stats(&mut self, transactions_names: &HashMap<u16, String>) {
+        let data_statements: Vec<StatementStatistics> = match self.target_dir.join(data_agg::STATEMENT_STATS_FILE).exists() {
+            true => match data_agg::get_statement_stats(&self.target_dir, transactions_names) {
+                Ok(data_statements) => data_statements,
+                Err(error) => {
+                    eprintln!("ERROR: {}", error);
+                    std::process::exit(1);
+                }
+            },
+            false => Vec::new(),
+        };
+
+        println!("Statement response times:");
+        data_agg::print_statements_stats(&data_statements);
+    }
+
+    // Print the throughput and the response times of each host, with --read-routing
+    fn print_hosts_summary(&mut self) {
+        let stats_map = match data_agg::get_host_stats(&self.target_dir) {
+            Ok(stats_map) => stats_map,
+            Err(error) => {
+                eprintln!("ERROR: {}", error);
+                std::process::exit(1);
+            }
+        };
+        let time_s = self.total_time_ms as f64 / 1000.0;
+
+        let mut data_hosts: Vec<HostSummary> = Vec::new();
+        for (i, host) in self.hosts.iter().enumerate() {
+            let host_id = i as u16;
+            let (n_commits, n_total) = match self.host_counters.get(&host_id) {
+                Some(counters) => (counters.n_commits, counters.n_total),
+                None => (0, 0),
+            };
+            let (mean, percentile_95, percentile_99) = stats_map.get(&host_id).cloned().unwrap_or((0.0, 0.0, 0.0));
+            data_hosts.push(HostSummary {
+                host: host.name.clone(),
+                role: host.role_name().to_string(),
+                n_commits,
+                n_errors: n_total - n_commits,
+                tps: (n_commits as f64 / time_s) as u32,
+                mean,
+                percentile_95,
+                percentile_99,
+            });
+        }
+
+        println!("Hosts:");
+        data_agg::print_hosts_summary(&data_hosts);
+    }
+
+    // Print the write outages of the clients, with --failover. The unavailability is the time
+    // during which at least one client was in outage.
+    fn print_outage_summary(&mut self) {
+        let outages = match self.target_dir.join(OUTAGE_LOG_FILE).exists() {
+            true => match data_agg::get_outages(&self.target_dir) {
+                Ok(outages) => outages,
+                Err(error) => {
+                    eprintln!("ERROR: {}", error);
+                    std::process::exit(1);
+                }
+            },
+            false => Vec::new(),
+        };
+
+        // Outages are sorted by start time
+        let mut unavailability_ms: i64 = 0;
+        let mut covered_until_ms = i64::MIN;
+        for (start_ms, end_ms, _, _) in outages.iter() {
+            let start_ms = (*start_ms).max(covered_until_ms);
+            if *end_ms > start_ms {
+                unavailability_ms += end_ms - start_ms;
+            }
+            covered_until_ms = covered_until_ms.max(*end_ms);
+        }
+        let mut clients: Vec<u32> = outages.iter().map(|(_, _, client_id, _)| *client_id).collect();
+        clients.sort_unstable();
+        clients.dedup();
+
+        let data_outages = vec![OutageSummary {
+            count: outages.len() as u64,
+            clients: clients.len() as u64,
+            unavailability: unavailability_ms as f64 / 1000.0,
+            max: outages.iter().map(|(start_ms, end_ms, _, _)| end_ms - start_ms).max().unwrap_or(0) as f64 / 1000.0,
+            lost: outages.iter().map(|(_, _, _, n_lost)| *n_lost as u64).sum(),
+        }];
+
+        println!("Outages:");
+        data_agg::print_outage_summary(&data_outages);
+    }
+
+    // Print the faults injected during the run, with --fault. Failed faults are reported in the
+    // error log only.
+    fn print_faults_summary(&mut self) {
+        let faults = match self.target_dir.join(FAULT_LOG_FILE).exists() {
+            true => match data_agg::get_faults(&self.target_dir) {
+                Ok(faults) => faults,
+                Err(error) => {
+                    eprintln!("ERROR: {}", error);
+                    std::process::exit(1);
+                }
+            },
+            false => Vec::new(),
+        };
+
+        let data_faults: Vec<FaultSummary> = faults
+            .into_iter()
+            .map(|(time_ms, fault, n_sessions, duration)| FaultSummary {
+                time: match DateTime::<Utc>::from_timestamp_millis(time_ms) {
+                    Some(time) => time.format("%H:%M:%S%.3f").to_string(),
+                    None => time_ms.to_string(),
+                },
+                fault,
+                n_sessions,
+                duration,
+            })
+            .collect();
+
+        println!("Faults:");
+        data_agg::print_faults_summary(&data_faults);
+    }
+
+    // Print the throughput and the response times of each stage of the load schedule
+    fn print_stages_summary(&mut self) {
+        let stats_map = match data_agg::get_stage_stats(&self.target_dir) {
+            Ok(stats_map) => stats_map,
+            Err(error) => {
+                eprintln!("ERROR: {}", error);
+                std::process::exit(1);
+            }
+        };
+
+        let mut data_stages: Vec<StageSummary> = Vec::new();
+        for (i, stage) in self.stages.iter().enumerate() {
+            let stage_id = i as u16 + 1;
+            let (n_commits, n_total) = match self.stage_counters.get(&stage_id) {
+                Some(counters) => (counters.n_commits, counters.n_total),
+                None => (0, 0),
+            };
+            let (mean, percentile_95, percentile_99) = stats_map.get(&stage_id).cloned().unwrap_or((0.0, 0.0, 0.0));
+            data_stages.push(StageSummary {
+                stage: stage_id,
+                clients: stage.clients,
+                n_commits,
+                n_errors: n_total - n_commits,
+                tpm: (n_commits as f64 / stage.time as f64 * 60.0) as u32,
+                tps: (n_commits as f64 / stage.time as f64) as u32,
+                mean,
+                percentile_95,
+                percentile_99,
+            });
+        }
+
+        println!("Stages:");
+        data_agg::print_stages_summary(&data_stages);
+    }
+
+    // Print the load levels measured by the saturation search and the highest one meeting the SLOs
+    fn print_find_max_summary(&mut self) {
+        let search = self.search.as_ref().unwrap();
+
+        println!("Saturation search:");
+        data_agg::print_find_max_summary(&search.summary());
+        match search.best() {
+            Some(point) => println!(
+                "Highest load meeting the SLOs: {} client(s), {:.3} TPS ({})",
+                point.clients,
+                point.tps,
+                search.format_values(point),
+            ),
+            None => println!("No load level meets the SLOs"),
+        }
+    }
 }
@@ -1,8 +1,27 @@
 use async_trait::async_trait;
 use postgres::Client;
+use postgres::types::Type;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use sqlx::PgConnection;
 use tabled::Tabled;
 
+use super::progress::ProgressReporter;
+use super::protocol::StmtTimer;
+use super::super::args::{IndexStorage, InitArgs, RunArgs, StorageArgs, TableStorage, TransactionMode};
+
+// Size of a database page, in bytes
+const PAGE_SIZE: u64 = 8_192;
+// Page header size, and size of the special space ending btree pages
+const PAGE_HEADER_SIZE: u64 = 24;
+const BTREE_SPECIAL_SIZE: u64 = 16;
+// Heap tuple header size, aligned, index tuple header size and line pointer size
+const HEAP_TUPLE_HEADER_SIZE: f64 = 24.0;
+const INDEX_TUPLE_HEADER_SIZE: f64 = 8.0;
+const LINE_POINTER_SIZE: f64 = 4.0;
+// Fillfactor of btree indexes when not set
+const BTREE_DEFAULT_FILLFACTOR: u8 = 90;
+
 // Transaction specifications
 #[derive(Clone)]
 pub struct BenchmarkTransaction {
@@ -14,6 +33,8 @@ pub struct BenchmarkTransaction {
     pub weight: u16,
     // Description of the transaction, useful for the report
     pub description: String,
+    // The transaction does not write, it can run on a standby
+    pub read_only: bool,
 }
 
 #[derive(Clone)]
@@ -21,6 +42,8 @@ pub struct Counter {
     pub n_commits: u64,
     pub n_total: u64,
     pub total_duration_ms: f64,
+    // Errors caused by serialization failures, included in n_total
+    pub n_serialization_failures: u64,
 }
 
 #[derive(Clone)]
@@ -28,6 +51,275 @@ pub struct BenchmarkStmt {
     pub sql: String,
 }
 
+// Statement executed during data loading, as planned before loading
+#[derive(Clone)]
+pub struct BenchmarkLoadStmt {
+    pub stmt: BenchmarkStmt,
+    // Populated table, if any
+    pub table: Option<String>,
+    // Estimated number of rows loaded by all the executions of the statement
+    pub rows: u64,
+}
+
+// Table definition
+#[derive(Clone)]
+pub struct BenchmarkTable {
+    // Table name
+    pub name: String,
+    // Columns definition, as written between the parentheses of CREATE TABLE
+    pub columns: String,
+}
+
+// Index definition, used for primary keys and additional indexes
+#[derive(Clone)]
+pub struct BenchmarkIndex {
+    // Index name
+    pub name: String,
+    // Indexed table
+    pub table: String,
+    // Index definition: optional access method followed by the list of columns
+    pub definition: String,
+    // Unique index?
+    pub unique: bool,
+}
+
+impl BenchmarkTable {
+    // Returns the name and the type of each column, based on the columns definition where each
+    // column is defined on its own line.
+    fn column_defs(&self) -> Vec<(&str, &str)> {
+        self.columns
+            .lines()
+            .filter_map(|line| {
+                let mut words = line.split_whitespace();
+                Some((words.next()?, words.next()?.trim_end_matches(',')))
+            })
+            .collect()
+    }
+
+    // Returns the type of each column
+    pub fn column_types(&self) -> Vec<Type> {
+        self.column_defs()
+            .into_iter()
+            .map(|(_, column_type)| match column_type.split('(').next().unwrap_or_default() {
+                "INTEGER" => Type::INT4,
+                "REAL" => Type::FLOAT4,
+                "NUMERIC" => Type::NUMERIC,
+                "VARCHAR" => Type::VARCHAR,
+                "CHAR" => Type::BPCHAR,
+                "TIMESTAMP" => Type::TIMESTAMP,
+                _ => Type::TEXT,
+            })
+            .collect()
+    }
+
+    // Estimated average width of the values of the given columns, including alignment padding.
+    // text_length gives the average length of text columns values, when known.
+    fn data_width(&self, columns: &[&str], text_length: &dyn Fn(&str) -> Option<f64>) -> f64 {
+        let column_defs = self.column_defs();
+        let mut width: f64 = 0.0;
+        for column in columns.iter() {
+            if let Some((name, column_type)) = column_defs.iter().find(|(name, _)| name == column) {
+                let (value_width, alignment) = column_width(column_type, text_length(name));
+                width = (width / alignment).ceil() * alignment + value_width;
+            }
+        }
+
+        width
+    }
+
+    // Estimated size of the table holding the given number of rows, in bytes, once vacuumed
+    pub fn estimate_size(&self, rows: u64, storage: &TableStorage, text_length: &dyn Fn(&str) -> Option<f64>) -> u64 {
+        let columns: Vec<&str> = self.column_defs().into_iter().map(|(name, _)| name).collect();
+        let tuple_size = ((HEAP_TUPLE_HEADER_SIZE + self.data_width(&columns, text_length)) / 8.0).ceil() * 8.0 + LINE_POINTER_SIZE;
+        let fillfactor = storage.fillfactor.unwrap_or(100) as f64;
+        let rows_per_page = ((PAGE_SIZE - PAGE_HEADER_SIZE) as f64 * fillfactor / 100.0 / tuple_size).floor().max(1.0) as u64;
+
+        // Free space map and visibility map pages, created by VACUUM
+        (rows.div_ceil(rows_per_page) + 4) * PAGE_SIZE
+    }
+
+    pub fn drop_stmt(&self) -> BenchmarkStmt {
+        BenchmarkStmt {
+            sql: format!("DROP TABLE IF EXISTS {} CASCADE", self.name),
+        }
+    }
+
+    // Returns the CREATE TABLE statement, including the storage options
+    pub fn create_stmt(&self, storage: &TableStorage) -> BenchmarkStmt {
+        let mut sql = format!(
+            "CREATE {}TABLE {} ({})",
+            if storage.unlogged { "UNLOGGED " } else { "" },
+            self.name,
+            self.columns,
+        );
+        if let Some(access_method) = &storage.access_method {
+            sql.push_str(&format!(" USING {}", access_method));
+        }
+        if let Some(fillfactor) = storage.fillfactor {
+            sql.push_str(&format!(" WITH (fillfactor={})", fillfactor));
+        }
+        if let Some(tablespace) = &storage.tablespace {
+            sql.push_str(&format!(" TABLESPACE {}", tablespace));
+        }
+
+        BenchmarkStmt { sql }
+    }
+}
+
+// Estimated width and alignment of one column value, in bytes. Text values up to 126 bytes use a
+// 1 byte header, longer ones a 4 bytes header. NUMERIC values are small integers.
+fn column_width(column_type: &str, text_length: Option<f64>) -> (f64, f64) {
+    let mut parts = column_type.split(['(', ')']);
+    let type_name = parts.next().unwrap_or_default();
+    // Declared length of CHAR(n) and VARCHAR(n)
+    let declared_length = parts.next().and_then(|n| n.parse::<f64>().ok());
+
+    match type_name {
+        "INTEGER" | "REAL" => (4.0, 4.0),
+        "TIMESTAMP" => (8.0, 8.0),
+        "NUMERIC" => (7.0, 1.0),
+        "CHAR" | "VARCHAR" => {
+            let length = match type_name {
+                "CHAR" => declared_length,
+                _ => text_length.or(declared_length),
+            }.unwrap_or_default();
+            if length < 127.0 { (1.0 + length, 1.0) } else { (4.0 + length, 4.0) }
+        },
+        _ => (8.0, 8.0),
+    }
+}
+
+impl BenchmarkIndex {
+    // Returns the indexed columns, listed between the parentheses of the definition
+    pub fn columns(&self) -> Vec<&str> {
+        let start = self.definition.find('(').map(|i| i + 1).unwrap_or(0);
+        let end = self.definition.rfind(')').unwrap_or(self.definition.len());
+
+        self.definition[start..end].split(',').map(|column| column.trim()).collect()
+    }
+
+    // Estimated size of the btree index of the given table, in bytes. Inner pages are counted as
+    // 1% of the leaf pages, plus the metapage.
+    pub fn estimate_size(&self, table: &BenchmarkTable, rows: u64, storage: &IndexStorage, text_length: &dyn Fn(&str) -> Option<f64>) -> u64 {
+        let tuple_size = ((INDEX_TUPLE_HEADER_SIZE + table.data_width(&self.columns(), text_length)) / 8.0).ceil() * 8.0 + LINE_POINTER_SIZE;
+        let fillfactor = storage.fillfactor.unwrap_or(BTREE_DEFAULT_FILLFACTOR) as f64;
+        let rows_per_page = ((PAGE_SIZE - PAGE_HEADER_SIZE - BTREE_SPECIAL_SIZE) as f64 * fillfactor / 100.0 / tuple_size).floor().max(1.0) as u64;
+        let leaf_pages = rows.div_ceil(rows_per_page);
+
+        (leaf_pages + leaf_pages / 100 + 1) * PAGE_SIZE
+    }
+
+    // Returns the ALTER TABLE .. ADD PRIMARY KEY statement, including the storage options
+    pub fn pkey_stmt(&self, storage: &IndexStorage) -> BenchmarkStmt {
+        let mut sql = format!("ALTER TABLE {} ADD PRIMARY KEY {}", self.table, self.definition);
+        if let Some(fillfactor) = storage.fillfactor {
+            sql.push_str(&format!(" WITH (fillfactor={})", fillfactor));
+        }
+        if let Some(tablespace) = &storage.tablespace {
+            sql.push_str(&format!(" USING INDEX TABLESPACE {}", tablespace));
+        }
+
+        BenchmarkStmt { sql }
+    }
+
+    // Returns the CREATE INDEX statement, including the storage options
+    pub fn index_stmt(&self, storage: &IndexStorage) -> BenchmarkStmt {
+        let mut sql = format!(
+            "CREATE {}INDEX {} ON {} {}",
+            if self.unique { "UNIQUE " } else { "" },
+            self.name,
+            self.table,
+            self.definition,
+        );
+        if let Some(fillfactor) = storage.fillfactor {
+            sql.push_str(&format!(" WITH (fillfactor={})", fillfactor));
+        }
+        if let Some(tablespace) = &storage.tablespace {
+            sql.push_str(&format!(" TABLESPACE {}", tablespace));
+        }
+
+        BenchmarkStmt { sql }
+    }
+}
+
+// Data loading statistics of one table
+#[derive(Clone)]
+pub struct LoadStats {
+    pub table: String,
+    // Number of rows sent
+    pub rows: u64,
+    // Number of bytes sent, COPY data only
+    pub bytes: u64,
+    // Time spent loading the table
+    pub duration_us: u128,
+}
+
+impl LoadStats {
+    pub fn new(table: &str) -> LoadStats {
+        LoadStats {
+            table: table.to_string(),
+            rows: 0,
+            bytes: 0,
+            duration_us: 0,
+        }
+    }
+
+    // Add stats to the list, summing it up with the stats of the same table if any
+    pub fn merge(all_stats: &mut Vec<LoadStats>, stats: LoadStats) {
+        match all_stats.iter_mut().find(|s| s.table == stats.table) {
+            Some(s) => {
+                s.rows += stats.rows;
+                s.bytes += stats.bytes;
+                s.duration_us += stats.duration_us;
+            },
+            None => all_stats.push(stats),
+        }
+    }
+}
+
+// Estimated size of one table and of its indexes, in bytes
+#[derive(Clone)]
+pub struct SizeEstimate {
+    pub table: String,
+    pub rows: u64,
+    pub table_bytes: u64,
+    pub pkey_bytes: u64,
+    pub index_bytes: u64,
+}
+
+#[derive(Tabled)]
+pub struct SizeSummary {
+    #[tabled(rename = "Table")]
+    pub table: String,
+    #[tabled(rename = "Rows")]
+    pub rows: u64,
+    #[tabled(rename = "Table (MB)")]
+    pub table_mb: f64,
+    #[tabled(rename = "Primary key (MB)")]
+    pub pkey_mb: f64,
+    #[tabled(rename = "Indexes (MB)")]
+    pub index_mb: f64,
+    #[tabled(rename = "Total (MB)")]
+    pub total_mb: f64,
+}
+
+#[derive(Tabled)]
+pub struct LoadSummary {
+    #[tabled(rename = "Table")]
+    pub table: String,
+    #[tabled(rename = "Rows")]
+    pub rows: u64,
+    #[tabled(rename = "Size (MB)")]
+    pub size_mb: f64,
+    // Time spent by all the jobs on this table
+    #[tabled(rename = "Time (s)")]
+    pub duration_s: f64,
+    #[tabled(rename = "Rows/s")]
+    pub rows_per_sec: f64,
+    #[tabled(rename = "MB/s")]
+    pub mb_per_sec: f64,
+}
+
 #[derive(Tabled,Clone,Debug)]
 pub struct ResponseTimeStatistics {
     #[tabled(rename = "Transaction")]
@@ -46,20 +338,198 @@ pub struct ResponseTimeStatistics {
     pub percentile_99: f64,
 }
 
+// Response times of one statement of a transaction, with --statement-timing
+#[derive(Tabled)]
+pub struct StatementStatistics {
+    #[tabled(rename = "Transaction")]
+    pub name: String,
+    #[tabled(rename = "Statement")]
+    pub statement: String,
+    #[tabled(rename = "Executions")]
+    pub count: u64,
+    #[tabled(rename = "Avg. (ms)")]
+    pub mean: f64,
+    #[tabled(rename = "Min. (ms)")]
+    pub min: f64,
+    #[tabled(rename = "Max. (ms)")]
+    pub max: f64,
+    #[tabled(rename = "95% (ms)")]
+    pub percentile_95: f64,
+    #[tabled(rename = "99% (ms)")]
+    pub percentile_99: f64,
+}
+
+// Connection times of the clients, with --connect-per-transaction or --reconnect-every
+#[derive(Tabled)]
+pub struct ConnectionStatistics {
+    #[tabled(rename = "Connections")]
+    pub count: u64,
+    #[tabled(rename = "Avg. (ms)")]
+    pub mean: f64,
+    #[tabled(rename = "Min. (ms)")]
+    pub min: f64,
+    #[tabled(rename = "Max. (ms)")]
+    pub max: f64,
+    #[tabled(rename = "Std. Dev. (ms)")]
+    pub std: f64,
+    #[tabled(rename = "95% (ms)")]
+    pub percentile_95: f64,
+    #[tabled(rename = "99% (ms)")]
+    pub percentile_99: f64,
+}
+
+// Throughput and response times of one host, with --read-routing
+#[derive(Tabled)]
+pub struct HostSummary {
+    #[tabled(rename = "Host")]
+    pub host: String,
+    #[tabled(rename = "Role")]
+    pub role: String,
+    #[tabled(rename = "Committed")]
+    pub n_commits: u64,
+    #[tabled(rename = "Errors")]
+    pub n_errors: u64,
+    #[tabled(rename = "TPS")]
+    pub tps: u32,
+    #[tabled(rename = "Avg. (ms)")]
+    pub mean: f64,
+    #[tabled(rename = "95% (ms)")]
+    pub percentile_95: f64,
+    #[tabled(rename = "99% (ms)")]
+    pub percentile_99: f64,
+}
+
+// Waits for a connection of the pool, with --pool-size
+#[derive(Tabled)]
+pub struct PoolStatistics {
+    #[tabled(rename = "Pool size")]
+    pub size: u32,
+    #[tabled(rename = "Acquisitions")]
+    pub count: u64,
+    // Share of the acquisitions made while no connection was idle
+    #[tabled(rename = "Saturation (%)")]
+    pub saturation: f64,
+    #[tabled(rename = "Avg. wait (ms)")]
+    pub mean: f64,
+    #[tabled(rename = "Max. wait (ms)")]
+    pub max: f64,
+    #[tabled(rename = "95% (ms)")]
+    pub percentile_95: f64,
+    #[tabled(rename = "99% (ms)")]
+    pub percentile_99: f64,
+}
+
+// Write outages of the clients, with --failover
+#[derive(Tabled)]
+pub struct OutageSummary {
+    #[tabled(rename = "Outages")]
+    pub count: u64,
+    #[tabled(rename = "Clients")]
+    pub clients: u64,
+    // Time during which at least one client could not commit
+    #[tabled(rename = "Unavailability (s)")]
+    pub unavailability: f64,
+    #[tabled(rename = "Longest outage (s)")]
+    pub max: f64,
+    // Transactions failed during the outages
+    #[tabled(rename = "Lost transactions")]
+    pub lost: u64,
+}
+
+// Fault injected during the run, with --fault
+#[derive(Tabled)]
+pub struct FaultSummary {
+    #[tabled(rename = "Time (UTC)")]
+    pub time: String,
+    #[tabled(rename = "Fault")]
+    pub fault: String,
+    // Sessions terminated or canceled
+    #[tabled(rename = "Sessions")]
+    pub n_sessions: u32,
+    // Time taken by the fault, or time the lock is held
+    #[tabled(rename = "Duration (ms)")]
+    pub duration: f64,
+}
+
+// Throughput and response times of one stage of the load schedule
+#[derive(Tabled)]
+pub struct StageSummary {
+    #[tabled(rename = "Stage")]
+    pub stage: u16,
+    #[tabled(rename = "Clients")]
+    pub clients: u16,
+    #[tabled(rename = "Committed")]
+    pub n_commits: u64,
+    #[tabled(rename = "Errors")]
+    pub n_errors: u64,
+    #[tabled(rename = "TPM")]
+    pub tpm: u32,
+    #[tabled(rename = "TPS")]
+    pub tps: u32,
+    #[tabled(rename = "Avg. (ms)")]
+    pub mean: f64,
+    #[tabled(rename = "95% (ms)")]
+    pub percentile_95: f64,
+    #[tabled(rename = "99% (ms)")]
+    pub percentile_99: f64,
+}
+
+// One measured level of the saturation search
+#[derive(Tabled)]
+pub struct FindMaxSummary {
+    #[tabled(rename = "Window")]
+    pub window: u16,
+    #[tabled(rename = "Clients")]
+    pub clients: u16,
+    #[tabled(rename = "TPS")]
+    pub tps: f64,
+    #[tabled(rename = "TPM")]
+    pub tpm: f64,
+    #[tabled(rename = "SLO")]
+    pub slo: String,
+    #[tabled(rename = "Met")]
+    pub passed: String,
+}
+
+// Response time and commit time of a committed transaction, in us. The commit time is None when
+// the client does not issue the commit, like with --mode procedure.
+pub type TransactionDurations = (u128, Option<u128>);
+
 // ReadWrite trait for all benchmarks implementing read/write workload
 #[async_trait]
 pub trait ReadWrite {
-    async fn execute_rw_transaction(&self, conn: &mut PgConnection, transaction :&BenchmarkTransaction) -> Result<u128, Box<dyn std::error::Error>>;
+    // Prepare the benchmark object of one client, before running the workload
+    async fn prepare_rw(&mut self, conn: &mut PgConnection, args: &RunArgs, client_id: u32) -> Result<(), Box<dyn std::error::Error>>;
+    // Home object ID and sub-object ID of the given client, used with --affinity
+    fn get_client_home(&self, client_id: u32) -> (u32, u32);
+    async fn execute_rw_transaction(&self, conn: &mut PgConnection, transaction :&BenchmarkTransaction, rng: &mut StdRng, timer: &mut StmtTimer) -> Result<TransactionDurations, Box<dyn std::error::Error>>;
+}
+
+// Random generator of one stream of data: one warehouse during data loading, one client during
+// the workload. With a seed, each stream gets its own deterministic generator so the generated
+// values do not depend on how streams are spread across jobs or threads. Without seed, the
+// generator is seeded from the operating system.
+pub fn stream_rng(seed: Option<u64>, stream: u64) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15)),
+        None => StdRng::from_entropy(),
+    }
 }
 
 #[derive(Tabled)]
 pub struct TransactionSummary {
     #[tabled(rename = "Transaction")]
     name: String,
+    // Share of the transaction in the mix
+    #[tabled(rename = "Mix (%)")]
+    mix: f64,
     #[tabled(rename = "Committed")]
     n_commits: u64,
     #[tabled(rename = "Errors")]
     n_errors: u64,
+    // Errors caused by serialization failures, included in the errors
+    #[tabled(rename = "Serialization failures")]
+    n_serialization_failures: u64,
     #[tabled(rename = "Error rate (%)")]
     error_rate: f64,
     #[tabled(rename = "TPM")]
@@ -69,11 +539,14 @@ pub struct TransactionSummary {
 }
 
 impl TransactionSummary {
-    pub fn new(name: String, n_commits: u64, n_errors: u64, error_rate: f64, tpm: u32, tps: u32) -> TransactionSummary {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(name: String, mix: f64, n_commits: u64, n_errors: u64, n_serialization_failures: u64, error_rate: f64, tpm: u32, tps: u32) -> TransactionSummary {
         TransactionSummary {
             name: name,
+            mix,
             n_commits: n_commits,
             n_errors: n_errors,
+            n_serialization_failures,
             error_rate: error_rate,
             tpm: tpm,
             tps: tps,
@@ -82,14 +555,34 @@ impl TransactionSummary {
 }
 
 pub trait Benchmark:ReadWrite {
-    fn initialize_schema(&self, client: &mut Client) -> Result<u128, postgres::Error>;
-    fn pre_load_data(&self, client: &mut Client) -> Result<u128, String>;
-    fn load_data(&self, client: &mut Client, ids: Vec<u32>) -> Result<u128, String>;
+    fn initialize_schema(&self, client: &mut Client, storage: &StorageArgs) -> Result<u128, postgres::Error>;
+    fn pre_load_data(&self, client: &mut Client, args: &InitArgs) -> Result<Vec<LoadStats>, String>;
+    fn load_data(&self, client: &mut Client, ids: Vec<u32>, args: &InitArgs, progress: &mut ProgressReporter) -> Result<Vec<LoadStats>, String>;
     fn get_default_max_id(&self, client: &mut Client) -> Result<u32, postgres::Error>;
     fn get_transactions_rw(&self) -> Vec<BenchmarkTransaction>;
-    fn get_table_ddls(&self) -> Vec<BenchmarkStmt>;
-    fn get_pkey_ddls(&self) -> Vec<BenchmarkStmt>;
+    // Override the weights of the listed transactions, the others keep their default weight
+    fn set_transaction_weights(&mut self, weights: &[(String, u16)]) -> Result<(), String>;
+    // Execute the read/write transactions statement by statement, or as function calls
+    fn set_transaction_mode(&mut self, mode: TransactionMode);
+    fn get_tables(&self) -> Vec<BenchmarkTable>;
+    fn get_pkeys(&self) -> Vec<BenchmarkIndex>;
+    fn get_indexes(&self) -> Vec<BenchmarkIndex>;
+    // Check that the data of every object ID between min_id and max_id is loaded
+    fn check_ids(&self, client: &mut Client, min_id: u32, max_id: u32) -> Result<(), String>;
+    // Scans reading the table and index pages of the object IDs between min_id and max_id
+    fn get_prewarm_stmts(&self, min_id: u32, max_id: u32) -> Vec<BenchmarkStmt>;
+    fn get_table_ddls(&self, storage: &StorageArgs) -> Vec<BenchmarkStmt>;
+    fn get_pkey_ddls(&self, storage: &StorageArgs) -> Vec<BenchmarkStmt>;
     fn get_fkey_ddls(&self) -> Vec<BenchmarkStmt>;
-    fn get_index_ddls(&self) -> Vec<BenchmarkStmt>;
+    fn get_index_ddls(&self, storage: &StorageArgs) -> Vec<BenchmarkStmt>;
     fn get_vacuum_stmts(&self) -> Vec<BenchmarkStmt>;
+    // Functions implementing the read/write transactions, called in procedure mode
+    fn get_procedure_ddls(&self) -> Vec<BenchmarkStmt>;
+    fn get_procedure_names(&self) -> Vec<String>;
+    // Statements executed by pre_load_data() and load_data(), in execution order
+    fn get_load_stmts(&self, args: &InitArgs) -> Vec<BenchmarkLoadStmt>;
+    // Estimated size of each table and of its indexes, once loaded
+    fn estimate_sizes(&self, storage: &StorageArgs) -> Vec<SizeEstimate>;
+    // SQL templates of one read/write transaction, in execution order
+    fn get_transaction_stmts(&self, transaction: &BenchmarkTransaction) -> Vec<BenchmarkStmt>;
 }
@@ -0,0 +1,300 @@
+use std::error::Error;
+use std::io::Write;
+use std::time::Instant;
+
+use bytes::{BufMut, BytesMut};
+use chrono::DateTime;
+use crossbeam_channel::{Receiver, Sender};
+use postgres::Client;
+use postgres::binary_copy::BinaryCopyInWriter;
+use postgres::types::{IsNull, ToSql, Type, to_sql_checked};
+
+use super::benchmark::LoadStats;
+
+// Number of rows sent to the COPY consumer thread at once
+const CHUNK_SIZE: usize = 1_000;
+// Difference between the Unix epoch and the Postgres epoch (2000-01-01), in microseconds
+const PG_EPOCH_OFFSET_US: i64 = 946_684_800_000_000;
+
+// One column value, as sent by COPY
+#[derive(Debug, Clone)]
+pub enum CopyValue {
+    Null,
+    Int(i32),
+    Real(f32),
+    // NUMERIC value without fractional part
+    Numeric(i64),
+    Text(String),
+    // TIMESTAMP value, in microseconds since the Unix epoch
+    Timestamp(i64),
+}
+
+// One table row
+pub type CopyRow = Vec<CopyValue>;
+
+impl CopyValue {
+    // Append the value to buf, using the COPY text format
+    fn write_text(&self, buf: &mut String) {
+        match self {
+            CopyValue::Null => buf.push_str("\\N"),
+            CopyValue::Int(v) => buf.push_str(itoa::Buffer::new().format(*v)),
+            CopyValue::Real(v) => buf.push_str(ryu::Buffer::new().format(*v)),
+            CopyValue::Numeric(v) => buf.push_str(itoa::Buffer::new().format(*v)),
+            CopyValue::Text(v) => buf.push_str(v),
+            CopyValue::Timestamp(v) => {
+                let ts = DateTime::from_timestamp_micros(*v).unwrap_or_default();
+                buf.push_str(&ts.format("%Y-%m-%d %H:%M:%S%.6f").to_string());
+            },
+        }
+    }
+
+    // Number of bytes used by the value in the COPY binary format, including the length word
+    fn binary_size(&self) -> usize {
+        4 + match self {
+            CopyValue::Null => 0,
+            CopyValue::Int(_) | CopyValue::Real(_) => 4,
+            CopyValue::Numeric(v) => 8 + 2 * numeric_digits(*v).len(),
+            CopyValue::Text(v) => v.len(),
+            CopyValue::Timestamp(_) => 8,
+        }
+    }
+}
+
+// Split an integer into base 10000 digits, most significant first, as expected by the NUMERIC
+// binary format. Trailing zero digits are removed.
+fn numeric_digits(value: i64) -> Vec<i16> {
+    let mut digits = Vec::new();
+    let mut v = value.unsigned_abs();
+    while v > 0 {
+        digits.push((v % 10_000) as i16);
+        v /= 10_000;
+    }
+    digits.reverse();
+    // The weight keeps track of the removed digits
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+
+    digits
+}
+
+impl ToSql for CopyValue {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match (self, ty) {
+            (CopyValue::Null, _) => return Ok(IsNull::Yes),
+            (CopyValue::Int(v), &Type::INT4) => out.put_i32(*v),
+            (CopyValue::Real(v), &Type::FLOAT4) => out.put_f32(*v),
+            (CopyValue::Numeric(v), &Type::NUMERIC) => {
+                let digits = numeric_digits(*v);
+                // Number of base 10000 digits before the decimal point, minus one
+                let mut weight: i16 = -1;
+                let mut n = v.unsigned_abs();
+                while n > 0 {
+                    weight += 1;
+                    n /= 10_000;
+                }
+                out.put_i16(digits.len() as i16);
+                out.put_i16(weight.max(0));
+                out.put_u16(if *v < 0 { 0x4000 } else { 0x0000 });
+                // Display scale
+                out.put_u16(0);
+                for digit in digits {
+                    out.put_i16(digit);
+                }
+            },
+            (CopyValue::Text(v), &Type::VARCHAR | &Type::BPCHAR | &Type::TEXT) => out.put_slice(v.as_bytes()),
+            (CopyValue::Timestamp(v), &Type::TIMESTAMP) => out.put_i64(*v - PG_EPOCH_OFFSET_US),
+            _ => return Err(format!("cannot encode {:?} as {}", self, ty).into()),
+        }
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
+
+// COPY command loading one table using the text format
+pub fn text_copy_sql(table: &str) -> String {
+    format!("COPY {} FROM stdin", table)
+}
+
+// COPY command loading one table using the binary format
+pub fn binary_copy_sql(table: &str) -> String {
+    format!("COPY {} FROM stdin WITH (FORMAT binary)", table)
+}
+
+// Load rows using text COPY, running one COPY command every batch_size rows
+pub struct TextCopyLoader<'a> {
+    client: &'a mut Client,
+    batch: String,
+    batch_rows: u32,
+    batch_size: u32,
+    stats: LoadStats,
+    // Like the binary COPY, the load time includes the rows generation
+    start: Instant,
+}
+
+impl<'a> TextCopyLoader<'a> {
+    pub fn new(client: &'a mut Client, table: &str, batch_size: u32) -> TextCopyLoader<'a> {
+        TextCopyLoader {
+            client,
+            batch: String::new(),
+            batch_rows: 0,
+            batch_size,
+            stats: LoadStats::new(table),
+            start: Instant::now(),
+        }
+    }
+
+    pub fn push(&mut self, row: CopyRow) -> Result<(), String> {
+        for (i, value) in row.iter().enumerate() {
+            if i > 0 {
+                self.batch.push('\t');
+            }
+            value.write_text(&mut self.batch);
+        }
+        self.batch.push('\n');
+        self.batch_rows += 1;
+
+        if self.batch_rows >= self.batch_size {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    // Send the pending rows using one COPY command
+    fn flush(&mut self) -> Result<(), String> {
+        if self.batch_rows == 0 {
+            return Ok(());
+        }
+        let mut writer = match self.client.copy_in(text_copy_sql(&self.stats.table).as_str()) {
+            Ok(w) => w,
+            Err(e) => return Err(e.to_string()),
+        };
+        match writer.write_all(self.batch.as_bytes()) {
+            Ok(_) => (),
+            Err(e) => return Err(e.to_string()),
+        }
+        match writer.finish() {
+            Ok(_) => (),
+            Err(e) => return Err(e.to_string()),
+        }
+
+        self.stats.rows += self.batch_rows as u64;
+        self.stats.bytes += self.batch.len() as u64;
+        self.batch.clear();
+        self.batch_rows = 0;
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<LoadStats, String> {
+        self.flush()?;
+        self.stats.duration_us = self.start.elapsed().as_micros();
+
+        Ok(self.stats)
+    }
+}
+
+// Messages sent by the rows producer thread to the binary COPY consumer
+pub enum CopyMessage {
+    // Start loading a new table: table name and columns types
+    Start(String, Vec<Type>),
+    Rows(Vec<CopyRow>),
+    End,
+}
+
+// Producer side of the binary COPY pipeline: rows are sent by chunks to the consumer
+pub struct CopySender {
+    tx: Sender<CopyMessage>,
+    chunk: Vec<CopyRow>,
+}
+
+impl CopySender {
+    pub fn new(tx: Sender<CopyMessage>) -> CopySender {
+        CopySender {
+            tx,
+            chunk: Vec::with_capacity(CHUNK_SIZE),
+        }
+    }
+
+    fn send(&self, message: CopyMessage) -> Result<(), String> {
+        // The consumer has stopped, the error is reported by the consumer itself
+        self.tx.send(message).map_err(|_| "COPY consumer stopped".to_string())
+    }
+
+    pub fn start(&mut self, table: &str, types: Vec<Type>) -> Result<(), String> {
+        self.send(CopyMessage::Start(table.to_string(), types))
+    }
+
+    pub fn push(&mut self, row: CopyRow) -> Result<(), String> {
+        self.chunk.push(row);
+        if self.chunk.len() >= CHUNK_SIZE {
+            let chunk = std::mem::replace(&mut self.chunk, Vec::with_capacity(CHUNK_SIZE));
+            self.send(CopyMessage::Rows(chunk))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn end(&mut self) -> Result<(), String> {
+        if !self.chunk.is_empty() {
+            let chunk = std::mem::take(&mut self.chunk);
+            self.send(CopyMessage::Rows(chunk))?;
+        }
+        self.send(CopyMessage::End)
+    }
+}
+
+// Consumer side of the binary COPY pipeline: runs one binary COPY command per Start/End
+// sequence received, until the producer hangs up. Returns the loading statistics of each table.
+pub fn consume_binary_copy(client: &mut Client, rx: Receiver<CopyMessage>) -> Result<Vec<LoadStats>, String> {
+    let mut all_stats: Vec<LoadStats> = Vec::new();
+
+    // The producer closes the channel once everything has been sent
+    while let Ok(message) = rx.recv() {
+        let (table, types) = match message {
+            CopyMessage::Start(table, types) => (table, types),
+            _ => return Err("unexpected COPY message".to_string()),
+        };
+        let start = Instant::now();
+        let mut stats = LoadStats::new(&table);
+
+        let writer = match client.copy_in(binary_copy_sql(&table).as_str()) {
+            Ok(w) => w,
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut writer = BinaryCopyInWriter::new(writer, &types);
+        loop {
+            match rx.recv() {
+                Ok(CopyMessage::Rows(rows)) => {
+                    for row in rows.iter() {
+                        match writer.write_raw(row.iter()) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e.to_string()),
+                        }
+                        // Tuple header: number of fields
+                        stats.bytes += 2 + row.iter().map(|v| v.binary_size() as u64).sum::<u64>();
+                        stats.rows += 1;
+                    }
+                },
+                Ok(CopyMessage::End) => break,
+                Ok(CopyMessage::Start(_, _)) => return Err("unexpected COPY message".to_string()),
+                Err(_) => return Err("COPY producer stopped".to_string()),
+            }
+        }
+        match writer.finish() {
+            Ok(_) => (),
+            Err(e) => return Err(e.to_string()),
+        }
+        stats.duration_us = start.elapsed().as_micros();
+        LoadStats::merge(&mut all_stats, stats);
+    }
+
+    Ok(all_stats)
+}
@@ -1,8 +1,8 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 
 use polars::prelude::*;
-use super::benchmark::{BenchmarkTransaction, ResponseTimeStatistics, TransactionSummary};
+use super::benchmark::{BenchmarkTransaction, ConnectionStatistics, FaultSummary, FindMaxSummary, HostSummary, LoadSummary, OutageSummary, PoolStatistics, ResponseTimeStatistics, SizeSummary, StageSummary, StatementStatistics, TransactionSummary};
 use tabled::{
     object::{Rows, Object, Columns},
     Alignment,
@@ -11,8 +11,17 @@ use tabled::{
     Table,
 };
 
+// Events marked in the time series: outages of the clients, start and end timestamps in seconds,
+// and injected faults, timestamp in seconds and name
+#[derive(Default)]
+pub struct RunEvents {
+    pub outages: Vec<(i64, i64)>,
+    pub faults: Vec<(i64, String)>,
+}
+
 // Aggregates collected transaction data (response time, throughput) and saves it as CSV files.
-pub fn aggregate_tpcc_data(log_file: &str, target_dir: &PathBuf, transactions: &Vec<BenchmarkTransaction>) -> Result<(), Box<dyn std::error::Error>> {
+// The commit times statistics are only produced when the client issues the commits.
+pub fn aggregate_tpcc_data(log_file: &str, target_dir: &PathBuf, transactions: &Vec<BenchmarkTransaction>, events: &RunEvents, commit_times: bool) -> Result<(), Box<dyn std::error::Error>> {
     // Transaction log file parsing
     let df = LazyCsvReader::new(target_dir.join(log_file))
         .with_delimiter(b' ')
@@ -37,6 +46,8 @@ pub fn aggregate_tpcc_data(log_file: &str, target_dir: &PathBuf, transactions: &
             ])
             .sort("time_s", Default::default())
             .collect()?;
+        // No commit during the outages
+        tpm_df = mark_events(tpm_df, df.clone().filter(col("column_3").eq(transaction.id as i64)), events, Some(0))?;
 
         // Save data as a CSV file
         let mut file = std::fs::File::create(target_dir.join(format!("pgmtr-tpm-{}.csv", transaction.name)))?;
@@ -57,6 +68,7 @@ pub fn aggregate_tpcc_data(log_file: &str, target_dir: &PathBuf, transactions: &
             ])
             .sort("time_s", Default::default())
             .collect()?;
+        response_time_df = mark_events(response_time_df, df.clone().filter(col("column_3").eq(transaction.id as i64)), events, None)?;
 
         // Response times statistics
         let mut stats = df
@@ -77,6 +89,24 @@ pub fn aggregate_tpcc_data(log_file: &str, target_dir: &PathBuf, transactions: &
         // Save statistics
         let mut file_stats = std::fs::File::create(target_dir.join(format!("pgmtr-stats-{}.csv", transaction.name)))?;
         CsvWriter::new(&mut file_stats).finish(&mut stats)?;
+
+        // Commit times statistics
+        if commit_times {
+            let mut commit_stats = df
+                .clone()
+                .filter(
+                    col("column_3").eq(transaction.id as i64)
+                )
+                .select([
+                    col("column_6").alias("commit_time_ms"),
+                ])
+                .collect()?
+                .describe(Some(&[0.95, 0.99]))
+                .select(["describe", "commit_time_ms"])?;
+
+            let mut file_commit_stats = std::fs::File::create(target_dir.join(format!("pgmtr-commit-stats-{}.csv", transaction.name)))?;
+            CsvWriter::new(&mut file_commit_stats).finish(&mut commit_stats)?;
+        }
     }
 
     // Produce total TPM data, including data from all transaction types
@@ -91,16 +121,458 @@ pub fn aggregate_tpcc_data(log_file: &str, target_dir: &PathBuf, transactions: &
         ])
         .sort("time_s", Default::default())
         .collect()?;
+    tpm_all_df = mark_events(tpm_all_df, df.clone(), events, Some(0))?;
 
     // Save data as a CSV file
     let mut file = std::fs::File::create(target_dir.join("pgmtr-tpm-all.csv"))?;
     CsvWriter::new(&mut file).finish(&mut tpm_all_df)?;
 
+    // Response times statistics of each stage, from all transaction types. Transactions executed
+    // during the rampup are tagged with stage 0.
+    let mut stages_df = df
+        .clone()
+        .filter(
+            col("column_5").gt(lit(0))
+        )
+        .groupby([col("column_5")])
+        .agg([
+            col("column_4").mean().alias("mean"),
+            col("column_4").quantile(lit(0.95), QuantileInterpolOptions::Linear).alias("95%"),
+            col("column_4").quantile(lit(0.99), QuantileInterpolOptions::Linear).alias("99%"),
+        ])
+        .select([
+            col("column_5").alias("stage"),
+            col("mean"),
+            col("95%"),
+            col("99%"),
+        ])
+        .sort("stage", Default::default())
+        .collect()?;
+
+    // Save data as a CSV file
+    let mut file = std::fs::File::create(target_dir.join("pgmtr-stats-stages.csv"))?;
+    CsvWriter::new(&mut file).finish(&mut stages_df)?;
+
+    Ok(())
+}
+
+// Adds the seconds without commits to a time series, an outage column set to 1 during the
+// outages, and a fault column with the names of the faults injected during each second. time_s 0
+// is the first second of the transaction log rows log_df. Missing values are replaced by fill,
+// when given. The time series is left unchanged when there is no event.
+fn mark_events(ts_df: DataFrame, log_df: LazyFrame, events: &RunEvents, fill: Option<i64>) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    if events.outages.is_empty() && events.faults.is_empty() {
+        return Ok(ts_df);
+    }
+    let origin = match log_df
+        .select([col("column_1").min()])
+        .collect()?
+        .column("column_1")?
+        .get(0)?
+        .try_extract::<i64>() {
+        Ok(origin) => origin,
+        // Nothing has been committed
+        Err(_) => return Ok(ts_df),
+    };
+
+    // The series covers the events happening after the last commit
+    let last_event_s = events.outages
+        .iter()
+        .map(|(_, end)| *end)
+        .chain(events.faults.iter().map(|(time, _)| *time))
+        .max()
+        .unwrap_or(origin) - origin;
+    let end_s = ts_df.column("time_s")?.max::<i64>().unwrap_or(0).max(last_event_s);
+    let times: Vec<i64> = (0..=end_s).collect();
+
+    let mut columns: Vec<Expr> = Vec::new();
+    for name in ts_df.get_column_names() {
+        match fill {
+            Some(value) if name != "time_s" => columns.push(col(name).fill_null(lit(value))),
+            _ => columns.push(col(name)),
+        }
+    }
+    let mut events_df = df!("time_s" => &times)?;
+    if !events.outages.is_empty() {
+        let flags: Vec<i64> = times
+            .iter()
+            .map(|t| events.outages.iter().any(|(start, end)| *start <= t + origin && t + origin <= *end) as i64)
+            .collect();
+        events_df.with_column(Series::new("outage", flags))?;
+        columns.push(col("outage"));
+    }
+    if !events.faults.is_empty() {
+        // Faults injected during the same second are joined by +
+        let names: Vec<Option<String>> = times
+            .iter()
+            .map(|t| {
+                let names: Vec<&str> = events.faults
+                    .iter()
+                    .filter(|(time, _)| *time == t + origin)
+                    .map(|(_, name)| name.as_str())
+                    .collect();
+                match names.is_empty() {
+                    true => None,
+                    false => Some(names.join("+")),
+                }
+            })
+            .collect();
+        events_df.with_column(Series::new("fault", names))?;
+        columns.push(col("fault"));
+    }
+
+    Ok(
+        events_df
+            .lazy()
+            .left_join(ts_df.lazy(), col("time_s"), col("time_s"))
+            .select(columns)
+            .collect()?
+    )
+}
+
+// Response times statistics of each statement, with --statement-timing
+pub const STATEMENT_STATS_FILE: &str = "pgmtr-stats-statements.csv";
+
+// Aggregates the statement durations by transaction and statement, and saves the statistics as a
+// CSV file.
+pub fn aggregate_statement_data(log_file: &str, target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // Statement log file parsing: timestamp, transaction id, statement label and duration
+    let mut stats_df = LazyCsvReader::new(target_dir.join(log_file))
+        .with_delimiter(b' ')
+        .has_header(false)
+        .finish()?
+        .groupby([col("column_2"), col("column_3")])
+        .agg([
+            col("column_4").count().alias("count"),
+            col("column_4").mean().alias("mean"),
+            col("column_4").min().alias("min"),
+            col("column_4").max().alias("max"),
+            col("column_4").quantile(lit(0.95), QuantileInterpolOptions::Linear).alias("95%"),
+            col("column_4").quantile(lit(0.99), QuantileInterpolOptions::Linear).alias("99%"),
+        ])
+        .select([
+            col("column_2").alias("tx_id"),
+            col("column_3").alias("statement"),
+            col("count"),
+            col("mean"),
+            col("min"),
+            col("max"),
+            col("95%"),
+            col("99%"),
+        ])
+        .sort_by_exprs([col("tx_id"), col("statement")], [false, false], false)
+        .collect()?;
+
+    // Save data as a CSV file
+    let mut file = std::fs::File::create(target_dir.join(STATEMENT_STATS_FILE))?;
+    CsvWriter::new(&mut file).finish(&mut stats_df)?;
+
+    Ok(())
+}
+
+// Connection times statistics, with --connect-per-transaction or --reconnect-every
+const CONNECTION_STATS_FILE: &str = "pgmtr-stats-connections.csv";
+
+// Computes the connection times statistics and saves them as a CSV file
+pub fn aggregate_connection_data(log_file: &str, target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // Connection log file parsing: timestamp, client id and connection time
+    let mut stats = LazyCsvReader::new(target_dir.join(log_file))
+        .with_delimiter(b' ')
+        .has_header(false)
+        .finish()?
+        .select([
+            col("column_3").alias("connection_time_ms"),
+        ])
+        .collect()?
+        .describe(Some(&[0.95, 0.99]))
+        .select(["describe", "connection_time_ms"])?;
+
+    // Save statistics
+    let mut file = std::fs::File::create(target_dir.join(CONNECTION_STATS_FILE))?;
+    CsvWriter::new(&mut file).finish(&mut stats)?;
+
+    Ok(())
+}
+
+// Reads the CSV file containing the connection times statistics
+pub fn get_connection_stats(target_dir: &Path) -> Result<ConnectionStatistics, Box<dyn std::error::Error>> {
+    let df = LazyCsvReader::new(target_dir.join(CONNECTION_STATS_FILE))
+        .with_delimiter(b',')
+        .has_header(true)
+        .finish()?
+        .collect()?;
+
+    let mut stats = ConnectionStatistics {
+        count: 0,
+        mean: 0.0,
+        min: 0.0,
+        max: 0.0,
+        std: 0.0,
+        percentile_95: 0.0,
+        percentile_99: 0.0,
+    };
+    let labels = df.column("describe")?.utf8()?;
+    let values = df.column("connection_time_ms")?;
+    for i in 0..df.height() {
+        let value: f64 = values.get(i)?.try_extract::<f64>()?;
+        match labels.get(i).unwrap_or_default() {
+            "count" => stats.count = value as u64,
+            "mean" => stats.mean = value,
+            "min" => stats.min = value,
+            "max" => stats.max = value,
+            "std" => stats.std = value,
+            "95%" => stats.percentile_95 = value,
+            "99%" => stats.percentile_99 = value,
+            _ => (),
+        }
+    }
+
+    Ok(stats)
+}
+
+// Response times statistics of each host, with --read-routing
+const HOST_STATS_FILE: &str = "pgmtr-stats-hosts.csv";
+
+// Computes the response times statistics of each host, from all transaction types, and saves them
+// as a CSV file
+pub fn aggregate_host_data(log_file: &str, target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut hosts_df = LazyCsvReader::new(target_dir.join(log_file))
+        .with_delimiter(b' ')
+        .has_header(false)
+        .finish()?
+        .groupby([col("column_7")])
+        .agg([
+            col("column_4").mean().alias("mean"),
+            col("column_4").quantile(lit(0.95), QuantileInterpolOptions::Linear).alias("95%"),
+            col("column_4").quantile(lit(0.99), QuantileInterpolOptions::Linear).alias("99%"),
+        ])
+        .select([
+            col("column_7").alias("host"),
+            col("mean"),
+            col("95%"),
+            col("99%"),
+        ])
+        .sort("host", Default::default())
+        .collect()?;
+
+    // Save data as a CSV file
+    let mut file = std::fs::File::create(target_dir.join(HOST_STATS_FILE))?;
+    CsvWriter::new(&mut file).finish(&mut hosts_df)?;
+
+    Ok(())
+}
+
+// Response times of one host: mean, 95% and 99%
+pub type HostResponseTimes = (f64, f64, f64);
+
+// Reads the CSV file containing the response times statistics of each host and returns them as
+// the following structure: HashMap<host index: u16, HostResponseTimes>
+pub fn get_host_stats(target_dir: &Path) -> Result<HashMap<u16, HostResponseTimes>, Box<dyn std::error::Error>> {
+    let df = LazyCsvReader::new(target_dir.join(HOST_STATS_FILE))
+        .with_delimiter(b',')
+        .has_header(true)
+        .finish()?
+        .collect()?;
+
+    let mut stats_map: HashMap<u16, HostResponseTimes> = HashMap::new();
+    for i in 0..df.height() {
+        let host = df.column("host")?.get(i)?.try_extract::<u16>()?;
+        let mean = df.column("mean")?.get(i)?.try_extract::<f64>()?;
+        let percentile_95 = df.column("95%")?.get(i)?.try_extract::<f64>()?;
+        let percentile_99 = df.column("99%")?.get(i)?.try_extract::<f64>()?;
+        stats_map.insert(host, (mean, percentile_95, percentile_99));
+    }
+
+    Ok(stats_map)
+}
+
+// Pool wait times statistics, with --pool-size
+const POOL_STATS_FILE: &str = "pgmtr-stats-pool.csv";
+
+// Computes the pool wait times statistics and the pool saturation, and saves them as a CSV file
+pub fn aggregate_pool_data(log_file: &str, target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // Pool log file parsing: timestamp, client id, wait time and saturation flag
+    let mut stats_df = LazyCsvReader::new(target_dir.join(log_file))
+        .with_delimiter(b' ')
+        .has_header(false)
+        .finish()?
+        .select([
+            col("column_3").count().alias("count"),
+            (col("column_4").cast(DataType::Float64).mean() * lit(100)).alias("saturation"),
+            col("column_3").mean().alias("mean"),
+            col("column_3").max().alias("max"),
+            col("column_3").quantile(lit(0.95), QuantileInterpolOptions::Linear).alias("95%"),
+            col("column_3").quantile(lit(0.99), QuantileInterpolOptions::Linear).alias("99%"),
+        ])
+        .collect()?;
+
+    // Save data as a CSV file
+    let mut file = std::fs::File::create(target_dir.join(POOL_STATS_FILE))?;
+    CsvWriter::new(&mut file).finish(&mut stats_df)?;
+
+    Ok(())
+}
+
+// Reads the CSV file containing the pool statistics
+pub fn get_pool_stats(target_dir: &Path, size: u32) -> Result<PoolStatistics, Box<dyn std::error::Error>> {
+    let df = LazyCsvReader::new(target_dir.join(POOL_STATS_FILE))
+        .with_delimiter(b',')
+        .has_header(true)
+        .finish()?
+        .collect()?;
+
+    Ok(PoolStatistics {
+        size,
+        count: df.column("count")?.get(0)?.try_extract::<u64>()?,
+        saturation: df.column("saturation")?.get(0)?.try_extract::<f64>()?,
+        mean: df.column("mean")?.get(0)?.try_extract::<f64>()?,
+        max: df.column("max")?.get(0)?.try_extract::<f64>()?,
+        percentile_95: df.column("95%")?.get(0)?.try_extract::<f64>()?,
+        percentile_99: df.column("99%")?.get(0)?.try_extract::<f64>()?,
+    })
+}
+
+// Client outages, with --failover
+const OUTAGES_FILE: &str = "pgmtr-outages.csv";
+
+// Aggregates the outage log: the outages of the clients are sorted by start time and saved as a
+// CSV file.
+pub fn aggregate_outage_data(log_file: &str, target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // Outage log file parsing: start timestamp (ms), client id, duration and lost transactions
+    let mut outages_df = LazyCsvReader::new(target_dir.join(log_file))
+        .with_delimiter(b' ')
+        .has_header(false)
+        .finish()?
+        .select([
+            col("column_1").alias("start_ms"),
+            (col("column_1") + col("column_3").cast(DataType::Int64)).alias("end_ms"),
+            col("column_2").alias("client"),
+            col("column_3").alias("duration_ms"),
+            col("column_4").alias("lost"),
+        ])
+        .sort("start_ms", Default::default())
+        .collect()?;
+
+    // Save data as a CSV file
+    let mut file = std::fs::File::create(target_dir.join(OUTAGES_FILE))?;
+    CsvWriter::new(&mut file).finish(&mut outages_df)?;
+
+    Ok(())
+}
+
+// Outage of a client: start and end timestamps in milliseconds, client id and lost transactions
+pub type ClientOutage = (i64, i64, u32, u32);
+
+// Reads the CSV file containing the outages of the clients, sorted by start time
+pub fn get_outages(target_dir: &Path) -> Result<Vec<ClientOutage>, Box<dyn std::error::Error>> {
+    let df = LazyCsvReader::new(target_dir.join(OUTAGES_FILE))
+        .with_delimiter(b',')
+        .has_header(true)
+        .finish()?
+        .collect()?;
+
+    let mut outages = Vec::new();
+    for i in 0..df.height() {
+        outages.push((
+            df.column("start_ms")?.get(i)?.try_extract::<i64>()?,
+            df.column("end_ms")?.get(i)?.try_extract::<i64>()?,
+            df.column("client")?.get(i)?.try_extract::<u32>()?,
+            df.column("lost")?.get(i)?.try_extract::<u32>()?,
+        ));
+    }
+
+    Ok(outages)
+}
+
+// Faults injected during the run, with --fault
+const FAULTS_FILE: &str = "pgmtr-faults.csv";
+
+// Aggregates the fault log: the injected faults are sorted by time and saved as a CSV file.
+pub fn aggregate_fault_data(log_file: &str, target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // Fault log file parsing: timestamp (ms), fault name, signaled sessions and duration
+    let mut faults_df = LazyCsvReader::new(target_dir.join(log_file))
+        .with_delimiter(b' ')
+        .has_header(false)
+        .finish()?
+        .select([
+            col("column_1").alias("time_ms"),
+            col("column_2").alias("fault"),
+            col("column_3").alias("sessions"),
+            col("column_4").alias("duration_ms"),
+        ])
+        .sort("time_ms", Default::default())
+        .collect()?;
+
+    // Save data as a CSV file
+    let mut file = std::fs::File::create(target_dir.join(FAULTS_FILE))?;
+    CsvWriter::new(&mut file).finish(&mut faults_df)?;
+
     Ok(())
 }
 
-// Reads the CSV file containing statistics and returns them as the following structure: HashMap<transaction_id: u16, ResponseTimeStatistices>
+// Injected fault: timestamp in milliseconds, name, signaled sessions and duration in milliseconds
+pub type InjectedFault = (i64, String, u32, f64);
+
+// Reads the CSV file containing the injected faults, sorted by time
+pub fn get_faults(target_dir: &Path) -> Result<Vec<InjectedFault>, Box<dyn std::error::Error>> {
+    let df = LazyCsvReader::new(target_dir.join(FAULTS_FILE))
+        .with_delimiter(b',')
+        .has_header(true)
+        .finish()?
+        .collect()?;
+
+    let mut faults = Vec::new();
+    for i in 0..df.height() {
+        faults.push((
+            df.column("time_ms")?.get(i)?.try_extract::<i64>()?,
+            df.column("fault")?.utf8()?.get(i).unwrap_or_default().to_string(),
+            df.column("sessions")?.get(i)?.try_extract::<u32>()?,
+            df.column("duration_ms")?.get(i)?.try_extract::<f64>()?,
+        ));
+    }
+
+    Ok(faults)
+}
+
+// Reads the CSV file containing the statement statistics, named after their transaction
+pub fn get_statement_stats(target_dir: &Path, transactions_names: &HashMap<u16, String>) -> Result<Vec<StatementStatistics>, Box<dyn std::error::Error>> {
+    let df = LazyCsvReader::new(target_dir.join(STATEMENT_STATS_FILE))
+        .with_delimiter(b',')
+        .has_header(true)
+        .finish()?
+        .collect()?;
+
+    let mut stats = Vec::new();
+    for i in 0..df.height() {
+        let tx_id = df.column("tx_id")?.get(i)?.try_extract::<u16>()?;
+        stats.push(StatementStatistics {
+            name: transactions_names.get(&tx_id).cloned().unwrap_or_else(|| tx_id.to_string()),
+            statement: df.column("statement")?.utf8()?.get(i).unwrap_or_default().to_string(),
+            count: df.column("count")?.get(i)?.try_extract::<u64>()?,
+            mean: df.column("mean")?.get(i)?.try_extract::<f64>()?,
+            min: df.column("min")?.get(i)?.try_extract::<f64>()?,
+            max: df.column("max")?.get(i)?.try_extract::<f64>()?,
+            percentile_95: df.column("95%")?.get(i)?.try_extract::<f64>()?,
+            percentile_99: df.column("99%")?.get(i)?.try_extract::<f64>()?,
+        });
+    }
+
+    Ok(stats)
+}
+
+// Reads the CSV file containing statistics and returns them as the following structure: HashMap<transaction_id: u16, ResponseTimeStatistices>.
+// Transactions that have never committed are left out.
 pub fn get_stats(target_dir: &PathBuf, transactions: &Vec<BenchmarkTransaction>) -> Result<HashMap<u16, ResponseTimeStatistics>, Box<dyn std::error::Error>> {
+    read_stats(target_dir, transactions, "pgmtr-stats", "response_time_ms")
+}
+
+// Same as get_stats(), for the commit times
+pub fn get_commit_stats(target_dir: &Path, transactions: &Vec<BenchmarkTransaction>) -> Result<HashMap<u16, ResponseTimeStatistics>, Box<dyn std::error::Error>> {
+    read_stats(target_dir, transactions, "pgmtr-commit-stats", "commit_time_ms")
+}
+
+// Reads the statistics of the given column from the <file_prefix>-<transaction name>.csv files
+fn read_stats(target_dir: &Path, transactions: &Vec<BenchmarkTransaction>, file_prefix: &str, column: &str) -> Result<HashMap<u16, ResponseTimeStatistics>, Box<dyn std::error::Error>> {
     let labels = vec!["mean", "std", "min", "95%", "99%", "max"];
 
     let mut stats_map: HashMap<u16, ResponseTimeStatistics> = HashMap::new();
@@ -116,20 +588,31 @@ pub fn get_stats(target_dir: &PathBuf, transactions: &Vec<BenchmarkTransaction>)
             percentile_99: 0.0,
         };
         
-        let df = LazyCsvReader::new(target_dir.join(format!("pgmtr-stats-{}.csv", transaction.name)))
+        let df = LazyCsvReader::new(target_dir.join(format!("{}-{}.csv", file_prefix, transaction.name)))
             .with_delimiter(b',')
             .has_header(true)
             .finish()?;
 
+        // Transactions that have never committed have no statistics
+        let count = df
+            .clone()
+            .filter(col("describe").eq(lit("count")))
+            .first()
+            .select([col(column)])
+            .collect()?;
+        if count.column(column)?.get(0)?.try_extract::<f64>()? == 0.0 {
+            continue;
+        }
+
         for label in labels.iter() {
 
             let row = df
                 .clone()
                 .filter(col("describe").eq(lit(*label)))
                 .first()
-                .select([col("response_time_ms")])
+                .select([col(column)])
                 .collect()?;
-            let value: f64 = row.column("response_time_ms")?.get(0)?.try_extract::<f64>()?;
+            let value: f64 = row.column(column)?.get(0)?.try_extract::<f64>()?;
 
             match *label {
                 "mean" => {
@@ -159,6 +642,30 @@ pub fn get_stats(target_dir: &PathBuf, transactions: &Vec<BenchmarkTransaction>)
     Ok(stats_map)
 }
 
+// Response times of one stage: mean, 95% and 99%
+pub type StageResponseTimes = (f64, f64, f64);
+
+// Reads the CSV file containing the response times statistics of each stage and returns them as
+// the following structure: HashMap<stage: u16, StageResponseTimes>
+pub fn get_stage_stats(target_dir: &Path) -> Result<HashMap<u16, StageResponseTimes>, Box<dyn std::error::Error>> {
+    let df = LazyCsvReader::new(target_dir.join("pgmtr-stats-stages.csv"))
+        .with_delimiter(b',')
+        .has_header(true)
+        .finish()?
+        .collect()?;
+
+    let mut stats_map: HashMap<u16, StageResponseTimes> = HashMap::new();
+    for i in 0..df.height() {
+        let stage = df.column("stage")?.get(i)?.try_extract::<u16>()?;
+        let mean = df.column("mean")?.get(i)?.try_extract::<f64>()?;
+        let percentile_95 = df.column("95%")?.get(i)?.try_extract::<f64>()?;
+        let percentile_99 = df.column("99%")?.get(i)?.try_extract::<f64>()?;
+        stats_map.insert(stage, (mean, percentile_95, percentile_99));
+    }
+
+    Ok(stats_map)
+}
+
 pub fn print_transactions_summary(data: &Vec<TransactionSummary>) {
     let mut table = Table::from_iter(data);
     let style = Style::rounded();
@@ -174,6 +681,7 @@ pub fn print_transactions_summary(data: &Vec<TransactionSummary>) {
             Columns::single(1)
                 .not(Rows::first())
                 .modify()
+                .with(|s: &str| format!("{val:.*}", 1, val=s.parse::<f64>().unwrap()))
                 .with(Alignment::right())
         )
         .with(
@@ -186,18 +694,30 @@ pub fn print_transactions_summary(data: &Vec<TransactionSummary>) {
             Columns::single(3)
                 .not(Rows::first())
                 .modify()
-                .with(|s: &str| format!("{val:.*}", 3,  val=s.parse::<f64>().unwrap()))
                 .with(Alignment::right())
         )
         .with(
             Columns::single(4)
                 .not(Rows::first())
                 .modify()
-                .with(|s: &str| format!("{}", s.parse::<u32>().unwrap()))
                 .with(Alignment::right())
         )
         .with(
             Columns::single(5)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 3,  val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::single(6)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{}", s.parse::<u32>().unwrap()))
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::single(7)
                 .not(Rows::first())
                 .modify()
                 .with(|s: &str| format!("{}", s.parse::<u32>().unwrap()))
@@ -263,3 +783,296 @@ pub fn print_transactions_stats(data: &Vec<ResponseTimeStatistics>) {
 
         println!("{}", table);
 }
+
+pub fn print_statements_stats(data: &Vec<StatementStatistics>) {
+    let mut table = Table::from_iter(data);
+    let style = Style::rounded();
+
+    table
+        .with(style)
+        .with(
+            Rows::first()
+                .modify()
+                .with(Alignment::center())
+        )
+        .with(
+            Columns::single(2)
+                .not(Rows::first())
+                .modify()
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::new(3..=7)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 3, val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        );
+
+        println!("{}", table);
+}
+
+pub fn print_connections_stats(data: &Vec<ConnectionStatistics>) {
+    let mut table = Table::from_iter(data);
+    let style = Style::rounded();
+
+    table
+        .with(style)
+        .with(
+            Rows::first()
+                .modify()
+                .with(Alignment::center())
+        )
+        .with(
+            Columns::single(0)
+                .not(Rows::first())
+                .modify()
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::new(1..=6)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 3, val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        );
+
+        println!("{}", table);
+}
+
+pub fn print_hosts_summary(data: &Vec<HostSummary>) {
+    let mut table = Table::from_iter(data);
+    let style = Style::rounded();
+
+    table
+        .with(style)
+        .with(
+            Rows::first()
+                .modify()
+                .with(Alignment::center())
+        )
+        .with(
+            Columns::new(2..=4)
+                .not(Rows::first())
+                .modify()
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::new(5..=7)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 3, val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        );
+
+        println!("{}", table);
+}
+
+pub fn print_pool_stats(data: &Vec<PoolStatistics>) {
+    let mut table = Table::from_iter(data);
+    let style = Style::rounded();
+
+    table
+        .with(style)
+        .with(
+            Rows::first()
+                .modify()
+                .with(Alignment::center())
+        )
+        .with(
+            Columns::new(0..=1)
+                .not(Rows::first())
+                .modify()
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::single(2)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 1, val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::new(3..=6)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 3, val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        );
+
+        println!("{}", table);
+}
+
+pub fn print_outage_summary(data: &Vec<OutageSummary>) {
+    let mut table = Table::from_iter(data);
+    let style = Style::rounded();
+
+    table
+        .with(style)
+        .with(
+            Rows::first()
+                .modify()
+                .with(Alignment::center())
+        )
+        .with(
+            Columns::new(0..=1)
+                .not(Rows::first())
+                .modify()
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::new(2..=3)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 3, val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::single(4)
+                .not(Rows::first())
+                .modify()
+                .with(Alignment::right())
+        );
+
+        println!("{}", table);
+}
+
+pub fn print_faults_summary(data: &Vec<FaultSummary>) {
+    let mut table = Table::from_iter(data);
+    let style = Style::rounded();
+
+    table
+        .with(style)
+        .with(
+            Rows::first()
+                .modify()
+                .with(Alignment::center())
+        )
+        .with(
+            Columns::single(2)
+                .not(Rows::first())
+                .modify()
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::single(3)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 3, val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        );
+
+        println!("{}", table);
+}
+
+pub fn print_load_summary(data: &Vec<LoadSummary>) {
+    let mut table = Table::from_iter(data);
+    let style = Style::rounded();
+
+    table
+        .with(style)
+        .with(
+            Rows::first()
+                .modify()
+                .with(Alignment::center())
+        )
+        .with(
+            Columns::single(1)
+                .not(Rows::first())
+                .modify()
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::new(2..=5)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 3, val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        );
+
+        println!("{}", table);
+}
+
+pub fn print_size_summary(data: &Vec<SizeSummary>) {
+    let mut table = Table::from_iter(data);
+    let style = Style::rounded();
+
+    table
+        .with(style)
+        .with(
+            Rows::first()
+                .modify()
+                .with(Alignment::center())
+        )
+        .with(
+            Columns::single(1)
+                .not(Rows::first())
+                .modify()
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::new(2..=5)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 3, val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        );
+
+        println!("{}", table);
+}
+
+pub fn print_stages_summary(data: &Vec<StageSummary>) {
+    let mut table = Table::from_iter(data);
+    let style = Style::rounded();
+
+    table
+        .with(style)
+        .with(
+            Rows::first()
+                .modify()
+                .with(Alignment::center())
+        )
+        .with(
+            Columns::new(0..=5)
+                .not(Rows::first())
+                .modify()
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::new(6..=8)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 3, val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        );
+
+        println!("{}", table);
+}
+
+pub fn print_find_max_summary(data: &Vec<FindMaxSummary>) {
+    let mut table = Table::from_iter(data);
+    let style = Style::rounded();
+
+    table
+        .with(style)
+        .with(
+            Rows::first()
+                .modify()
+                .with(Alignment::center())
+        )
+        .with(
+            Columns::new(0..=1)
+                .not(Rows::first())
+                .modify()
+                .with(Alignment::right())
+        )
+        .with(
+            Columns::new(2..=3)
+                .not(Rows::first())
+                .modify()
+                .with(|s: &str| format!("{val:.*}", 3, val=s.parse::<f64>().unwrap()))
+                .with(Alignment::right())
+        );
+
+        println!("{}", table);
+}
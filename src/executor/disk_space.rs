@@ -0,0 +1,23 @@
+use std::ffi::CString;
+use std::io;
+use std::mem::MaybeUninit;
+
+// Space available to unprivileged users on the file system holding the given local path, in bytes,
+// along with the file system id: tablespaces sharing a file system share its free space.
+// statvfs fields types depend on the platform
+#[allow(clippy::unnecessary_cast)]
+pub fn free_space(path: &str) -> Result<(u64, u64), String> {
+    let c_path = match CString::new(path) {
+        Ok(c_path) => c_path,
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+
+    // statvfs() fills stat when it succeeds
+    if unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return Err(format!("could not stat {}: {}", path, io::Error::last_os_error()));
+    }
+    let stat = unsafe { stat.assume_init() };
+
+    Ok((stat.f_fsid as u64, stat.f_bavail as u64 * stat.f_frsize as u64))
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use super::benchmark::{BenchmarkTransaction, FindMaxSummary};
+use super::super::args::Slo;
+
+// Commit response times of one measurement window, in ms, by transaction id
+pub type WindowSamples = HashMap<u16, Vec<f64>>;
+
+// Measured load level
+pub struct SearchPoint {
+    pub clients: u16,
+    pub tps: f64,
+    // Measured percentile of each SLO, None when the transaction has not committed
+    pub values: Vec<Option<f64>>,
+    pub passed: bool,
+}
+
+// Search for the highest number of clients meeting the SLOs. The number of clients is doubled
+// until the SLOs are missed or max_clients is reached, and then bisected between the last level
+// meeting the SLOs and the first one missing them. Response times are expected to grow with the
+// number of clients.
+pub struct SaturationSearch {
+    slos: Vec<Slo>,
+    // Transaction id of each SLO
+    slo_tx_ids: Vec<u16>,
+    max_clients: u16,
+    // Highest level meeting the SLOs, 0 when none
+    low: u16,
+    // Lowest level missing the SLOs, None while growing
+    high: Option<u16>,
+    points: Vec<SearchPoint>,
+}
+
+impl SaturationSearch {
+    pub fn new(slos: &[Slo], transactions: &[BenchmarkTransaction], max_clients: u16) -> Result<SaturationSearch, String> {
+        let mut slo_tx_ids = Vec::new();
+        for slo in slos.iter() {
+            match transactions.iter().find(|t| t.name.eq_ignore_ascii_case(&slo.transaction)) {
+                Some(transaction) => slo_tx_ids.push(transaction.id),
+                None => return Err(format!(
+                    "unknown transaction in SLO: {}. Transactions: {}",
+                    slo.transaction,
+                    transactions.iter().map(|t| t.name.as_str()).collect::<Vec<&str>>().join(", "),
+                )),
+            }
+        }
+
+        Ok(SaturationSearch {
+            slos: slos.to_vec(),
+            slo_tx_ids,
+            max_clients: max_clients.max(1),
+            low: 0,
+            high: None,
+            points: Vec::new(),
+        })
+    }
+
+    // Number of clients of the next measurement window, None once the search is over
+    pub fn next_clients(&self) -> Option<u16> {
+        match self.high {
+            None => {
+                if self.low >= self.max_clients {
+                    None
+                }
+                else if self.low == 0 {
+                    Some(1)
+                }
+                else {
+                    Some(self.low.saturating_mul(2).min(self.max_clients))
+                }
+            },
+            Some(high) => {
+                if high - self.low <= 1 {
+                    return None;
+                }
+                Some(self.low + (high - self.low) / 2)
+            },
+        }
+    }
+
+    // Record the measurement window of the given number of clients
+    pub fn record(&mut self, clients: u16, samples: &mut WindowSamples, window_s: f64) {
+        let n_commits: usize = samples.values().map(|durations| durations.len()).sum();
+        let mut values = Vec::new();
+        let mut passed = true;
+        for (slo, tx_id) in self.slos.iter().zip(self.slo_tx_ids.iter()) {
+            let value = samples.get_mut(tx_id).and_then(|durations| percentile(durations, slo.percentile));
+            match value {
+                Some(v) if v < slo.max_ms => (),
+                _ => passed = false,
+            }
+            values.push(value);
+        }
+
+        if passed {
+            self.low = clients;
+        }
+        else {
+            self.high = Some(clients);
+        }
+        self.points.push(SearchPoint {clients, tps: n_commits as f64 / window_s, values, passed});
+    }
+
+    // Highest level meeting the SLOs
+    pub fn best(&self) -> Option<&SearchPoint> {
+        self.points.iter().filter(|p| p.passed).max_by_key(|p| p.clients)
+    }
+
+    // One row per measured level, sorted by number of clients
+    pub fn summary(&self) -> Vec<FindMaxSummary> {
+        let mut summary: Vec<FindMaxSummary> = self.points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| FindMaxSummary {
+                window: i as u16 + 1,
+                clients: point.clients,
+                tps: point.tps,
+                tpm: point.tps * 60.0,
+                slo: self.format_values(point),
+                passed: if point.passed { "yes".to_string() } else { "no".to_string() },
+            })
+            .collect();
+        summary.sort_by_key(|row| row.clients);
+
+        summary
+    }
+
+    // Measured value of each SLO, like "New-Order p90 = 42.125 ms"
+    pub fn format_values(&self, point: &SearchPoint) -> String {
+        self.slos
+            .iter()
+            .zip(point.values.iter())
+            .map(|(slo, value)| match value {
+                Some(v) => format!("{} p{} = {:.3} ms", slo.transaction, slo.percentile, v),
+                None => format!("{} p{} = n/a", slo.transaction, slo.percentile),
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+// Percentile of the given values, using a linear interpolation between the closest ranks
+fn percentile(values: &mut [f64], percentile: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let rank = percentile / 100.0 * (values.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+
+    Some(values[lower] + (values[upper] - values[lower]) * (rank - lower as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Search where the response time is the number of clients, levels up to max_ok pass
+    fn run_search(max_ok: u16, max_clients: u16) -> (SaturationSearch, Vec<u16>) {
+        let slos = vec![Slo {transaction: "tx".to_string(), percentile: 90.0, max_ms: max_ok as f64 + 0.5}];
+        let transactions = vec![BenchmarkTransaction {
+            id: 1,
+            name: "tx".to_string(),
+            weight: 100,
+            description: "".to_string(),
+            read_only: false,
+        }];
+        let mut search = SaturationSearch::new(&slos, &transactions, max_clients).unwrap();
+        let mut levels = Vec::new();
+        while let Some(clients) = search.next_clients() {
+            assert!(levels.len() < 64, "the search does not end");
+            let mut samples = WindowSamples::from([(1, vec![clients as f64; 10])]);
+            search.record(clients, &mut samples, 1.0);
+            levels.push(clients);
+        }
+
+        (search, levels)
+    }
+
+    #[test]
+    fn first_level_fails() {
+        let (search, levels) = run_search(0, 64);
+        assert_eq!(levels, vec![1]);
+        assert!(search.best().is_none());
+    }
+
+    #[test]
+    fn every_level_passes() {
+        let (search, levels) = run_search(100, 20);
+        assert_eq!(levels, vec![1, 2, 4, 8, 16, 20]);
+        assert_eq!(search.best().map(|p| p.clients), Some(20));
+    }
+
+    #[test]
+    fn search_converges() {
+        let (search, levels) = run_search(11, 64);
+        assert_eq!(levels, vec![1, 2, 4, 8, 16, 12, 10, 11]);
+        assert_eq!(search.best().map(|p| p.clients), Some(11));
+        assert_eq!(search.high.map(|high| high - search.low), Some(1));
+    }
+
+    #[test]
+    fn percentile_empty() {
+        assert_eq!(percentile(&mut [], 90.0), None);
+    }
+
+    #[test]
+    fn percentile_single_value() {
+        assert_eq!(percentile(&mut [4.2], 0.0), Some(4.2));
+        assert_eq!(percentile(&mut [4.2], 99.0), Some(4.2));
+        assert_eq!(percentile(&mut [4.2], 100.0), Some(4.2));
+    }
+}
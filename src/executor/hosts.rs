@@ -0,0 +1,116 @@
+use postgres::{Client, NoTls};
+use sqlx::{Connection, PgConnection};
+
+use super::super::args::TargetSessionAttrs;
+
+// Role of the server and default_transaction_read_only
+const ROLE_QUERY: &str = "SELECT pg_is_in_recovery(), current_setting('default_transaction_read_only')::BOOLEAN";
+
+// One host of the --host list
+pub struct TargetHost {
+    // host:port
+    pub name: String,
+    pub dsn: String,
+    // Role of the server, None until probed or when it cannot be reached
+    pub role: Option<HostRole>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct HostRole {
+    // The server is a standby, pg_is_in_recovery()
+    pub standby: bool,
+    // Transactions are read-only by default, default_transaction_read_only
+    pub read_only: bool,
+}
+
+impl TargetHost {
+    pub fn new(name: String, dsn: String) -> TargetHost {
+        TargetHost {name, dsn, role: None}
+    }
+
+    // Connect to the host and read its role. Unreachable hosts are left without role.
+    pub fn probe(&mut self) {
+        self.role = match Client::connect(&self.dsn, NoTls) {
+            Ok(mut client) => match client.query_one(ROLE_QUERY, &[]) {
+                Ok(row) => Some(HostRole {standby: row.get(0), read_only: row.get(1)}),
+                Err(_) => None,
+            },
+            Err(_) => None,
+        };
+    }
+
+    pub fn role_name(&self) -> &'static str {
+        match self.role {
+            Some(role) if role.standby => "standby",
+            Some(_) => "primary",
+            None => "unknown",
+        }
+    }
+
+    // The host can be connected to with the given target_session_attrs. Hosts that have not been
+    // probed only match any.
+    fn matches(&self, attrs: TargetSessionAttrs) -> bool {
+        role_matches(self.role, attrs)
+    }
+}
+
+fn role_matches(role: Option<HostRole>, attrs: TargetSessionAttrs) -> bool {
+    match (attrs, role) {
+        (TargetSessionAttrs::Any, _) => true,
+        (_, None) => false,
+        (TargetSessionAttrs::ReadWrite, Some(role)) => !role.standby && !role.read_only,
+        (TargetSessionAttrs::ReadOnly, Some(role)) => role.standby || role.read_only,
+        (TargetSessionAttrs::Primary, Some(role)) => !role.standby,
+        (TargetSessionAttrs::Standby | TargetSessionAttrs::PreferStandby, Some(role)) => role.standby,
+    }
+}
+
+// Index of the first host matching target_session_attrs. Like libpq, prefer-standby falls back to
+// any reachable host.
+pub fn select_host(hosts: &[TargetHost], attrs: TargetSessionAttrs) -> Option<usize> {
+    match hosts.iter().position(|host| host.matches(attrs)) {
+        None if attrs == TargetSessionAttrs::PreferStandby => hosts.iter().position(|host| host.role.is_some()),
+        position => position,
+    }
+}
+
+// Indexes of the reachable standbys
+pub fn standby_hosts(hosts: &[TargetHost]) -> Vec<usize> {
+    hosts
+        .iter()
+        .enumerate()
+        .filter(|(_, host)| host.matches(TargetSessionAttrs::Standby))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+// Connect to the first host of the list matching target_session_attrs, reading the role of the
+// hosts again. Returns the index of the host with its connection, None when no host matches.
+pub async fn connect_first(dsns: &[String], attrs: TargetSessionAttrs) -> Option<(usize, PgConnection)> {
+    // Like libpq, prefer-standby falls back to any reachable host
+    let passes = match attrs {
+        TargetSessionAttrs::PreferStandby => vec![TargetSessionAttrs::PreferStandby, TargetSessionAttrs::Any],
+        attrs => vec![attrs],
+    };
+    for attrs in passes {
+        for (i, dsn) in dsns.iter().enumerate() {
+            let mut connection = match PgConnection::connect(dsn).await {
+                Ok(connection) => connection,
+                Err(_) => continue,
+            };
+            if attrs == TargetSessionAttrs::Any {
+                return Some((i, connection));
+            }
+            let role = match sqlx::query_as::<_, (bool, bool)>(ROLE_QUERY).fetch_one(&mut connection).await {
+                Ok((standby, read_only)) => Some(HostRole {standby, read_only}),
+                Err(_) => None,
+            };
+            if role_matches(role, attrs) {
+                return Some((i, connection));
+            }
+            let _ = connection.close().await;
+        }
+    }
+
+    None
+}
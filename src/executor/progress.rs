@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, Sender};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+
+// Number of generated rows between two progress messages
+const REPORT_ROWS: u64 = 1_000;
+
+// Messages sent by the loader jobs to the progress renderer
+pub enum LoadProgress {
+    // The job starts loading one table for one warehouse
+    Table { job: u32, warehouse_id: u32, table: String },
+    // Rows loaded into one table
+    Rows { table: String, rows: u64 },
+    // One more warehouse has been loaded
+    Warehouse,
+    // The job has finished its work
+    Done { job: u32 },
+}
+
+// Job side of the progress reporting. Rows are counted locally and sent by batches.
+#[derive(Clone)]
+pub struct ProgressReporter {
+    tx: Option<Sender<LoadProgress>>,
+    job: u32,
+    table: String,
+    pending_rows: u64,
+}
+
+impl ProgressReporter {
+    pub fn new(tx: Sender<LoadProgress>, job: u32) -> ProgressReporter {
+        ProgressReporter {
+            tx: Some(tx),
+            job,
+            table: String::new(),
+            pending_rows: 0,
+        }
+    }
+
+    // Reporter sending nothing, used when no progress is rendered
+    pub fn disabled() -> ProgressReporter {
+        ProgressReporter {
+            tx: None,
+            job: 0,
+            table: String::new(),
+            pending_rows: 0,
+        }
+    }
+
+    fn send(&self, message: LoadProgress) {
+        // Progress is informative only: a renderer that has gone is not an error
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(message);
+        }
+    }
+
+    pub fn start_table(&mut self, warehouse_id: u32, table: &str) {
+        self.flush();
+        self.table = table.to_string();
+        self.send(LoadProgress::Table { job: self.job, warehouse_id, table: table.to_string() });
+    }
+
+    pub fn add_rows(&mut self, rows: u64) {
+        self.pending_rows += rows;
+        if self.pending_rows >= REPORT_ROWS {
+            self.flush();
+        }
+    }
+
+    pub fn end_warehouse(&mut self) {
+        self.flush();
+        self.send(LoadProgress::Warehouse);
+    }
+
+    pub fn done(&mut self) {
+        self.flush();
+        self.send(LoadProgress::Done { job: self.job });
+    }
+
+    pub fn flush(&mut self) {
+        if self.pending_rows > 0 {
+            self.send(LoadProgress::Rows { table: self.table.clone(), rows: self.pending_rows });
+            self.pending_rows = 0;
+        }
+    }
+}
+
+// Start the thread rendering the loading progress: one bar for the loaded warehouses with an ETA,
+// one line per table with its loading rate and one line per job with its status. Bars are
+// cleared once every reporter has hung up.
+pub fn start_load_progress(rx: Receiver<LoadProgress>, n_jobs: u32, n_warehouses: u32) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let multi = MultiProgress::new();
+
+        let main_bar = multi.add(ProgressBar::new(n_warehouses as u64));
+        main_bar.set_style(
+            ProgressStyle::with_template("[{elapsed_precise}] {bar:40} {pos}/{len} warehouses, ETA {eta}")
+                .unwrap()
+                .progress_chars("=> ")
+        );
+        main_bar.enable_steady_tick(Duration::from_millis(200));
+
+        let table_style = ProgressStyle::with_template("{prefix:>12} {human_pos:>12} rows {msg:>14}").unwrap();
+        let job_style = ProgressStyle::with_template("{prefix:>12} {msg}").unwrap();
+
+        let mut job_bars = HashMap::new();
+        for job in 1..=n_jobs {
+            let bar = multi.add(ProgressBar::new_spinner());
+            bar.set_style(job_style.clone());
+            bar.set_prefix(format!("job {}", job));
+            bar.set_message("waiting");
+            job_bars.insert(job, bar);
+        }
+        // Table bars, with the time the table has been seen for the first time
+        let mut table_bars: HashMap<String, (ProgressBar, Instant)> = HashMap::new();
+
+        while let Ok(message) = rx.recv() {
+            match message {
+                LoadProgress::Table { job, warehouse_id, table } => {
+                    if let Some(bar) = job_bars.get(&job) {
+                        bar.set_message(format!("warehouse {}: {}", warehouse_id, table));
+                    }
+                    // Table lines are inserted above the job lines, by order of appearance
+                    if !table_bars.contains_key(&table) {
+                        let bar = multi.insert(table_bars.len() + 1, ProgressBar::new_spinner());
+                        bar.set_style(table_style.clone());
+                        bar.set_prefix(table.clone());
+                        table_bars.insert(table, (bar, Instant::now()));
+                    }
+                },
+                LoadProgress::Rows { table, rows } => {
+                    if let Some((bar, start)) = table_bars.get(&table) {
+                        bar.inc(rows);
+                        let rate = bar.position() as f64 / start.elapsed().as_secs_f64().max(f64::EPSILON);
+                        bar.set_message(format!("{:.0} rows/s", rate));
+                    }
+                },
+                LoadProgress::Warehouse => main_bar.inc(1),
+                LoadProgress::Done { job } => {
+                    if let Some(bar) = job_bars.get(&job) {
+                        bar.set_message("done");
+                    }
+                },
+            }
+        }
+
+        for bar in table_bars.values().map(|(bar, _)| bar).chain(job_bars.values()) {
+            bar.finish_and_clear();
+        }
+        main_bar.finish_and_clear();
+    })
+}
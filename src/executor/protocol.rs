@@ -0,0 +1,182 @@
+use std::time::Instant;
+
+use sqlx::{Executor, PgConnection, Postgres, Row};
+use sqlx::postgres::PgRow;
+
+use super::super::args::QueryProtocol;
+
+// Value of a statement parameter
+pub enum SqlParam {
+    Int(i32),
+    Real(f32),
+    Double(f64),
+    // NULL when None
+    Text(Option<String>),
+    IntArray(Vec<i32>),
+}
+
+impl SqlParam {
+    // SQL literal of the value, used by the protocols without parameters. Literals are typed
+    // like the bound values.
+    fn literal(&self) -> String {
+        match self {
+            SqlParam::Int(v) => v.to_string(),
+            SqlParam::Real(v) => format!("'{}'::REAL", v),
+            SqlParam::Double(v) => format!("'{}'::FLOAT8", v),
+            SqlParam::Text(Some(v)) => format!("'{}'::TEXT", v.replace('\'', "''")),
+            SqlParam::Text(None) => "NULL::TEXT".to_string(),
+            SqlParam::IntArray(v) => format!(
+                "'{{{}}}'::INTEGER[]",
+                v.iter().map(|i| i.to_string()).collect::<Vec<String>>().join(","),
+            ),
+        }
+    }
+}
+
+// Statement and the values of its $1, $2, .. parameters. The label names the statement in the
+// statement timings.
+pub struct SqlStmt {
+    pub label: &'static str,
+    pub sql: String,
+    pub params: Vec<SqlParam>,
+}
+
+impl SqlStmt {
+    pub fn new(label: &'static str, sql: &str, params: Vec<SqlParam>) -> SqlStmt {
+        SqlStmt {label, sql: sql.to_string(), params}
+    }
+
+    // Statement text where the parameters are replaced by their literal
+    fn inline(&self) -> String {
+        let mut sql = String::with_capacity(self.sql.len());
+        let mut chars = self.sql.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' || !chars.peek().is_some_and(|next| next.is_ascii_digit()) {
+                sql.push(c);
+                continue;
+            }
+            let mut number = String::new();
+            while let Some(digit) = chars.peek().filter(|next| next.is_ascii_digit()) {
+                number.push(*digit);
+                chars.next();
+            }
+            match number.parse::<usize>().ok().and_then(|n| self.params.get(n.wrapping_sub(1))) {
+                Some(param) => sql.push_str(&param.literal()),
+                None => {
+                    sql.push('$');
+                    sql.push_str(&number);
+                },
+            }
+        }
+
+        sql
+    }
+}
+
+// Durations in us of the statements executed by one transaction, by label. Nothing is measured
+// when the statement timing is disabled.
+pub struct StmtTimer {
+    enabled: bool,
+    pub timings: Vec<(&'static str, u128)>,
+}
+
+impl StmtTimer {
+    pub fn new(enabled: bool) -> StmtTimer {
+        StmtTimer {enabled, timings: Vec::new()}
+    }
+
+    fn start(&self) -> Option<Instant> {
+        match self.enabled {
+            true => Some(Instant::now()),
+            false => None,
+        }
+    }
+
+    // Record the time elapsed since start, then restart
+    fn record(&mut self, label: &'static str, start: &mut Option<Instant>) {
+        if let Some(instant) = start {
+            self.timings.push((label, instant.elapsed().as_micros()));
+            *start = Some(Instant::now());
+        }
+    }
+}
+
+// Execute one statement and return its rows:
+// - simple: one query message, parameters being inlined
+// - extended: unnamed statement, parsed for each execution
+// - prepared: named statement, prepared once per connection
+pub async fn execute_stmt(conn: &mut PgConnection, protocol: QueryProtocol, timer: &mut StmtTimer, stmt: SqlStmt) -> Result<Vec<PgRow>, sqlx::Error> {
+    let mut start = timer.start();
+    let rows = match protocol {
+        QueryProtocol::Simple => conn.fetch_all(stmt.inline().as_str()).await?,
+        QueryProtocol::Extended | QueryProtocol::Prepared => {
+            let mut query = sqlx::query(&stmt.sql).persistent(protocol == QueryProtocol::Prepared);
+            for param in stmt.params.into_iter() {
+                query = match param {
+                    SqlParam::Int(v) => query.bind(v),
+                    SqlParam::Real(v) => query.bind(v),
+                    SqlParam::Double(v) => query.bind(v),
+                    SqlParam::Text(v) => query.bind(v),
+                    SqlParam::IntArray(v) => query.bind(v),
+                };
+            }
+            query.fetch_all(&mut *conn).await?
+        },
+    };
+    timer.record(stmt.label, &mut start);
+
+    Ok(rows)
+}
+
+// First column of the first row, failing when there is no row like fetch_one()
+pub fn first_value<T>(rows: &[PgRow]) -> Result<T, sqlx::Error>
+where
+    T: for<'r> sqlx::Decode<'r, Postgres> + sqlx::Type<Postgres>,
+{
+    match rows.first() {
+        Some(row) => row.try_get(0),
+        None => Err(sqlx::Error::RowNotFound),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inline(sql: &str, params: Vec<SqlParam>) -> String {
+        SqlStmt::new("test", sql, params).inline()
+    }
+
+    #[test]
+    fn inline_multi_digit_parameters() {
+        let params = (1..=10).map(SqlParam::Int).collect();
+        assert_eq!(inline("SELECT $1, $10", params), "SELECT 1, 10");
+    }
+
+    #[test]
+    fn inline_keeps_dollar_without_digit() {
+        assert_eq!(inline("SELECT $$a$$, $ 1, $1", vec![SqlParam::Int(7)]), "SELECT $$a$$, $ 1, 7");
+    }
+
+    #[test]
+    fn inline_keeps_unknown_parameter() {
+        assert_eq!(inline("SELECT $2", vec![SqlParam::Int(7)]), "SELECT $2");
+        assert_eq!(inline("SELECT $0", vec![SqlParam::Int(7)]), "SELECT $0");
+    }
+
+    #[test]
+    fn literal_escapes_quotes() {
+        assert_eq!(SqlParam::Text(Some("it's 'x'".to_string())).literal(), "'it''s ''x'''::TEXT");
+    }
+
+    #[test]
+    fn literal_null() {
+        assert_eq!(SqlParam::Text(None).literal(), "NULL::TEXT");
+    }
+
+    #[test]
+    fn literal_int_array() {
+        assert_eq!(SqlParam::IntArray(vec![3, -1, 20]).literal(), "'{3,-1,20}'::INTEGER[]");
+        assert_eq!(SqlParam::IntArray(vec![]).literal(), "'{}'::INTEGER[]");
+    }
+}
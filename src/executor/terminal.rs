@@ -28,3 +28,12 @@ pub fn err_msg(error: &str) {
     io::stderr().write_all(error.as_bytes()).unwrap();
     io::stderr().flush().unwrap();
 }
+
+pub fn warn_msg(warning: &str) {
+    let m_warn = format!("{}\n", style("warning").yellow());
+    let warning = format!("{}\n", style(warning).yellow());
+    io::stdout().write_all(m_warn.as_bytes()).unwrap();
+    io::stdout().flush().unwrap();
+    io::stderr().write_all(warning.as_bytes()).unwrap();
+    io::stderr().flush().unwrap();
+}
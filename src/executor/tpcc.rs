@@ -1,21 +1,763 @@
 use std::error::Error;
 use std::fmt;
-use std::io::Write;
+use std::thread;
 use std::time::Instant;
 
 use async_trait::async_trait;
 use chrono::Utc;
+use crossbeam_channel::bounded;
 use postgres::Client;
+use postgres::types::ToSql;
 use sqlx::PgConnection;
-use sqlx::Connection;
-use rand::{distributions::Alphanumeric, Rng, seq::SliceRandom};
+use sqlx::{Connection, Row};
+use rand::{distributions::Alphanumeric, Rng, rngs::StdRng, seq::SliceRandom};
 
 use super::benchmark::{
     Benchmark,
+    BenchmarkIndex,
+    BenchmarkLoadStmt,
     BenchmarkStmt,
+    BenchmarkTable,
     BenchmarkTransaction,
+    LoadStats,
     ReadWrite,
+    SizeEstimate,
+    TransactionDurations,
+    stream_rng,
 };
+use super::progress::ProgressReporter;
+use super::copy_data::{
+    binary_copy_sql,
+    consume_binary_copy,
+    CopyRow,
+    CopySender,
+    CopyValue,
+    text_copy_sql,
+    TextCopyLoader,
+};
+use super::protocol::{execute_stmt, first_value, SqlParam::{Double, Int, IntArray, Real, Text}, SqlStmt, StmtTimer};
+use super::super::args::{CopyFormat, DataGeneration, InitArgs, QueryProtocol, RunArgs, StorageArgs, TransactionMode};
+
+// Tables populated for each warehouse, in loading order
+const WAREHOUSE_TABLES: [&str; 8] = ["warehouse", "district", "stock", "customer", "history", "orders", "new_order", "order_line"];
+// Tables populated by INSERT .. SELECT when data is generated server side. Other tables are
+// small and always loaded using COPY.
+const SERVER_GENERATED_TABLES: [&str; 5] = ["stock", "customer", "history", "orders", "order_line"];
+// Warehouse id column of each table partitioned by warehouse
+const WAREHOUSE_ID_COLUMNS: [(&str, &str); 8] = [
+    ("warehouse", "w_id"),
+    ("district", "d_w_id"),
+    ("stock", "s_w_id"),
+    ("customer", "c_w_id"),
+    ("history", "h_w_id"),
+    ("orders", "o_w_id"),
+    ("new_order", "no_w_id"),
+    ("order_line", "ol_w_id"),
+];
+// Tables not checked for each warehouse id before running the workload: history has no index,
+// new_order can legitimately have no row for a warehouse. The warehouse id of the other tables
+// leads their primary key.
+const UNCHECKED_WAREHOUSE_TABLES: [&str; 2] = ["history", "new_order"];
+// Tables loaded using COPY when data is generated server side
+const SERVER_COPIED_TABLES: [&str; 3] = ["warehouse", "district", "new_order"];
+// Number of rows sent by each text COPY command
+const TEXT_COPY_BATCH_SIZE: u32 = 500;
+// Number of row chunks waiting to be sent by the binary COPY consumer
+const COPY_CHANNEL_SIZE: usize = 16;
+// NURand C-load constants storage
+const NURAND_INSERT: &str = "INSERT INTO nurand VALUES ($1, $2, $3)";
+// Random stream used to pick the NURand C-load constants, out of the warehouse ids range
+const NURAND_RNG_STREAM: u64 = u32::MAX as u64 + 1;
+// Entry and delivery dates of the loaded data when a random seed is set, in seconds since the Unix
+// epoch (2024-01-01 00:00:00 UTC): seeded datasets must not depend on when they were loaded
+const SEEDED_LOAD_TIMESTAMP: i64 = 1_704_067_200;
+
+// Server side data generation setup: random strings are taken from a pool of random characters
+// built once per session, then temporary functions following the rules of random_alpha_string(),
+// random_state(), random_zip() and gen_last() are created.
+const SERVER_GENERATION_SETUP: [&str; 6] = [
+    r"
+    SELECT set_config(
+        'pgmtr.random_pool',
+        (
+            SELECT string_agg(substr('0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz', 1 + floor(random() * 62)::INTEGER, 1), '')
+            FROM generate_series(1, 4096)
+        ),
+        false
+    )
+    ",
+    r"
+    CREATE FUNCTION pg_temp.random_alpha_string(min_length INTEGER, max_length INTEGER) RETURNS TEXT AS $$
+        SELECT substr(
+            current_setting('pgmtr.random_pool'),
+            1 + floor(random() * (4096 - max_length))::INTEGER,
+            min_length + floor(random() * (max_length - min_length + 1))::INTEGER
+        )
+    $$ LANGUAGE SQL VOLATILE
+    ",
+    r"
+    CREATE FUNCTION pg_temp.random_data() RETURNS TEXT AS $$
+        SELECT
+            CASE WHEN random() < 0.1
+            THEN overlay(d PLACING 'ORIGINAL' FROM 2 + floor(random() * (length(d) - 9))::INTEGER FOR 9)
+            ELSE d
+            END
+        FROM pg_temp.random_alpha_string(26, 50) AS d
+    $$ LANGUAGE SQL VOLATILE
+    ",
+    r"
+    CREATE FUNCTION pg_temp.random_state() RETURNS TEXT AS $$
+        SELECT chr(65 + a) || chr(65 + (a + 1 + floor(random() * 25)::INTEGER) % 26)
+        FROM (SELECT floor(random() * 26)::INTEGER AS a) AS r
+    $$ LANGUAGE SQL VOLATILE
+    ",
+    r"
+    CREATE FUNCTION pg_temp.random_zip() RETURNS TEXT AS $$
+        SELECT lpad((1 + floor(random() * 9999))::INTEGER::TEXT, 4, '0') || '11111'
+    $$ LANGUAGE SQL VOLATILE
+    ",
+    r"
+    CREATE FUNCTION pg_temp.gen_last(n INTEGER) RETURNS TEXT AS $$
+        SELECT s[n / 100 + 1] || s[n / 10 % 10 + 1] || s[n % 10 + 1]
+        FROM (SELECT ARRAY['BAR', 'OUGHT', 'ABLE', 'PRIS', 'PRES', 'ESE', 'ANTI', 'CALLY', 'ATION', 'EING']) AS syllables(s)
+    $$ LANGUAGE SQL IMMUTABLE
+    ",
+];
+
+// Server side data generation queries. $1 is the warehouse id, $2 the entry/delivery date as a Unix
+// timestamp. For customers, $3 is the NURand C-load constant of last names and $4 is true when last
+// names are picked uniformly.
+const SERVER_GENERATION_STOCK: &str = r"
+    INSERT INTO stock
+    SELECT
+        s_i_id, $1::INTEGER, 10 + floor(random() * 91)::INTEGER,
+        pg_temp.random_alpha_string(24, 24), pg_temp.random_alpha_string(24, 24),
+        pg_temp.random_alpha_string(24, 24), pg_temp.random_alpha_string(24, 24),
+        pg_temp.random_alpha_string(24, 24), pg_temp.random_alpha_string(24, 24),
+        pg_temp.random_alpha_string(24, 24), pg_temp.random_alpha_string(24, 24),
+        pg_temp.random_alpha_string(24, 24), pg_temp.random_alpha_string(24, 24),
+        0, 0, 0, pg_temp.random_data()
+    FROM generate_series(1, 100000) AS s_i_id
+";
+
+const SERVER_GENERATION_CUSTOMER: &str = r"
+    INSERT INTO customer
+    SELECT
+        c_id, c_d_id, $1::INTEGER,
+        pg_temp.random_alpha_string(8, 16), 'OE',
+        pg_temp.gen_last(
+            CASE
+            WHEN c_id < 1000 THEN c_id
+            WHEN $4::BOOLEAN THEN floor(random() * 1000)::INTEGER
+            ELSE ((floor(random() * 256)::INTEGER | floor(random() * 1000)::INTEGER) + $3::INTEGER) % 1000
+            END
+        ),
+        pg_temp.random_alpha_string(10, 20), pg_temp.random_alpha_string(10, 20),
+        pg_temp.random_alpha_string(10, 20), pg_temp.random_state(), pg_temp.random_zip(),
+        (1000000000000000 + floor(random() * 9000000000000000)::BIGINT)::TEXT,
+        to_timestamp($2::FLOAT8) AT TIME ZONE 'UTC',
+        CASE WHEN random() < 0.1 THEN 'BC' ELSE 'GC' END,
+        50000, round((random() * 0.5)::NUMERIC, 2), -10, 10, 1, 0,
+        pg_temp.random_alpha_string(300, 500)
+    FROM generate_series(1, 10) AS c_d_id, generate_series(1, 3000) AS c_id
+";
+
+const SERVER_GENERATION_HISTORY: &str = r"
+    INSERT INTO history
+    SELECT
+        h_c_id, h_d_id, $1::INTEGER, h_d_id, $1::INTEGER,
+        to_timestamp($2::FLOAT8) AT TIME ZONE 'UTC', 10.0,
+        pg_temp.random_alpha_string(12, 24)
+    FROM generate_series(1, 10) AS h_d_id, generate_series(1, 3000) AS h_c_id
+";
+
+const SERVER_GENERATION_ORDERS: &str = r"
+    INSERT INTO orders
+    SELECT
+        o_id, o_d_id, $1::INTEGER, o_id,
+        to_timestamp($2::FLOAT8) AT TIME ZONE 'UTC',
+        CASE WHEN o_id < 2101 THEN 1 + floor(random() * 10)::INTEGER END,
+        (o_id::BIGINT * (o_id + o_d_id + $1::INTEGER)) % 11 + 5, 1
+    FROM generate_series(1, 10) AS o_d_id, generate_series(1, 3000) AS o_id
+";
+
+// The item ids of one order are distinct, like those of the client side generator: they are taken
+// every 7919 items from a random offset, 7919 being coprime to the 100_000 items. The offsets are
+// drawn in a subquery, evaluated once per order since random() is volatile.
+const SERVER_GENERATION_ORDER_LINE: &str = r"
+    INSERT INTO order_line
+    SELECT
+        ol_o_id, ol_d_id, $1::INTEGER, ol_number, ((ol_i_offset + ol_number::BIGINT * 7919) % 100000 + 1)::INTEGER, $1::INTEGER,
+        CASE WHEN ol_o_id < 2101 THEN to_timestamp($2::FLOAT8) AT TIME ZONE 'UTC' END,
+        5,
+        CASE WHEN ol_o_id < 2101 THEN 0 ELSE 0.01 + random() * 9999.98 END,
+        pg_temp.random_alpha_string(24, 24)
+    FROM
+        (
+            SELECT ol_d_id, ol_o_id, floor(random() * 100000)::INTEGER AS ol_i_offset
+            FROM generate_series(1, 10) AS ol_d_id, generate_series(1, 3000) AS ol_o_id
+        ) AS orders,
+        generate_series(1, ((ol_o_id::BIGINT * (ol_o_id + ol_d_id + $1::INTEGER)) % 11 + 5)::INTEGER) AS ol_number
+";
+
+// Read/write transactions queries, in execution order
+const DELIVERY_SELECT_NEW_ORDER: &str = r"
+    SELECT no_o_id
+    FROM new_order
+    WHERE
+        no_w_id = $1
+        AND no_d_id = $2
+    ORDER BY no_o_id ASC
+    LIMIT 1
+";
+
+const DELIVERY_DELETE_NEW_ORDER: &str = r"
+    DELETE FROM new_order
+    WHERE
+        no_o_id = $1
+        AND no_w_id = $2
+        AND no_d_id = $3
+";
+
+const DELIVERY_UPDATE_ORDERS: &str = r"
+    UPDATE orders
+    SET
+        o_carrier_id = $1
+    WHERE
+        o_id = $2
+        AND o_w_id = $3
+        AND o_d_id = $4
+    RETURNING o_c_id
+";
+
+const DELIVERY_UPDATE_ORDER_LINE: &str = r"
+    UPDATE order_line
+    SET
+        ol_delivery_d = current_timestamp
+    WHERE
+        ol_o_id = $1
+        AND ol_w_id = $2
+        AND ol_d_id = $3
+";
+
+const DELIVERY_SELECT_AMOUNT: &str = r"
+    SELECT SUM(ol_amount * ol_quantity) AS total_ol_amount
+    FROM order_line
+    WHERE
+        ol_o_id = $1
+        AND ol_w_id = $2
+        AND ol_d_id = $3
+";
+
+const DELIVERY_UPDATE_CUSTOMER: &str = r"
+    UPDATE customer
+    SET
+        c_delivery_cnt = c_delivery_cnt + 1,
+        c_balance = c_balance + $1
+    WHERE
+        c_id = $2
+        AND c_w_id = $3
+        AND c_d_id = $4
+";
+
+const NEW_ORDER_SELECT_WAREHOUSE: &str = r"
+    SELECT w_tax FROM warehouse WHERE w_id = $1
+";
+
+const NEW_ORDER_UPDATE_DISTRICT: &str = r"
+    UPDATE district
+    SET d_next_o_id = d_next_o_id + 1
+    WHERE
+        d_w_id = $1
+        AND d_id = $2
+    RETURNING d_tax, d_next_o_id AS o_id
+";
+
+const NEW_ORDER_SELECT_CUSTOMER: &str = r"
+    SELECT c_discount, c_last, c_credit
+    FROM customer
+    WHERE
+        c_w_id = $1
+        AND c_d_id = $2
+        AND c_id = $3
+";
+
+const NEW_ORDER_INSERT_ORDERS: &str = r"
+    INSERT INTO orders (o_id, o_d_id, o_w_id, o_c_id, o_entry_d, o_ol_cnt, o_all_local)
+    VALUES ($1, $2, $3, $4, NOW(), $5, $6)
+";
+
+const NEW_ORDER_INSERT_NEW_ORDER: &str = r"
+    INSERT INTO new_order (no_o_id, no_d_id, no_w_id)
+    VALUES ($1, $2, $3)
+";
+
+// The stock column holding the district information depends on the district id: {d_id} is replaced
+// by the district id on 2 digits
+const NEW_ORDER_SELECT_STOCK: &str = r"
+    SELECT s_quantity, s_dist_{d_id} AS s_dist, s_data FROM stock WHERE s_i_id = $1 AND s_w_id = $2
+";
+
+const NEW_ORDER_SELECT_ITEM: &str = r"
+    SELECT i_price, i_name, i_data FROM item WHERE i_id = $1
+";
+
+const NEW_ORDER_UPDATE_STOCK: &str = r"
+    UPDATE stock SET
+        s_quantity = $3,
+        s_ytd = s_ytd + $4::FLOAT,
+        s_order_cnt = s_order_cnt + 1,
+        s_remote_cnt = s_remote_cnt + $5
+    WHERE
+        s_i_id = $1
+        AND s_w_id = $2
+";
+
+const NEW_ORDER_INSERT_ORDER_LINE: &str = r"
+    INSERT INTO order_line (
+        ol_o_id, ol_d_id, ol_w_id, ol_number, ol_i_id, ol_supply_w_id, ol_quantity,
+        ol_amount, ol_dist_info
+    ) VALUES (
+        $1, $2, $3, $4, $5, $6, $7, $8, $9
+    )
+";
+
+const PAYMENT_UPDATE_WAREHOUSE: &str = r"
+    UPDATE warehouse
+    SET w_ytd = w_ytd + $1::FLOAT
+    WHERE w_id = $2
+    RETURNING w_name
+";
+
+const PAYMENT_UPDATE_DISTRICT: &str = r"
+    UPDATE district
+    SET d_ytd = d_ytd + $1::FLOAT
+    WHERE
+        d_w_id = $2
+        AND d_id = $3
+    RETURNING d_name
+";
+
+// Shared by the Payment and Order-Status transactions
+const SELECT_CUSTOMER_BY_LAST_NAME: &str = r"
+    SELECT c_id FROM customer
+    WHERE c_w_id = $1 AND c_d_id = $2 AND c_last = $3
+    ORDER BY c_first ASC
+";
+
+const PAYMENT_SELECT_CUSTOMER: &str = r"
+    SELECT
+        c_credit
+    FROM customer
+    WHERE
+        c_w_id = $1
+        AND c_d_id = $2
+        AND c_id = $3
+";
+
+const PAYMENT_UPDATE_CUSTOMER_BC: &str = r"
+    UPDATE customer
+    SET
+        c_balance = c_balance - $1::FLOAT,
+        c_ytd_payment = c_ytd_payment + 1,
+        c_data = substring($5||' '||c_data, 1, 500)
+    WHERE
+        c_id = $2 AND c_d_id = $3 AND c_w_id = $4
+";
+
+const PAYMENT_UPDATE_CUSTOMER: &str = r"
+    UPDATE customer
+    SET
+        c_balance = c_balance - $1::FLOAT,
+        c_ytd_payment = c_ytd_payment + 1
+    WHERE
+        c_id = $2 AND c_d_id = $3 AND c_w_id = $4
+";
+
+const PAYMENT_INSERT_HISTORY: &str = r"
+    INSERT INTO history
+        (h_c_id, h_c_d_id, h_c_w_id, h_d_id, h_w_id, h_date, h_amount, h_data)
+    VALUES
+        ($1, $2, $3, $4, $5, NOW(), $6, substring($7||'    '||$8, 1, 24))
+";
+
+const ORDER_STATUS_SELECT_CUSTOMER: &str = r"
+    SELECT
+        c_balance, c_first, c_middle, c_last
+    FROM customer
+    WHERE
+        c_w_id = $1
+        AND c_d_id = $2
+        AND c_id = $3
+";
+
+const ORDER_STATUS_SELECT_ORDERS: &str = r"
+    SELECT
+        o_id
+    FROM orders
+    WHERE
+        o_w_id = $1
+        AND o_d_id = $2
+        AND o_c_id = $3
+    ORDER BY o_entry_d DESC LIMIT 1
+";
+
+const ORDER_STATUS_SELECT_ORDER_LINE: &str = r"
+    SELECT
+        ol_i_id, ol_supply_w_id, ol_quantity, ol_amount, ol_delivery_d
+    FROM order_line
+    WHERE
+        ol_w_id = $1
+        AND ol_d_id = $2
+        AND ol_o_id = $3
+";
+
+const STOCK_LEVEL_SELECT_DISTRICT: &str = r"
+    SELECT d_next_o_id
+    FROM district
+    WHERE d_w_id = $1 AND d_id = $2
+";
+
+const STOCK_LEVEL_SELECT_ORDER_LINE: &str = r"
+    SELECT
+        DISTINCT ol_i_id
+    FROM order_line
+    WHERE
+        ol_w_id = $1
+        AND ol_d_id = $2
+        AND ol_o_id < $3
+        AND ol_o_id >= ($3 - 20)
+";
+
+const STOCK_LEVEL_SELECT_STOCK: &str = r"
+    SELECT s_quantity
+    FROM stock
+    WHERE
+        s_w_id = $1
+        AND s_i_id = $2
+        AND s_quantity < $3
+";
+
+// Functions implementing the read/write transactions in procedure mode. Their statements mirror
+// the statements above, and inputs are generated by the client in both modes. Result checks raise
+// the same messages as in statement mode.
+const DELIVERY_PROCEDURE: &str = r"
+    CREATE OR REPLACE FUNCTION tpcc_delivery(p_w_id INTEGER, p_carrier_id INTEGER)
+    RETURNS VOID LANGUAGE plpgsql AS $$
+    DECLARE
+        v_d_id INTEGER;
+        v_o_id INTEGER;
+        v_c_id INTEGER;
+        v_amount FLOAT8;
+    BEGIN
+        FOR v_d_id IN 1..10 LOOP
+            SELECT no_o_id INTO STRICT v_o_id
+            FROM new_order
+            WHERE
+                no_w_id = p_w_id
+                AND no_d_id = v_d_id
+            ORDER BY no_o_id ASC
+            LIMIT 1;
+
+            DELETE FROM new_order
+            WHERE
+                no_o_id = v_o_id
+                AND no_w_id = p_w_id
+                AND no_d_id = v_d_id;
+
+            UPDATE orders
+            SET
+                o_carrier_id = p_carrier_id
+            WHERE
+                o_id = v_o_id
+                AND o_w_id = p_w_id
+                AND o_d_id = v_d_id
+            RETURNING o_c_id INTO STRICT v_c_id;
+
+            UPDATE order_line
+            SET
+                ol_delivery_d = current_timestamp
+            WHERE
+                ol_o_id = v_o_id
+                AND ol_w_id = p_w_id
+                AND ol_d_id = v_d_id;
+
+            SELECT SUM(ol_amount * ol_quantity) INTO v_amount
+            FROM order_line
+            WHERE
+                ol_o_id = v_o_id
+                AND ol_w_id = p_w_id
+                AND ol_d_id = v_d_id;
+            -- The client fails to read a NULL amount in statement mode
+            IF v_amount IS NULL THEN
+                RAISE EXCEPTION 'Delivery transaction rollbacked. Order lines not found.';
+            END IF;
+
+            UPDATE customer
+            SET
+                c_delivery_cnt = c_delivery_cnt + 1,
+                c_balance = c_balance + v_amount
+            WHERE
+                c_id = v_c_id
+                AND c_w_id = p_w_id
+                AND c_d_id = v_d_id;
+        END LOOP;
+    END
+    $$
+";
+
+// Order lines are passed as arrays of item ids, supply warehouse ids and quantities, in order line
+// number order
+const NEW_ORDER_PROCEDURE: &str = r"
+    CREATE OR REPLACE FUNCTION tpcc_new_order(
+        p_w_id INTEGER, p_d_id INTEGER, p_c_id INTEGER, p_all_local INTEGER,
+        p_i_ids INTEGER[], p_supply_w_ids INTEGER[], p_quantities INTEGER[]
+    )
+    RETURNS VOID LANGUAGE plpgsql AS $$
+    DECLARE
+        v_o_id INTEGER;
+        v_i_price REAL;
+        v_s_quantity INTEGER;
+        v_s_dist TEXT;
+        v_ol_number INTEGER;
+    BEGIN
+        PERFORM w_tax FROM warehouse WHERE w_id = p_w_id;
+
+        UPDATE district
+        SET d_next_o_id = d_next_o_id + 1
+        WHERE
+            d_w_id = p_w_id
+            AND d_id = p_d_id
+        RETURNING d_next_o_id - 1 INTO STRICT v_o_id;
+
+        PERFORM c_discount, c_last, c_credit
+        FROM customer
+        WHERE
+            c_w_id = p_w_id
+            AND c_d_id = p_d_id
+            AND c_id = p_c_id;
+
+        INSERT INTO orders (o_id, o_d_id, o_w_id, o_c_id, o_entry_d, o_ol_cnt, o_all_local)
+        VALUES (v_o_id, p_d_id, p_w_id, p_c_id, NOW(), array_length(p_i_ids, 1), p_all_local);
+
+        INSERT INTO new_order (no_o_id, no_d_id, no_w_id)
+        VALUES (v_o_id, p_d_id, p_w_id);
+
+        FOR v_ol_number IN 1..array_length(p_i_ids, 1) LOOP
+            SELECT i_price INTO v_i_price FROM item WHERE i_id = p_i_ids[v_ol_number];
+            IF NOT FOUND THEN
+                RAISE EXCEPTION 'New-order transaction rollbacked. Item not found.';
+            END IF;
+
+            SELECT
+                s_quantity,
+                CASE p_d_id
+                    WHEN 1 THEN s_dist_01 WHEN 2 THEN s_dist_02 WHEN 3 THEN s_dist_03
+                    WHEN 4 THEN s_dist_04 WHEN 5 THEN s_dist_05 WHEN 6 THEN s_dist_06
+                    WHEN 7 THEN s_dist_07 WHEN 8 THEN s_dist_08 WHEN 9 THEN s_dist_09
+                    WHEN 10 THEN s_dist_10
+                END
+            INTO STRICT v_s_quantity, v_s_dist
+            FROM stock
+            WHERE s_i_id = p_i_ids[v_ol_number] AND s_w_id = p_supply_w_ids[v_ol_number];
+
+            IF v_s_quantity - p_quantities[v_ol_number] > 10 THEN
+                v_s_quantity := v_s_quantity - p_quantities[v_ol_number];
+            ELSE
+                v_s_quantity := v_s_quantity - p_quantities[v_ol_number] + 91;
+            END IF;
+
+            UPDATE stock SET
+                s_quantity = v_s_quantity,
+                s_ytd = s_ytd + p_quantities[v_ol_number]::FLOAT,
+                s_order_cnt = s_order_cnt + 1,
+                s_remote_cnt = s_remote_cnt + CASE WHEN p_supply_w_ids[v_ol_number] <> p_w_id THEN 1 ELSE 0 END
+            WHERE
+                s_i_id = p_i_ids[v_ol_number]
+                AND s_w_id = p_supply_w_ids[v_ol_number];
+
+            INSERT INTO order_line (
+                ol_o_id, ol_d_id, ol_w_id, ol_number, ol_i_id, ol_supply_w_id, ol_quantity,
+                ol_amount, ol_dist_info
+            ) VALUES (
+                v_o_id, p_d_id, p_w_id, v_ol_number, p_i_ids[v_ol_number], p_supply_w_ids[v_ol_number],
+                p_quantities[v_ol_number], v_i_price * p_quantities[v_ol_number], v_s_dist
+            );
+        END LOOP;
+    END
+    $$
+";
+
+// The customer is looked up by last name when p_c_last is not NULL
+const PAYMENT_PROCEDURE: &str = r"
+    CREATE OR REPLACE FUNCTION tpcc_payment(
+        p_w_id INTEGER, p_d_id INTEGER, p_c_w_id INTEGER, p_c_d_id INTEGER, p_c_id INTEGER,
+        p_c_last TEXT, p_h_amount REAL
+    )
+    RETURNS VOID LANGUAGE plpgsql AS $$
+    DECLARE
+        v_w_name TEXT;
+        v_d_name TEXT;
+        v_c_ids INTEGER[];
+        v_c_id INTEGER := p_c_id;
+        v_c_credit TEXT;
+    BEGIN
+        UPDATE warehouse
+        SET w_ytd = w_ytd + p_h_amount::FLOAT
+        WHERE w_id = p_w_id
+        RETURNING w_name INTO STRICT v_w_name;
+
+        UPDATE district
+        SET d_ytd = d_ytd + p_h_amount::FLOAT
+        WHERE
+            d_w_id = p_w_id
+            AND d_id = p_d_id
+        RETURNING d_name INTO STRICT v_d_name;
+
+        IF p_c_last IS NOT NULL THEN
+            SELECT array_agg(c_id ORDER BY c_first ASC) INTO v_c_ids
+            FROM customer
+            WHERE c_w_id = p_c_w_id AND c_d_id = p_c_d_id AND c_last = p_c_last;
+            IF v_c_ids IS NULL THEN
+                RAISE EXCEPTION 'Payment transaction rollbacked. Customer not found (c_last).';
+            END IF;
+            v_c_id := v_c_ids[array_length(v_c_ids, 1) / 2 + 1];
+        END IF;
+
+        SELECT
+            c_credit INTO STRICT v_c_credit
+        FROM customer
+        WHERE
+            c_w_id = p_c_w_id
+            AND c_d_id = p_c_d_id
+            AND c_id = v_c_id;
+
+        IF v_c_credit = 'BC' THEN
+            UPDATE customer
+            SET
+                c_balance = c_balance - p_h_amount::FLOAT,
+                c_ytd_payment = c_ytd_payment + 1,
+                c_data = substring(concat_ws(' ', v_c_id, p_c_d_id, p_c_w_id, p_d_id, p_w_id, p_h_amount)||' '||c_data, 1, 500)
+            WHERE
+                c_id = v_c_id AND c_d_id = p_c_d_id AND c_w_id = p_c_w_id;
+        ELSE
+            UPDATE customer
+            SET
+                c_balance = c_balance - p_h_amount::FLOAT,
+                c_ytd_payment = c_ytd_payment + 1
+            WHERE
+                c_id = v_c_id AND c_d_id = p_c_d_id AND c_w_id = p_c_w_id;
+        END IF;
+
+        INSERT INTO history
+            (h_c_id, h_c_d_id, h_c_w_id, h_d_id, h_w_id, h_date, h_amount, h_data)
+        VALUES
+            (v_c_id, p_c_d_id, p_c_w_id, p_d_id, p_w_id, NOW(), p_h_amount, substring(v_w_name||'    '||v_d_name, 1, 24));
+    END
+    $$
+";
+
+// Customer lookup and error message are the same as in tpcc_payment, like in statement mode
+const ORDER_STATUS_PROCEDURE: &str = r"
+    CREATE OR REPLACE FUNCTION tpcc_order_status(p_w_id INTEGER, p_d_id INTEGER, p_c_id INTEGER, p_c_last TEXT)
+    RETURNS VOID LANGUAGE plpgsql AS $$
+    DECLARE
+        v_c_ids INTEGER[];
+        v_c_id INTEGER := p_c_id;
+        v_o_id INTEGER;
+    BEGIN
+        IF p_c_last IS NOT NULL THEN
+            SELECT array_agg(c_id ORDER BY c_first ASC) INTO v_c_ids
+            FROM customer
+            WHERE c_w_id = p_w_id AND c_d_id = p_d_id AND c_last = p_c_last;
+            IF v_c_ids IS NULL THEN
+                RAISE EXCEPTION 'Payment transaction rollbacked. Customer not found (c_last).';
+            END IF;
+            v_c_id := v_c_ids[array_length(v_c_ids, 1) / 2 + 1];
+        END IF;
+
+        PERFORM
+            c_balance, c_first, c_middle, c_last
+        FROM customer
+        WHERE
+            c_w_id = p_w_id
+            AND c_d_id = p_d_id
+            AND c_id = v_c_id;
+
+        SELECT
+            o_id INTO STRICT v_o_id
+        FROM orders
+        WHERE
+            o_w_id = p_w_id
+            AND o_d_id = p_d_id
+            AND o_c_id = v_c_id
+        ORDER BY o_entry_d DESC LIMIT 1;
+
+        PERFORM
+            ol_i_id, ol_supply_w_id, ol_quantity, ol_amount, ol_delivery_d
+        FROM order_line
+        WHERE
+            ol_w_id = p_w_id
+            AND ol_d_id = p_d_id
+            AND ol_o_id = v_o_id;
+    END
+    $$
+";
+
+const STOCK_LEVEL_PROCEDURE: &str = r"
+    CREATE OR REPLACE FUNCTION tpcc_stock_level(p_w_id INTEGER, p_d_id INTEGER, p_threshold INTEGER)
+    RETURNS VOID LANGUAGE plpgsql AS $$
+    DECLARE
+        v_next_o_id INTEGER;
+        v_i_id INTEGER;
+    BEGIN
+        SELECT d_next_o_id INTO STRICT v_next_o_id
+        FROM district
+        WHERE d_w_id = p_w_id AND d_id = p_d_id;
+
+        FOR v_i_id IN
+            SELECT
+                DISTINCT ol_i_id
+            FROM order_line
+            WHERE
+                ol_w_id = p_w_id
+                AND ol_d_id = p_d_id
+                AND ol_o_id < v_next_o_id
+                AND ol_o_id >= (v_next_o_id - 20)
+        LOOP
+            PERFORM s_quantity
+            FROM stock
+            WHERE
+                s_w_id = p_w_id
+                AND s_i_id = v_i_id
+                AND s_quantity < p_threshold;
+        END LOOP;
+    END
+    $$
+";
+
+// Name and DDL of each transaction function, in transaction id order
+const PROCEDURES: [(&str, &str); 5] = [
+    ("tpcc_delivery", DELIVERY_PROCEDURE),
+    ("tpcc_new_order", NEW_ORDER_PROCEDURE),
+    ("tpcc_payment", PAYMENT_PROCEDURE),
+    ("tpcc_order_status", ORDER_STATUS_PROCEDURE),
+    ("tpcc_stock_level", STOCK_LEVEL_PROCEDURE),
+];
+
+// Calls of the transaction functions, one statement per transaction
+const DELIVERY_CALL: &str = "SELECT tpcc_delivery($1, $2)";
+const NEW_ORDER_CALL: &str = "SELECT tpcc_new_order($1, $2, $3, $4, $5, $6, $7)";
+const PAYMENT_CALL: &str = "SELECT tpcc_payment($1, $2, $3, $4, $5, $6, $7)";
+const ORDER_STATUS_CALL: &str = "SELECT tpcc_order_status($1, $2, $3, $4)";
+const STOCK_LEVEL_CALL: &str = "SELECT tpcc_stock_level($1, $2, $3)";
+
+// SQLSTATE of the errors raised by RAISE EXCEPTION: the result checks of the transaction functions
+const RAISE_EXCEPTION_SQLSTATE: &str = "P0001";
+// SQLSTATE of the errors raised by the INTO STRICT row lookups of the transaction functions:
+// no_data_found and too_many_rows
+const NO_DATA_FOUND_SQLSTATE: &str = "P0002";
+const TOO_MANY_ROWS_SQLSTATE: &str = "P0003";
 
 // TPC-C like benchmark
 pub struct TPCC {
@@ -26,16 +768,24 @@ pub struct TPCC {
     pub max_id: u32,
     // Vector of the read and write transactions that will be executed for this benchmark
     pub transactions_rw: Vec<BenchmarkTransaction>,
-    // Tables DDLs
-    pub table_ddls: Vec<BenchmarkStmt>,
-    // Primary keys DDLs
-    pub pkey_ddls: Vec<BenchmarkStmt>,
+    // Tables definitions
+    pub tables: Vec<BenchmarkTable>,
+    // Primary keys definitions
+    pub pkeys: Vec<BenchmarkIndex>,
     // Foreign keys DDLs
     pub fkey_ddls: Vec<BenchmarkStmt>,
-    // Additional index DDLs
-    pub index_ddls: Vec<BenchmarkStmt>,
+    // Additional indexes definitions
+    pub indexes: Vec<BenchmarkIndex>,
     // Vacuum table statememts
     pub vacuum_stmts: Vec<BenchmarkStmt>,
+    // NURand run-time constants, set by prepare_rw()
+    pub nurand: NURand,
+    // Home warehouse and district of the client, set by prepare_rw() with --affinity
+    pub home: Option<(i32, i32)>,
+    // Execution mode of the read/write transactions
+    pub mode: TransactionMode,
+    // Query protocol of the read/write transactions, set by prepare_rw()
+    pub protocol: QueryProtocol,
 }
 
 #[derive(Debug)]
@@ -49,6 +799,78 @@ impl fmt::Display for TPCCError {
 
 impl Error for TPCCError {}
 
+// Non-uniform random number generation, as defined by the TPC-C specification (2.1.6):
+// NURand(A, x, y) = (((random(0, A) | random(x, y)) + C) % (y - x + 1)) + x
+// One C constant is used per field: customer last name, customer id and item id.
+#[derive(Clone, Copy)]
+pub struct NURand {
+    pub c_last: u32,
+    pub c_id: u32,
+    pub ol_i_id: u32,
+    // Pick values uniformly, ignoring the constants
+    pub uniform: bool,
+}
+
+impl NURand {
+    // Random C-load constants, chosen once at loading time
+    pub fn new_load(rng: &mut StdRng, uniform: bool) -> NURand {
+        NURand {
+            c_last: rng.gen_range(0..=255),
+            c_id: rng.gen_range(0..=1023),
+            ol_i_id: rng.gen_range(0..=8191),
+            uniform,
+        }
+    }
+
+    // Derive the C-run constants from the C-load constants. The delta between C-load and C-run
+    // of the last name field must be between 65 and 119, and must be neither 96 nor 112. C-run
+    // of the customer and item ids are free, C-load values are kept. Every client must use the
+    // same constants: C-run is not random but picked among the valid values, based on C-load.
+    pub fn run_constants(&self) -> NURand {
+        let candidates: Vec<u32> = (0..=255)
+            .filter(|c: &u32| {
+                let delta = c.abs_diff(self.c_last);
+                (65..=119).contains(&delta) && delta != 96 && delta != 112
+            })
+            .collect();
+
+        NURand {
+            c_last: candidates[self.c_last as usize % candidates.len()],
+            c_id: self.c_id,
+            ol_i_id: self.ol_i_id,
+            uniform: self.uniform,
+        }
+    }
+
+    fn nurand(rng: &mut StdRng, a: u32, x: u32, y: u32, c: u32) -> u32 {
+        (((rng.gen_range(0..=a) | rng.gen_range(x..=y)) + c) % (y - x + 1)) + x
+    }
+
+    // Number used to build a customer last name, between 0 and 999
+    pub fn last_name_number(&self, rng: &mut StdRng) -> u32 {
+        match self.uniform {
+            true => rng.gen_range(0..=999),
+            false => NURand::nurand(rng, 255, 0, 999, self.c_last),
+        }
+    }
+
+    // Customer id, between 1 and 3000
+    pub fn customer_id(&self, rng: &mut StdRng) -> i32 {
+        match self.uniform {
+            true => rng.gen_range(1..=3_000),
+            false => NURand::nurand(rng, 1023, 1, 3_000, self.c_id) as i32,
+        }
+    }
+
+    // Item id, between 1 and 100_000
+    pub fn item_id(&self, rng: &mut StdRng) -> i32 {
+        match self.uniform {
+            true => rng.gen_range(1..=100_000),
+            false => NURand::nurand(rng, 8191, 1, 100_000, self.ol_i_id) as i32,
+        }
+    }
+}
+
 // TPC-C-like implementation
 impl TPCC {
     pub fn new(scalefactor: u32, min_id: u32, max_id: u32) -> TPCC {
@@ -65,41 +887,43 @@ impl TPCC {
                         weight: 4,
                         name: "Delivery".to_string(),
                         description: "The Delivery transaction".to_string(),
+                        read_only: false,
                     },
                     BenchmarkTransaction {
                         id: 2,
                         weight: 45,
                         name: "New-Order".to_string(),
                         description: "The New-Order transaction".to_string(),
+                        read_only: false,
                     },
                     BenchmarkTransaction {
                         id: 3,
                         weight: 43,
                         name: "Payment".to_string(),
                         description: "The Payment transaction".to_string(),
+                        read_only: false,
                     },
                     BenchmarkTransaction {
                         id: 4,
                         weight: 4,
                         name: "Order-Status".to_string(),
                         description: "The Order-Status transaction".to_string(),
+                        read_only: true,
                     },
                     BenchmarkTransaction {
                         id: 5,
                         weight: 4,
                         name: "Stock-Level".to_string(),
                         description: "The Stock-Level transaction".to_string(),
+                        read_only: true,
                     },
                 ]
             ),
-            table_ddls: Vec::from(
+            tables: Vec::from(
                 [
-                    BenchmarkStmt {
-                        sql: "DROP TABLE IF EXISTS warehouse CASCADE".to_string(),
-                    },
-                    BenchmarkStmt {
-                        sql: r"
-                            CREATE TABLE warehouse (
+                    BenchmarkTable {
+                        name: "warehouse".to_string(),
+                        columns: r"
                                 w_id INTEGER,
                                 w_name VARCHAR(10),
                                 w_street_1 VARCHAR(20),
@@ -109,14 +933,11 @@ impl TPCC {
                                 w_zip CHAR(9),
                                 w_tax REAL,
                                 w_ytd NUMERIC(24, 12)
-                            );".to_string(),
-                    },
-                    BenchmarkStmt {
-                        sql: "DROP TABLE IF EXISTS district CASCADE".to_string(),
+                            ".to_string(),
                     },
-                    BenchmarkStmt {
-                        sql: r"
-                            CREATE TABLE district (
+                    BenchmarkTable {
+                        name: "district".to_string(),
+                        columns: r"
                                 d_id INTEGER,
                                 d_w_id INTEGER,
                                 d_name VARCHAR(10),
@@ -128,14 +949,11 @@ impl TPCC {
                                 d_tax REAL,
                                 d_ytd NUMERIC(24, 12),
                                 d_next_o_id INTEGER
-                            );".to_string(),
-                    },
-                    BenchmarkStmt {
-                        sql: "DROP TABLE IF EXISTS customer CASCADE".to_string(),
+                            ".to_string(),
                     },
-                    BenchmarkStmt {
-                        sql: r"
-                            CREATE TABLE customer (
+                    BenchmarkTable {
+                        name: "customer".to_string(),
+                        columns: r"
                                 c_id INTEGER,
                                 c_d_id INTEGER,
                                 c_w_id INTEGER,
@@ -157,14 +975,11 @@ impl TPCC {
                                 c_payment_cnt REAL,
                                 c_delivery_cnt REAL,
                                 c_data VARCHAR(500)
-                            );".to_string(),
-                    },
-                    BenchmarkStmt {
-                        sql: "DROP TABLE IF EXISTS history CASCADE".to_string(),
+                            ".to_string(),
                     },
-                    BenchmarkStmt {
-                        sql: r"
-                            CREATE TABLE history (
+                    BenchmarkTable {
+                        name: "history".to_string(),
+                        columns: r"
                                 h_c_id INTEGER,
                                 h_c_d_id INTEGER,
                                 h_c_w_id INTEGER,
@@ -173,25 +988,19 @@ impl TPCC {
                                 h_date TIMESTAMP,
                                 h_amount REAL,
                                 h_data VARCHAR(24)
-                            );".to_string(),
-                    },
-                    BenchmarkStmt {
-                        sql: "DROP TABLE IF EXISTS new_order CASCADE".to_string(),
+                            ".to_string(),
                     },
-                    BenchmarkStmt {
-                        sql: r"
-                            CREATE TABLE new_order (
+                    BenchmarkTable {
+                        name: "new_order".to_string(),
+                        columns: r"
                                 no_o_id INTEGER,
                                 no_d_id INTEGER,
                                 no_w_id INTEGER
-                            );".to_string(),
-                    },
-                    BenchmarkStmt {
-                        sql: "DROP TABLE IF EXISTS orders CASCADE".to_string(),
+                            ".to_string(),
                     },
-                    BenchmarkStmt {
-                        sql: r"
-                            CREATE TABLE orders (
+                    BenchmarkTable {
+                        name: "orders".to_string(),
+                        columns: r"
                                 o_id INTEGER,
                                 o_d_id INTEGER,
                                 o_w_id INTEGER,
@@ -200,14 +1009,11 @@ impl TPCC {
                                 o_carrier_id INTEGER,
                                 o_ol_cnt INTEGER,
                                 o_all_local INTEGER
-                            );".to_string(),
-                    },
-                    BenchmarkStmt {
-                        sql: "DROP TABLE IF EXISTS order_line CASCADE".to_string(),
+                            ".to_string(),
                     },
-                    BenchmarkStmt {
-                        sql: r"
-                            CREATE TABLE order_line (
+                    BenchmarkTable {
+                        name: "order_line".to_string(),
+                        columns: r"
                                 ol_o_id INTEGER,
                                 ol_d_id INTEGER,
                                 ol_w_id INTEGER,
@@ -218,27 +1024,29 @@ impl TPCC {
                                 ol_quantity INTEGER,
                                 ol_amount REAL,
                                 ol_dist_info VARCHAR(24)
-                            );".to_string(),
-                    },
-                    BenchmarkStmt {
-                        sql: "DROP TABLE IF EXISTS item CASCADE".to_string(),
+                            ".to_string(),
                     },
-                    BenchmarkStmt {
-                        sql: r"
-                            CREATE TABLE item (
+                    BenchmarkTable {
+                        name: "item".to_string(),
+                        columns: r"
                                 i_id INTEGER,
                                 i_im_id INTEGER,
                                 i_name VARCHAR(24),
                                 i_price REAL,
                                 i_data VARCHAR(50)
-                            );".to_string(),
+                            ".to_string(),
                     },
-                    BenchmarkStmt {
-                        sql: "DROP TABLE IF EXISTS stock CASCADE".to_string(),
+                    BenchmarkTable {
+                        name: "nurand".to_string(),
+                        columns: r"
+                                nu_c_last INTEGER,
+                                nu_c_id INTEGER,
+                                nu_ol_i_id INTEGER
+                            ".to_string(),
                     },
-                    BenchmarkStmt {
-                        sql: r"
-                            CREATE TABLE stock (
+                    BenchmarkTable {
+                        name: "stock".to_string(),
+                        columns: r"
                                 s_i_id INTEGER,
                                 s_w_id INTEGER,
                                 s_quantity INTEGER,
@@ -256,35 +1064,59 @@ impl TPCC {
                                 s_order_cnt REAL,
                                 s_remote_cnt REAL,
                                 s_data VARCHAR(50)
-                            );".to_string(),
+                            ".to_string(),
                     },
                 ]
             ),
-            pkey_ddls: Vec::from(
+            pkeys: Vec::from(
                 [
-                    BenchmarkStmt {
-                        sql: "ALTER TABLE warehouse ADD PRIMARY KEY (w_id)".to_string(),
+                    BenchmarkIndex {
+                        name: "warehouse_pkey".to_string(),
+                        table: "warehouse".to_string(),
+                        definition: "(w_id)".to_string(),
+                        unique: true,
                     },
-                    BenchmarkStmt {
-                        sql: "ALTER TABLE district ADD PRIMARY KEY (d_w_id, d_id)".to_string(),
+                    BenchmarkIndex {
+                        name: "district_pkey".to_string(),
+                        table: "district".to_string(),
+                        definition: "(d_w_id, d_id)".to_string(),
+                        unique: true,
                     },
-                    BenchmarkStmt {
-                        sql: "ALTER TABLE customer ADD PRIMARY KEY (c_w_id, c_d_id, c_id)".to_string(),
+                    BenchmarkIndex {
+                        name: "customer_pkey".to_string(),
+                        table: "customer".to_string(),
+                        definition: "(c_w_id, c_d_id, c_id)".to_string(),
+                        unique: true,
                     },
-                    BenchmarkStmt {
-                        sql: "ALTER TABLE new_order ADD PRIMARY KEY (no_w_id, no_d_id, no_o_id)".to_string(),
+                    BenchmarkIndex {
+                        name: "new_order_pkey".to_string(),
+                        table: "new_order".to_string(),
+                        definition: "(no_w_id, no_d_id, no_o_id)".to_string(),
+                        unique: true,
                     },
-                    BenchmarkStmt {
-                        sql: "ALTER TABLE orders ADD PRIMARY KEY (o_w_id, o_d_id, o_id)".to_string(),
+                    BenchmarkIndex {
+                        name: "orders_pkey".to_string(),
+                        table: "orders".to_string(),
+                        definition: "(o_w_id, o_d_id, o_id)".to_string(),
+                        unique: true,
                     },
-                    BenchmarkStmt {
-                        sql: "ALTER TABLE order_line ADD PRIMARY KEY (ol_w_id, ol_d_id, ol_o_id, ol_number)".to_string(),
+                    BenchmarkIndex {
+                        name: "order_line_pkey".to_string(),
+                        table: "order_line".to_string(),
+                        definition: "(ol_w_id, ol_d_id, ol_o_id, ol_number)".to_string(),
+                        unique: true,
                     },
-                    BenchmarkStmt {
-                        sql: "ALTER TABLE stock ADD PRIMARY KEY (s_w_id, s_i_id)".to_string(),
+                    BenchmarkIndex {
+                        name: "stock_pkey".to_string(),
+                        table: "stock".to_string(),
+                        definition: "(s_w_id, s_i_id)".to_string(),
+                        unique: true,
                     },
-                    BenchmarkStmt {
-                        sql: "ALTER TABLE item ADD PRIMARY KEY (i_id)".to_string(),
+                    BenchmarkIndex {
+                        name: "item_pkey".to_string(),
+                        table: "item".to_string(),
+                        definition: "(i_id)".to_string(),
+                        unique: true,
                     },
                 ]
             ),
@@ -372,16 +1204,25 @@ impl TPCC {
                     },
                 ]
             ),
-            index_ddls: Vec::from(
+            indexes: Vec::from(
                 [
-                     BenchmarkStmt {
-                        sql: "CREATE UNIQUE INDEX i_customer_last_first ON customer (c_w_id, c_d_id, c_last, c_first, c_id);".to_string(),
+                    BenchmarkIndex {
+                        name: "i_customer_last_first".to_string(),
+                        table: "customer".to_string(),
+                        definition: "(c_w_id, c_d_id, c_last, c_first, c_id)".to_string(),
+                        unique: true,
                     },
-                    BenchmarkStmt {
-                        sql: "CREATE UNIQUE INDEX i_orders ON orders USING BTREE (o_w_id, o_d_id, o_c_id, o_id);".to_string(),
+                    BenchmarkIndex {
+                        name: "i_orders".to_string(),
+                        table: "orders".to_string(),
+                        definition: "USING BTREE (o_w_id, o_d_id, o_c_id, o_id)".to_string(),
+                        unique: true,
                     },
-                    BenchmarkStmt {
-                        sql: "CREATE INDEX i_stock_quantity ON stock (s_w_id, s_i_id, s_quantity)".to_string(),
+                    BenchmarkIndex {
+                        name: "i_stock_quantity".to_string(),
+                        table: "stock".to_string(),
+                        definition: "(s_w_id, s_i_id, s_quantity)".to_string(),
+                        unique: false,
                     },
                 ]
             ),
@@ -395,132 +1236,96 @@ impl TPCC {
                     BenchmarkStmt { sql: "VACUUM FREEZE ANALYZE orders".to_string() },
                     BenchmarkStmt { sql: "VACUUM FREEZE ANALYZE order_line".to_string() },
                     BenchmarkStmt { sql: "VACUUM FREEZE ANALYZE item".to_string() },
+                    BenchmarkStmt { sql: "VACUUM FREEZE ANALYZE nurand".to_string() },
                     BenchmarkStmt { sql: "VACUUM FREEZE ANALYZE stock".to_string() },
                 ]
             ),
+            nurand: NURand {c_last: 0, c_id: 0, ol_i_id: 0, uniform: true},
+            home: None,
+            mode: TransactionMode::Statement,
+            protocol: QueryProtocol::Prepared,
         }
     }
 
     // The Delivery business transaction
-    pub async fn delivery(conn: &mut PgConnection, warehouse_id :i32, _min_id :u32, _max_id :u32) -> Result<u128, Box<dyn std::error::Error>> {
+    pub async fn delivery(&self, conn: &mut PgConnection, rng: &mut StdRng, timer: &mut StmtTimer, warehouse_id :i32) -> Result<TransactionDurations, Box<dyn std::error::Error>> {
         let start = Instant::now();
 
-        let carrier_id :i32 = rand::thread_rng()
+        let carrier_id :i32 = rng
             .gen_range(1..=10);
 
+        if self.mode == TransactionMode::Procedure {
+            let call = SqlStmt::new("call", DELIVERY_CALL, vec![Int(warehouse_id), Int(carrier_id)]);
+            execute_stmt(conn, self.protocol, timer, call)
+                .await
+                .map_err(TPCC::procedure_error)?;
+
+            return Ok((start.elapsed().as_micros(), None));
+        }
+
         let mut transaction = conn.begin().await?;
 
         for district_id in 1..=10 {
-            let row: (i32,) = sqlx::query_as(r"
-                SELECT no_o_id
-                FROM new_order
-                WHERE
-                    no_w_id = $1
-                    AND no_d_id = $2
-                ORDER BY no_o_id ASC
-                LIMIT 1
-                ")
-                .bind(&warehouse_id)
-                .bind(&district_id)
-                .fetch_one(&mut transaction)
-                .await?;
-
-            let order_id: i32 = row.0;
-
-            sqlx::query(r"
-                DELETE FROM new_order
-                WHERE
-                    no_o_id = $1
-                    AND no_w_id = $2
-                    AND no_d_id = $3
-                ")
-                .bind(&order_id)
-                .bind(&warehouse_id)
-                .bind(&district_id)
-                .execute(&mut transaction)
-                .await?;
-
-            let row_orders: (i32,) = sqlx::query_as(r"
-                UPDATE orders
-                SET
-                    o_carrier_id = $1
-                WHERE
-                    o_id = $2
-                    AND o_w_id = $3
-                    AND o_d_id = $4
-                RETURNING o_c_id
-                ")
-                .bind(&carrier_id)
-                .bind(&order_id)
-                .bind(&warehouse_id)
-                .bind(&district_id)
-                .fetch_one(&mut transaction)
-                .await?;
-
-            let customer_id: i32 = row_orders.0;
-
-            sqlx::query(r"
-                UPDATE order_line
-                SET
-                    ol_delivery_d = current_timestamp
-                WHERE
-                    ol_o_id = $1
-                    AND ol_w_id = $2
-                    AND ol_d_id = $3
-                ")
-                .bind(&order_id)
-                .bind(&warehouse_id)
-                .bind(&district_id)
-                .execute(&mut transaction)
-                .await?;
-
-            let row_amount: (f64,)= sqlx::query_as(r"
-                SELECT SUM(ol_amount * ol_quantity) AS total_ol_amount
-                FROM order_line
-                WHERE
-                    ol_o_id = $1
-                    AND ol_w_id = $2
-                    AND ol_d_id = $3
-                ")
-                .bind(&order_id)
-                .bind(&warehouse_id)
-                .bind(&district_id)
-                .fetch_one(&mut transaction)
-                .await?;
-
-            let total_ol_amount :f64 = row_amount.0;
-
-            sqlx::query(r"
-                UPDATE customer
-                SET
-                    c_delivery_cnt = c_delivery_cnt + 1,
-                    c_balance = c_balance + $1
-                WHERE
-                    c_id = $2
-                    AND c_w_id = $3
-                    AND c_d_id = $4;
-                ")
-                .bind(&total_ol_amount)
-                .bind(&customer_id)
-                .bind(&warehouse_id)
-                .bind(&district_id)
-                .execute(&mut transaction)
-                .await?;
+            let rows = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_new_order", DELIVERY_SELECT_NEW_ORDER, vec![
+                Int(warehouse_id),
+                Int(district_id),
+            ])).await?;
+            let order_id: i32 = first_value(&rows)?;
+
+            execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("delete_new_order", DELIVERY_DELETE_NEW_ORDER, vec![
+                Int(order_id),
+                Int(warehouse_id),
+                Int(district_id),
+            ])).await?;
+
+            let rows = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("update_orders", DELIVERY_UPDATE_ORDERS, vec![
+                Int(carrier_id),
+                Int(order_id),
+                Int(warehouse_id),
+                Int(district_id),
+            ])).await?;
+            let customer_id: i32 = first_value(&rows)?;
+
+            execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("update_order_line", DELIVERY_UPDATE_ORDER_LINE, vec![
+                Int(order_id),
+                Int(warehouse_id),
+                Int(district_id),
+            ])).await?;
+
+            let rows = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_amount", DELIVERY_SELECT_AMOUNT, vec![
+                Int(order_id),
+                Int(warehouse_id),
+                Int(district_id),
+            ])).await?;
+            let total_ol_amount: f64 = first_value(&rows)?;
+
+            execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("update_customer", DELIVERY_UPDATE_CUSTOMER, vec![
+                Double(total_ol_amount),
+                Int(customer_id),
+                Int(warehouse_id),
+                Int(district_id),
+            ])).await?;
         }
+
+        let commit_start = Instant::now();
         transaction.commit().await?;
+        let commit_duration = commit_start.elapsed().as_micros();
 
-        Ok(start.elapsed().as_micros())
+        Ok((start.elapsed().as_micros(), Some(commit_duration)))
     }
 
     // The New-Order business transaction
-    pub async fn new_order(conn: &mut PgConnection, warehouse_id :i32, min_id :u32, max_id :u32) -> Result<u128, Box<dyn std::error::Error>> {
-        let district_id :i32 = rand::thread_rng()
-            .gen_range(1..=10);
-        let customer_id :i32 = rand::thread_rng()
-            .gen_range(1..=3000);
+    pub async fn new_order(&self, conn: &mut PgConnection, rng: &mut StdRng, timer: &mut StmtTimer, warehouse_id :i32, home_district_id: Option<i32>) -> Result<TransactionDurations, Box<dyn std::error::Error>> {
+        let nurand = &self.nurand;
+        let (min_id, max_id) = (self.min_id, self.max_id);
+        let district_id :i32 = match home_district_id {
+            Some(district_id) => district_id,
+            None => rng.gen_range(1..=10),
+        };
+        let customer_id :i32 = nurand.customer_id(rng);
 
         // Number of order_line entries
-        let ol_cnt :i32 = rand::thread_rng()
+        let ol_cnt :i32 = rng
             .gen_range(5..=15);
         let mut ol_all_local :i32 = 1;
 
@@ -531,18 +1336,17 @@ impl TPCC {
         let mut ol_i_id :i32;
 
         // Generate 1% of rollback by setting up an invalid item id
-        let mut rbk = rand::thread_rng()
+        let mut rbk = rng
             .gen_range(1..=100);
 
         for ol_number in 1..=ol_cnt {
             let mut ol_supply_w_id :i32 = warehouse_id;
-            let ol_quantity :i32 = rand::thread_rng()
+            let ol_quantity :i32 = rng
                 .gen_range(1..=10);
 
             // Generate ol_i_id / item id
             loop {
-                ol_i_id = rand::thread_rng()
-                    .gen_range(1..=100_000);
+                ol_i_id = nurand.item_id(rng);
                 if !item_ids.contains(&ol_i_id) {
                     item_ids.push(ol_i_id.clone());
                     break;
@@ -556,13 +1360,13 @@ impl TPCC {
             // If we have more than one warehouse, then ol_supply_w_id can be different from
             // warehouse_id
             if (max_id - min_id) > 0 {
-                let x :u8 = rand::thread_rng()
+                let x :u8 = rng
                     .gen_range(1..=100);
                 if x == 1 {
                     ol_all_local = 0;
                     // Pickup random warehouse id different from warehouse_id
                     while ol_supply_w_id == warehouse_id {
-                        ol_supply_w_id = rand::thread_rng()
+                        ol_supply_w_id = rng
                             .gen_range(min_id as i32..=max_id as i32);
                     }
                 }
@@ -572,97 +1376,88 @@ impl TPCC {
 
         // Starting database transaction
         let start = Instant::now();
+        if self.mode == TransactionMode::Procedure {
+            let i_ids: Vec<i32> = order_line_data.iter().map(|ol| ol.3).collect();
+            let supply_w_ids: Vec<i32> = order_line_data.iter().map(|ol| ol.1).collect();
+            let quantities: Vec<i32> = order_line_data.iter().map(|ol| ol.2).collect();
+            let call = SqlStmt::new("call", NEW_ORDER_CALL, vec![
+                Int(warehouse_id),
+                Int(district_id),
+                Int(customer_id),
+                Int(ol_all_local),
+                IntArray(i_ids),
+                IntArray(supply_w_ids),
+                IntArray(quantities),
+            ]);
+            execute_stmt(conn, self.protocol, timer, call)
+                .await
+                .map_err(TPCC::procedure_error)?;
+
+            return Ok((start.elapsed().as_micros(), None));
+        }
         let mut transaction = conn.begin().await?;
 
-        sqlx::query(r"
-            SELECT w_tax FROM warehouse WHERE w_id = $1
-            ")
-            .bind(&warehouse_id)
-            .execute(&mut transaction)
-            .await?;
-
-        let row_district: (f32, i32,) = sqlx::query_as(r"
-             UPDATE district
-             SET d_next_o_id = d_next_o_id + 1
-             WHERE
-                d_w_id = $1
-                AND d_id = $2
-            RETURNING d_tax, d_next_o_id AS o_id
-            ")
-            .bind(&warehouse_id)
-            .bind(&district_id)
-            .fetch_one(&mut transaction)
-            .await?;
-
-        let mut o_id :i32 = row_district.1;
+        execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_warehouse", NEW_ORDER_SELECT_WAREHOUSE, vec![
+            Int(warehouse_id),
+        ])).await?;
+
+        let rows = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("update_district", NEW_ORDER_UPDATE_DISTRICT, vec![
+            Int(warehouse_id),
+            Int(district_id),
+        ])).await?;
+        let row_district = rows.first().ok_or(sqlx::Error::RowNotFound)?;
+        let mut o_id :i32 = row_district.try_get(1)?;
         o_id -= 1;
 
-        sqlx::query(r"
-            SELECT c_discount, c_last, c_credit
-            FROM customer
-            WHERE
-                c_w_id = $1
-                AND c_d_id = $2
-                AND c_id = $3
-            ")
-            .bind(&warehouse_id)
-            .bind(&district_id)
-            .bind(&customer_id)
-            .execute(&mut transaction)
-            .await?;
+        execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_customer", NEW_ORDER_SELECT_CUSTOMER, vec![
+            Int(warehouse_id),
+            Int(district_id),
+            Int(customer_id),
+        ])).await?;
 
         // Inserting one new row into orders and new_order
-        sqlx::query(r"
-            INSERT INTO orders (o_id, o_d_id, o_w_id, o_c_id, o_entry_d, o_ol_cnt, o_all_local)
-            VALUES ($1, $2, $3, $4, NOW(), $5, $6)
-            ")
-            .bind(&o_id)
-            .bind(&district_id)
-            .bind(&warehouse_id)
-            .bind(&customer_id)
-            .bind(&ol_cnt)
-            .bind(&ol_all_local)
-            .execute(&mut transaction)
-            .await?;
-
-        sqlx::query(r"
-            INSERT INTO new_order (no_o_id, no_d_id, no_w_id)
-            VALUES ($1, $2, $3)
-            ")
-            .bind(&o_id)
-            .bind(&district_id)
-            .bind(&warehouse_id)
-            .execute(&mut transaction)
-            .await?;
-
-        let stock_query = format!("SELECT s_quantity, s_dist_{:0>2} AS s_dist, s_data FROM stock WHERE s_i_id = $1 AND s_w_id = $2", district_id);
+        execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("insert_orders", NEW_ORDER_INSERT_ORDERS, vec![
+            Int(o_id),
+            Int(district_id),
+            Int(warehouse_id),
+            Int(customer_id),
+            Int(ol_cnt),
+            Int(ol_all_local),
+        ])).await?;
+
+        execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("insert_new_order", NEW_ORDER_INSERT_NEW_ORDER, vec![
+            Int(o_id),
+            Int(district_id),
+            Int(warehouse_id),
+        ])).await?;
+
+        let stock_query = NEW_ORDER_SELECT_STOCK.replace("{d_id}", &format!("{:0>2}", district_id));
 
         for (ol_number, ol_supply_w_id, ol_quantity, ol_i_id) in order_line_data {
-            let row_item: Vec<(f32, String, String)> = sqlx::query_as(r"
-                SELECT i_price, i_name, i_data FROM item WHERE i_id = $1
-            ")
-            .bind(&ol_i_id)
-            .fetch_all(&mut transaction)
-            .await?;
-
-            if row_item.len() == 0 {
-                // Item not found then we must rollback the transaction
-                transaction.rollback().await?;
-                return Err(Box::new(TPCCError("New-order transaction rollbacked. Item not found.".into())));
-            }
+            let rows = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_item", NEW_ORDER_SELECT_ITEM, vec![
+                Int(ol_i_id),
+            ])).await?;
+
+            let row_item = match rows.first() {
+                Some(row) => row,
+                None => {
+                    // Item not found then we must rollback the transaction
+                    transaction.rollback().await?;
+                    return Err(Box::new(TPCCError("New-order transaction rollbacked. Item not found.".into())));
+                },
+            };
 
-            let i_price :f32 = row_item[0].0;
+            let i_price :f32 = row_item.try_get(0)?;
             let ol_amount :f32 = i_price * ol_quantity as f32;
 
             // Execute stock query
-            let row_stock :(i32, String, String) = sqlx::query_as(&stock_query)
-                .bind(&ol_i_id)
-                .bind(&ol_supply_w_id)
-                .fetch_one(&mut transaction)
-                .await?;
-
-            let mut s_quantity :i32 = row_stock.0;
-            let s_dist :String = row_stock.1;
+            let rows = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_stock", &stock_query, vec![
+                Int(ol_i_id),
+                Int(ol_supply_w_id),
+            ])).await?;
+            let row_stock = rows.first().ok_or(sqlx::Error::RowNotFound)?;
+            let mut s_quantity :i32 = row_stock.try_get(0)?;
+            let s_dist :String = row_stock.try_get(1)?;
 
             // Update stock
             if (s_quantity - ol_quantity) > 10 {
@@ -675,60 +1470,48 @@ impl TPCC {
             if ol_supply_w_id != warehouse_id {
                 s_remote_cnt_inc = 1.0;
             }
-            sqlx::query(r"
-                UPDATE stock SET
-                    s_quantity = $3,
-                    s_ytd = s_ytd + $4::FLOAT,
-                    s_order_cnt = s_order_cnt + 1,
-                    s_remote_cnt = s_remote_cnt + $5
-                WHERE
-                    s_i_id = $1
-                    AND s_w_id = $2
-                ")
-                .bind(&ol_i_id)
-                .bind(&ol_supply_w_id)
-                .bind(&s_quantity)
-                .bind(&ol_quantity)
-                .bind(&s_remote_cnt_inc)
-                .execute(&mut transaction)
-                .await?;
+            execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("update_stock", NEW_ORDER_UPDATE_STOCK, vec![
+                Int(ol_i_id),
+                Int(ol_supply_w_id),
+                Int(s_quantity),
+                Int(ol_quantity),
+                Real(s_remote_cnt_inc),
+            ])).await?;
 
             // Insert into order_line
-            sqlx::query(r"
-                INSERT INTO order_line (
-                    ol_o_id, ol_d_id, ol_w_id, ol_number, ol_i_id, ol_supply_w_id, ol_quantity,
-                    ol_amount, ol_dist_info
-                ) VALUES (
-                    $1, $2, $3, $4, $5, $6, $7, $8, $9
-                )
-                ")
-                .bind(&o_id)
-                .bind(&district_id)
-                .bind(&warehouse_id)
-                .bind(&ol_number)
-                .bind(&ol_i_id)
-                .bind(&ol_supply_w_id)
-                .bind(&ol_quantity)
-                .bind(&ol_amount)
-                .bind(&s_dist)
-                .execute(&mut transaction)
-                .await?;
+            execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("insert_order_line", NEW_ORDER_INSERT_ORDER_LINE, vec![
+                Int(o_id),
+                Int(district_id),
+                Int(warehouse_id),
+                Int(ol_number),
+                Int(ol_i_id),
+                Int(ol_supply_w_id),
+                Int(ol_quantity),
+                Real(ol_amount),
+                Text(Some(s_dist)),
+            ])).await?;
         }
 
+        let commit_start = Instant::now();
         transaction.commit().await?;
+        let commit_duration = commit_start.elapsed().as_micros();
 
-        Ok(start.elapsed().as_micros())
+        Ok((start.elapsed().as_micros(), Some(commit_duration)))
     }
 
     // The Payment business transaction
-    pub async fn payment(conn: &mut PgConnection, warehouse_id :i32, min_id :u32, max_id :u32) -> Result<u128, Box<dyn std::error::Error>> {
-        let x :u8 = rand::thread_rng()
+    pub async fn payment(&self, conn: &mut PgConnection, rng: &mut StdRng, timer: &mut StmtTimer, warehouse_id :i32, home_district_id: Option<i32>) -> Result<TransactionDurations, Box<dyn std::error::Error>> {
+        let nurand = &self.nurand;
+        let (min_id, max_id) = (self.min_id, self.max_id);
+        let x :u8 = rng
             .gen_range(1..=100);
-        let y :u8 = rand::thread_rng()
+        let y :u8 = rng
             .gen_range(1..=100);
 
-        let district_id :i32 = rand::thread_rng()
-            .gen_range(1..=10);
+        let district_id :i32 = match home_district_id {
+            Some(district_id) => district_id,
+            None => rng.gen_range(1..=10),
+        };
 
         let c_d_id :i32;
         let mut c_w_id :i32;
@@ -738,12 +1521,12 @@ impl TPCC {
             c_w_id = warehouse_id;
         }
         else {
-            c_d_id = rand::thread_rng()
+            c_d_id = rng
                 .gen_range(1..=10);
              if (max_id - min_id) > 0 {
                 // Pickup random warehouse id different from warehouse_id
                 loop {
-                    c_w_id = rand::thread_rng()
+                    c_w_id = rng
                         .gen_range(min_id as i32..=max_id as i32);
                     if c_w_id != warehouse_id {
                         break;
@@ -755,60 +1538,54 @@ impl TPCC {
             }
         }
         let mut c_last: String = "".to_string();
-        let mut c_id :i32 = rand::thread_rng()
-            .gen_range(1..=3_000);
+        let mut c_id :i32 = nurand.customer_id(rng);
 
         if y <= 60 {
-            let t :u32 = rand::thread_rng()
-                .gen_range(1..=1_000);
-            c_last = TPCC::gen_last(t);
+            c_last = TPCC::gen_last(nurand.last_name_number(rng));
         }
-        let h_amount :f32 = rand::thread_rng()
+        let h_amount :f32 = rng
             .gen_range(1.00..=5_000.00);
 
         let start = Instant::now();
+        if self.mode == TransactionMode::Procedure {
+            // NULL last name: the customer is looked up by id
+            let c_last: Option<String> = if y <= 60 { Some(c_last) } else { None };
+            let call = SqlStmt::new("call", PAYMENT_CALL, vec![
+                Int(warehouse_id),
+                Int(district_id),
+                Int(c_w_id),
+                Int(c_d_id),
+                Int(c_id),
+                Text(c_last),
+                Real(h_amount),
+            ]);
+            execute_stmt(conn, self.protocol, timer, call)
+                .await
+                .map_err(TPCC::procedure_error)?;
+
+            return Ok((start.elapsed().as_micros(), None));
+        }
         let mut transaction = conn.begin().await?;
 
-        let row_warehouse: (String,) = sqlx::query_as(r"
-            UPDATE warehouse
-            SET w_ytd = w_ytd + $1::FLOAT
-            WHERE w_id = $2
-            RETURNING w_name
-            ")
-            .bind(&h_amount)
-            .bind(&warehouse_id)
-            .fetch_one(&mut transaction)
-            .await?;
-
-        let w_name: String = row_warehouse.0;
-
-        let row_district: (String,) = sqlx::query_as(r"
-            UPDATE district
-            SET d_ytd = d_ytd + $1::FLOAT
-            WHERE
-                d_w_id = $2
-                AND d_id = $3
-            RETURNING d_name
-            ")
-            .bind(&h_amount)
-            .bind(&warehouse_id)
-            .bind(&district_id)
-            .fetch_one(&mut transaction)
-            .await?;
-
-        let d_name: String = row_district.0;
+        let rows = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("update_warehouse", PAYMENT_UPDATE_WAREHOUSE, vec![
+            Real(h_amount),
+            Int(warehouse_id),
+        ])).await?;
+        let w_name: String = first_value(&rows)?;
+
+        let rows = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("update_district", PAYMENT_UPDATE_DISTRICT, vec![
+            Real(h_amount),
+            Int(warehouse_id),
+            Int(district_id),
+        ])).await?;
+        let d_name: String = first_value(&rows)?;
 
         if y <= 60 {
-            let row_c_id: Vec<(i32,)> = sqlx::query_as(r"
-                SELECT c_id FROM customer
-                WHERE c_w_id = $1 AND c_d_id = $2 AND c_last = $3
-                ORDER BY c_first ASC
-                ")
-                .bind(&c_w_id)
-                .bind(&c_d_id)
-                .bind(&c_last)
-                .fetch_all(&mut transaction)
-                .await?;
+            let row_c_id = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_customer_by_last_name", SELECT_CUSTOMER_BY_LAST_NAME, vec![
+                Int(c_w_id),
+                Int(c_d_id),
+                Text(Some(c_last)),
+            ])).await?;
 
             if row_c_id.len() == 0 {
                 transaction.rollback().await?;
@@ -816,115 +1593,89 @@ impl TPCC {
             }
 
             let n = row_c_id.len();
-            c_id = row_c_id[n / 2].0;
+            c_id = row_c_id[n / 2].try_get(0)?;
         }
 
-        let row_customer: (String,) = sqlx::query_as(r"
-            SELECT
-                c_credit
-            FROM customer
-            WHERE
-                c_w_id = $1
-                AND c_d_id = $2
-                AND c_id = $3
-            ")
-            .bind(&c_w_id)
-            .bind(&c_d_id)
-            .bind(&c_id)
-            .fetch_one(&mut transaction)
-            .await?;
-
-        let c_credit: String = row_customer.0;
-
-        if c_credit == "BC".to_string() {
+        let rows = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_customer", PAYMENT_SELECT_CUSTOMER, vec![
+            Int(c_w_id),
+            Int(c_d_id),
+            Int(c_id),
+        ])).await?;
+        let c_credit: String = first_value(&rows)?;
+
+        if c_credit == "BC" {
             let pre_c_data = format!("{} {} {} {} {} {}", c_id, c_d_id, c_w_id, district_id, warehouse_id, h_amount);
-            sqlx::query(r"
-                UPDATE customer
-                SET
-                    c_balance = c_balance - $1::FLOAT,
-                    c_ytd_payment = c_ytd_payment + 1,
-                    c_data = substring($5||' '||c_data, 1, 500)
-                WHERE
-                    c_id = $2 AND c_d_id = $3 AND c_w_id = $4
-            ")
-            .bind(&h_amount)
-            .bind(&c_id)
-            .bind(&c_d_id)
-            .bind(&c_w_id)
-            .bind(&pre_c_data)
-            .execute(&mut transaction)
-            .await?;
+            execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("update_customer_bc", PAYMENT_UPDATE_CUSTOMER_BC, vec![
+                Real(h_amount),
+                Int(c_id),
+                Int(c_d_id),
+                Int(c_w_id),
+                Text(Some(pre_c_data)),
+            ])).await?;
         }
         else {
-            sqlx::query(r"
-                UPDATE customer
-                SET
-                    c_balance = c_balance - $1::FLOAT,
-                    c_ytd_payment = c_ytd_payment + 1
-                WHERE
-                    c_id = $2 AND c_d_id = $3 AND c_w_id = $4
-            ")
-            .bind(&h_amount)
-            .bind(&c_id)
-            .bind(&c_d_id)
-            .bind(&c_w_id)
-            .execute(&mut transaction)
-            .await?;
+            execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("update_customer", PAYMENT_UPDATE_CUSTOMER, vec![
+                Real(h_amount),
+                Int(c_id),
+                Int(c_d_id),
+                Int(c_w_id),
+            ])).await?;
         }
-        sqlx::query(r"
-            INSERT INTO history
-                (h_c_id, h_c_d_id, h_c_w_id, h_d_id, h_w_id, h_date, h_amount, h_data)
-            VALUES
-                ($1, $2, $3, $4, $5, NOW(), $6, substring($7||'    '||$8, 1, 24))
-            ")
-            .bind(&c_id)
-            .bind(&c_d_id)
-            .bind(&c_w_id)
-            .bind(&district_id)
-            .bind(&warehouse_id)
-            .bind(&h_amount)
-            .bind(&w_name)
-            .bind(&d_name)
-            .execute(&mut transaction)
-            .await?;
-
+        execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("insert_history", PAYMENT_INSERT_HISTORY, vec![
+            Int(c_id),
+            Int(c_d_id),
+            Int(c_w_id),
+            Int(district_id),
+            Int(warehouse_id),
+            Real(h_amount),
+            Text(Some(w_name)),
+            Text(Some(d_name)),
+        ])).await?;
+
+        let commit_start = Instant::now();
         transaction.commit().await?;
+        let commit_duration = commit_start.elapsed().as_micros();
 
-        Ok(start.elapsed().as_micros())
+        Ok((start.elapsed().as_micros(), Some(commit_duration)))
     }
 
     // The Order-Status business transaction
-    pub async fn order_status(conn: &mut PgConnection, warehouse_id :i32, _min_id :u32, _max_id :u32) -> Result<u128, Box<dyn std::error::Error>> {
-        let y :u8 = rand::thread_rng()
+    pub async fn order_status(&self, conn: &mut PgConnection, rng: &mut StdRng, timer: &mut StmtTimer, warehouse_id :i32, home_district_id: Option<i32>) -> Result<TransactionDurations, Box<dyn std::error::Error>> {
+        let nurand = &self.nurand;
+        let y :u8 = rng
             .gen_range(1..=100);
 
-        let district_id :i32 = rand::thread_rng()
-            .gen_range(1..=10);
+        let district_id :i32 = match home_district_id {
+            Some(district_id) => district_id,
+            None => rng.gen_range(1..=10),
+        };
 
         let mut c_last: String = "".to_string();
-        let mut c_id :i32 = rand::thread_rng()
-            .gen_range(1..=3_000);
+        let mut c_id :i32 = nurand.customer_id(rng);
 
         if y <= 60 {
-            let t :u32 = rand::thread_rng()
-                .gen_range(1..=999);
-            c_last = TPCC::gen_last(t);
+            c_last = TPCC::gen_last(nurand.last_name_number(rng));
         }
 
         let start = Instant::now();
+        if self.mode == TransactionMode::Procedure {
+            // NULL last name: the customer is looked up by id
+            let c_last: Option<String> = if y <= 60 { Some(c_last) } else { None };
+            let call = SqlStmt::new("call", ORDER_STATUS_CALL, vec![Int(warehouse_id), Int(district_id), Int(c_id), Text(c_last)]);
+            execute_stmt(conn, self.protocol, timer, call)
+                .await
+                .map_err(TPCC::procedure_error)?;
+
+            return Ok((start.elapsed().as_micros(), None));
+        }
         let mut transaction = conn.begin().await?;
 
         if y <= 60 {
-            let row_c_id: Vec<(i32,)> = sqlx::query_as(r"
-                SELECT c_id FROM customer
-                WHERE c_w_id = $1 AND c_d_id = $2 AND c_last = $3
-                ORDER BY c_first ASC
-                ")
-                .bind(&warehouse_id)
-                .bind(&district_id)
-                .bind(&c_last)
-                .fetch_all(&mut transaction)
-                .await?;
+            let row_c_id = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_customer_by_last_name", SELECT_CUSTOMER_BY_LAST_NAME, vec![
+                Int(warehouse_id),
+                Int(district_id),
+                Text(Some(c_last)),
+            ])).await?;
 
             if row_c_id.len() == 0 {
                 transaction.rollback().await?;
@@ -932,166 +1683,136 @@ impl TPCC {
             }
 
             let n = row_c_id.len();
-            c_id = row_c_id[n / 2].0;
+            c_id = row_c_id[n / 2].try_get(0)?;
         }
 
-        sqlx::query(r"
-            SELECT
-                c_balance, c_first, c_middle, c_last
-            FROM customer
-            WHERE
-                c_w_id = $1
-                AND c_d_id = $2
-                AND c_id = $3
-            ")
-            .bind(&warehouse_id)
-            .bind(&district_id)
-            .bind(&c_id)
-            .execute(&mut transaction)
-            .await?;
-
-        let row_order: (i32,) = sqlx::query_as(r"
-            SELECT
-                o_id
-            FROM orders
-            WHERE
-                o_w_id = $1
-                AND o_d_id = $2
-                AND o_c_id = $3
-            ORDER BY o_entry_d DESC LIMIT 1
-            ")
-            .bind(&warehouse_id)
-            .bind(&district_id)
-            .bind(&c_id)
-            .fetch_one(&mut transaction)
-            .await?;
-
-        let o_id :i32 = row_order.0;
-
-        sqlx::query(r"
-            SELECT
-                ol_i_id, ol_supply_w_id, ol_quantity, ol_amount, ol_delivery_d
-            FROM order_line
-            WHERE
-                ol_w_id = $1
-                AND ol_d_id = $2
-                AND ol_o_id = $3
-            ")
-            .bind(&warehouse_id)
-            .bind(&district_id)
-            .bind(&o_id)
-            .execute(&mut transaction)
-            .await?;
-
+        execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_customer", ORDER_STATUS_SELECT_CUSTOMER, vec![
+            Int(warehouse_id),
+            Int(district_id),
+            Int(c_id),
+        ])).await?;
+
+        let rows = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_orders", ORDER_STATUS_SELECT_ORDERS, vec![
+            Int(warehouse_id),
+            Int(district_id),
+            Int(c_id),
+        ])).await?;
+        let o_id :i32 = first_value(&rows)?;
+
+        execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_order_line", ORDER_STATUS_SELECT_ORDER_LINE, vec![
+            Int(warehouse_id),
+            Int(district_id),
+            Int(o_id),
+        ])).await?;
+
+        let commit_start = Instant::now();
         transaction.commit().await?;
-        Ok(start.elapsed().as_micros())
+        let commit_duration = commit_start.elapsed().as_micros();
+
+        Ok((start.elapsed().as_micros(), Some(commit_duration)))
     }
 
-    pub async fn stock_level(conn: &mut PgConnection, warehouse_id :i32, _min_id :u32, _max_id :u32) -> Result<u128, Box<dyn std::error::Error>> {
-        let district_id :i32 = rand::thread_rng()
-            .gen_range(1..=10);
-        let threshold :i32 = rand::thread_rng()
+    pub async fn stock_level(&self, conn: &mut PgConnection, rng: &mut StdRng, timer: &mut StmtTimer, warehouse_id :i32, home_district_id: Option<i32>) -> Result<TransactionDurations, Box<dyn std::error::Error>> {
+        let district_id :i32 = match home_district_id {
+            Some(district_id) => district_id,
+            None => rng.gen_range(1..=10),
+        };
+        let threshold :i32 = rng
             .gen_range(10..=20);
 
         let start = Instant::now();
+        if self.mode == TransactionMode::Procedure {
+            let call = SqlStmt::new("call", STOCK_LEVEL_CALL, vec![Int(warehouse_id), Int(district_id), Int(threshold)]);
+            execute_stmt(conn, self.protocol, timer, call)
+                .await
+                .map_err(TPCC::procedure_error)?;
+
+            return Ok((start.elapsed().as_micros(), None));
+        }
         let mut transaction = conn.begin().await?;
 
-        let row_district: (i32,) = sqlx::query_as(r"
-            SELECT d_next_o_id
-            FROM district
-            WHERE d_w_id = $1 AND d_id = $2
-            ")
-            .bind(&warehouse_id)
-            .bind(&district_id)
-            .fetch_one(&mut transaction)
-            .await?;
+        let rows = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_district", STOCK_LEVEL_SELECT_DISTRICT, vec![
+            Int(warehouse_id),
+            Int(district_id),
+        ])).await?;
+        let d_next_o_id :i32 = first_value(&rows)?;
 
-        let d_next_o_id :i32 = row_district.0;
-
-        let rows_order_line: Vec<(i32,)> = sqlx::query_as(r"
-            SELECT
-                DISTINCT ol_i_id
-            FROM order_line
-            WHERE
-                ol_w_id = $1
-                AND ol_d_id = $2
-                AND ol_o_id < $3
-                AND ol_o_id >= ($3 - 20)
-            ")
-            .bind(&warehouse_id)
-            .bind(&district_id)
-            .bind(&d_next_o_id)
-            .fetch_all(&mut transaction)
-            .await?;
+        let rows_order_line = execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_order_line", STOCK_LEVEL_SELECT_ORDER_LINE, vec![
+            Int(warehouse_id),
+            Int(district_id),
+            Int(d_next_o_id),
+        ])).await?;
 
         for row in rows_order_line {
-            let ol_i_id :i32 = row.0;
-            sqlx::query(r"
-                SELECT s_quantity
-                FROM stock
-                WHERE
-                    s_w_id = $1
-                    AND s_i_id = $2
-                    AND s_quantity < $3
-            ")
-            .bind(&warehouse_id)
-            .bind(&ol_i_id)
-            .bind(&threshold)
-            .execute(&mut transaction)
-            .await?;
+            let ol_i_id :i32 = row.try_get(0)?;
+            execute_stmt(&mut transaction, self.protocol, timer, SqlStmt::new("select_stock", STOCK_LEVEL_SELECT_STOCK, vec![
+                Int(warehouse_id),
+                Int(ol_i_id),
+                Int(threshold),
+            ])).await?;
         }
 
+        let commit_start = Instant::now();
         transaction.commit().await?;
-        Ok(start.elapsed().as_micros())
+        let commit_duration = commit_start.elapsed().as_micros();
+
+        Ok((start.elapsed().as_micros(), Some(commit_duration)))
+    }
+
+    // Errors raised by the result checks of the transaction functions are reported with their
+    // message only, like in statement mode. A failed row lookup is reported like the missing row
+    // of statement mode.
+    fn procedure_error(error: sqlx::Error) -> Box<dyn std::error::Error> {
+        if let Some(db_error) = error.as_database_error() {
+            match db_error.code().as_deref() {
+                Some(RAISE_EXCEPTION_SQLSTATE) => return Box::new(TPCCError(db_error.message().to_string())),
+                Some(NO_DATA_FOUND_SQLSTATE) | Some(TOO_MANY_ROWS_SQLSTATE) => return Box::new(sqlx::Error::RowNotFound),
+                _ => (),
+            }
+        }
+
+        Box::new(error)
     }
 
     // Returns a randomly generated alphanumeric string of length between min_length and max_length
-    fn random_alpha_string(min_length :usize, max_length: usize) -> String {
+    fn random_alpha_string(rng: &mut StdRng, min_length :usize, max_length: usize) -> String {
         let mut string_length: usize = max_length;
         if min_length < max_length {
-            string_length = rand::thread_rng()
+            string_length = rng
                 .gen_range(min_length..=max_length);
         }
-        let string_val :String = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(string_length)
-            .map(char::from)
+        let string_val :String = (0..string_length)
+            .map(|_| char::from(rng.sample(Alphanumeric)))
             .collect();
 
         string_val
     }
 
     // Returns a randomly generated Zip code
-    fn random_zip() -> String {
-        let part1 :u16 = rand::thread_rng()
+    fn random_zip(rng: &mut StdRng) -> String {
+        let part1 :u16 = rng
             .gen_range(1..10000);
 
         format!("{:0>4}11111", part1)
     }
 
     // Returns a randomly generated state code (2 capital letters)
-    fn random_state() -> String {
-        let mut rng = &mut rand::thread_rng();
+    fn random_state(rng: &mut StdRng) -> String {
         let sample = "ABCDEFGHIJKLMNOPQRSTUVWXYZ".as_bytes();
-        let val: Vec<u8> = sample.choose_multiple(&mut rng, 2).cloned().collect();
+        let val: Vec<u8> = sample.choose_multiple(rng, 2).cloned().collect();
 
         String::from_utf8(val).unwrap()
     }
 
-    // Generate customer's last name
-    fn gen_last(customer_id :u32) -> String {
-        let mut f_customer_id :u32 = customer_id;
-        if customer_id >= 1000 {
-            f_customer_id = rand::thread_rng()
-                .gen_range(0..=999);
-        }
+    // Generate customer's last name from a number between 0 and 999
+    fn gen_last(number :u32) -> String {
         let syllables = vec!["BAR", "OUGHT", "ABLE", "PRIS", "PRES", "ESE", "ANTI",
             "CALLY", "ATION", "EING"];
 
-        let f_customer_id_fmt = format!("{:0>3}", f_customer_id);
+        let number_fmt = format!("{:0>3}", number);
 
         let mut last :String = "".to_string();
-        for c in f_customer_id_fmt.chars() {
+        for c in number_fmt.chars() {
             let i :u32 = c.to_digit(10).unwrap();
             last = format!("{}{}", last, syllables[i as usize]);
         }
@@ -1099,596 +1820,605 @@ impl TPCC {
         last
     }
 
-    pub fn populate_item(client: &mut Client) -> Result<(), String> {
-        // Populate the item table with 100_000 items
-        let n_items = 100_000 as u32;
-        // Number of lines submitted for each COPY operation
-        let batch_size = 500 as u32;
-        // Number of batch, based on the total number of items and batch size
-        let n_batch = ((n_items as f64 / batch_size as f64) as f64).ceil() as u32;
+    // Load the given tables for one warehouse, using the given COPY format. Text COPY generates
+    // rows in the current thread and runs one COPY command per batch of rows, binary COPY
+    // generates rows in a separate producer thread and runs one COPY command per table. Each
+    // warehouse uses its own random generator, derived from the seed if any.
+    fn load_tables(&self, client: &mut Client, tables: &'static [&'static str], warehouse_id: u32, args: &InitArgs, nurand: NURand, progress: &mut ProgressReporter) -> Result<Vec<LoadStats>, String> {
+        let mut all_stats: Vec<LoadStats> = Vec::new();
+        let mut rng = stream_rng(args.seed, warehouse_id as u64);
+        // Entry and delivery dates
+        let now_us = TPCC::load_timestamp(args.seed) * 1_000_000;
+
+        match args.copy_format {
+            CopyFormat::Text => {
+                for table in tables.iter() {
+                    progress.start_table(warehouse_id, table);
+                    let mut loader = TextCopyLoader::new(client, table, TEXT_COPY_BATCH_SIZE);
+                    TPCC::generate_table(&mut rng, &nurand, table, warehouse_id, now_us, &mut |row| {
+                        progress.add_rows(1);
+                        loader.push(row)
+                    })?;
+                    LoadStats::merge(&mut all_stats, loader.finish()?);
+                }
+            },
+            CopyFormat::Binary => {
+                let (tx, rx) = bounded(COPY_CHANNEL_SIZE);
+                let table_defs = self.tables.clone();
+                // Rows are reported once generated, the bounded channel keeps the consumer close
+                let mut producer_progress = progress.clone();
+
+                let producer = thread::spawn(move || -> Result<(), String> {
+                    let mut sender = CopySender::new(tx);
+                    for table in tables.iter() {
+                        let types = match table_defs.iter().find(|t| t.name == *table) {
+                            Some(t) => t.column_types(),
+                            None => return Err(format!("unknown table {}", table)),
+                        };
+                        producer_progress.start_table(warehouse_id, table);
+                        sender.start(table, types)?;
+                        TPCC::generate_table(&mut rng, &nurand, table, warehouse_id, now_us, &mut |row| {
+                            producer_progress.add_rows(1);
+                            sender.push(row)
+                        })?;
+                        sender.end()?;
+                    }
+                    producer_progress.flush();
+                    Ok(())
+                });
+
+                // When the consumer fails, the producer fails too because its channel is closed:
+                // the consumer error comes first.
+                let consumed = consume_binary_copy(client, rx);
+                let produced = producer.join().expect("the producer thread panicked");
+                all_stats = consumed?;
+                produced?;
+            },
+        }
 
-        for b in 1..=n_batch {
-            // Start a new copy from stdin op.
-            let mut writer = match client.copy_in("COPY item FROM stdin") {
-                Ok(w) => w,
-                Err(e) => return Err(e.to_string()),
-            };
+        Ok(all_stats)
+    }
 
-            // Calculate items interval
-            let batch_start = (b * batch_size - batch_size + 1) as u32;
-            let mut batch_end = (b * batch_size) as u32;
-            if batch_end > n_items {
-                batch_end = n_items;
-            }
+    // NURand C-load constants are stored in the nurand table so that every job, and later the run
+    // clients, use the same values
+    fn save_nurand(client: &mut Client, nurand: &NURand) -> Result<(), String> {
+        let values = [nurand.c_last as i32, nurand.c_id as i32, nurand.ol_i_id as i32];
+        match client.execute(NURAND_INSERT, &[&values[0], &values[1], &values[2]]) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
 
-            // Fill the write buffer batch_size items
-            for i_id in batch_start..=batch_end {
-                // Generate random data
-                // Column i_name
-                let i_name :String = TPCC::random_alpha_string(14, 24);
-                // Column i_price
-                let i_price :f64 = rand::thread_rng()
-                    .gen_range(1.00..=100.00);
-                // Column i_im_id
-                let i_im_id :i32 = rand::thread_rng()
-                    .gen_range(1..=10_000);
-                // Column i_data
-                let mut i_data :String = TPCC::random_alpha_string(26, 50);
-                let i_data_length = i_data.len();
-                let orig :u32 = rand::thread_rng()
-                    .gen_range(1..=100);
-                if orig <= 10 {
-                    let pos :usize = rand::thread_rng()
-                        .gen_range(1..(i_data_length - 8));
-                    i_data.replace_range(pos..=(pos + 8), "ORIGINAL");
-                }
+    fn get_nurand(client: &mut Client, uniform: bool) -> Result<NURand, String> {
+        let row = match client.query_one("SELECT nu_c_last, nu_c_id, nu_ol_i_id FROM nurand", &[]) {
+            Ok(row) => row,
+            Err(e) => return Err(format!("could not read NURand constants: {}", e)),
+        };
 
-                let line = format!("{}\t{}\t{}\t{:.2}\t{}\n", i_id, i_im_id, i_name, i_price, i_data);
-                match writer.write_all(line.as_bytes()) {
-                    Ok(_) => (),
-                    Err(e) => return Err(e.to_string()),
-                }
-            }
+        Ok(NURand {
+            c_last: row.get::<_, i32>(0) as u32,
+            c_id: row.get::<_, i32>(1) as u32,
+            ol_i_id: row.get::<_, i32>(2) as u32,
+            uniform,
+        })
+    }
 
-            // Finishing the copy order for the current batch
-            match writer.finish() {
-                Ok(_) => (),
-                Err(e) => return Err(e.to_string()),
-            }
+    // Entry and delivery dates of the loaded data, in seconds since the Unix epoch
+    fn load_timestamp(seed: Option<u64>) -> i64 {
+        match seed {
+            Some(_) => SEEDED_LOAD_TIMESTAMP,
+            None => Utc::now().timestamp(),
         }
-
-        Ok(())
     }
 
-    pub fn populate_warehouse(client: &mut Client, warehouse_id: u32) -> Result<(), String> {
-        // Start a new copy from stdin op.
-        let mut writer = match client.copy_in("COPY warehouse FROM stdin") {
-            Ok(w) => w,
+    // Populate one table for the given warehouse using INSERT .. SELECT. Values follow the same
+    // rules as the client side generators.
+    fn generate_table_server(client: &mut Client, nurand: &NURand, table: &str, warehouse_id: u32, now_s: f64) -> Result<LoadStats, String> {
+        let warehouse_id = warehouse_id as i32;
+        let c_last = nurand.c_last as i32;
+        let sql = TPCC::server_generation_sql(table)?;
+        let params: Vec<&(dyn ToSql + Sync)> = match table {
+            "stock" => vec![&warehouse_id],
+            "customer" => vec![&warehouse_id, &now_s, &c_last, &nurand.uniform],
+            _ => vec![&warehouse_id, &now_s],
+        };
+        let start = Instant::now();
+        let mut stats = LoadStats::new(table);
+
+        stats.rows = match client.execute(sql, &params) {
+            Ok(rows) => rows,
             Err(e) => return Err(e.to_string()),
         };
+        stats.duration_us = start.elapsed().as_micros();
 
-        // Generate random data
-        let w_name :String = TPCC::random_alpha_string(6, 10);
-        let w_street1 :String = TPCC::random_alpha_string(10, 20);
-        let w_street2 :String = TPCC::random_alpha_string(10, 20);
-        let w_city :String = TPCC::random_alpha_string(10, 20);
-        let w_state: String = TPCC::random_state();
-        let w_zip: String = TPCC::random_zip();
-        let w_tax: f64 = rand::thread_rng()
-            .gen_range(0.10..0.20);
+        Ok(stats)
+    }
 
-        let line = format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.4}\t300000.00\n", warehouse_id, w_name, w_street1, w_street2, w_city, w_state, w_zip, w_tax);
+    // INSERT .. SELECT statement populating one table server side
+    fn server_generation_sql(table: &str) -> Result<&'static str, String> {
+        match table {
+            "stock" => Ok(SERVER_GENERATION_STOCK),
+            "customer" => Ok(SERVER_GENERATION_CUSTOMER),
+            "history" => Ok(SERVER_GENERATION_HISTORY),
+            "orders" => Ok(SERVER_GENERATION_ORDERS),
+            "order_line" => Ok(SERVER_GENERATION_ORDER_LINE),
+            _ => Err(format!("no server side data generator for table {}", table)),
+        }
+    }
 
-        match writer.write_all(line.as_bytes()) {
-            Ok(_) => (),
-            Err(e) => return Err(e.to_string()),
+    // Estimated number of rows of one table. Orders have 10 order lines on average.
+    fn estimated_rows(&self, table: &str) -> u64 {
+        let n_warehouses = self.scalefactor as u64;
+        match table {
+            "item" => 100_000,
+            "nurand" => 1,
+            "warehouse" => n_warehouses,
+            "district" => 10 * n_warehouses,
+            "stock" => 100_000 * n_warehouses,
+            "customer" | "history" | "orders" => 30_000 * n_warehouses,
+            "new_order" => 9_000 * n_warehouses,
+            "order_line" => 300_000 * n_warehouses,
+            _ => 0,
         }
-        // Finishing the copy order
-        match writer.finish() {
-            Ok(_) => (),
-            Err(e) => return Err(e.to_string()),
+    }
+
+    // Average length of the generated text values, following the data generation rules. Other
+    // text columns are filled up to their declared length.
+    fn text_length(column: &str) -> Option<f64> {
+        match column {
+            "w_name" | "d_name" => Some(8.0),
+            "w_street_1" | "w_street_2" | "w_city" | "d_street_1" | "d_street_2" | "d_city"
+                | "c_street_1" | "c_street_2" | "c_city" => Some(15.0),
+            "c_first" => Some(12.0),
+            // Three syllables of 4.1 characters on average
+            "c_last" => Some(12.3),
+            "c_data" => Some(400.0),
+            "h_data" => Some(18.0),
+            "i_name" => Some(19.0),
+            "i_data" | "s_data" => Some(38.0),
+            _ => None,
         }
+    }
 
-        Ok(())
+    // Seed the session random generator used by server side data generation, from the stream of
+    // the given warehouse. Nothing to do without seed.
+    fn set_server_seed(client: &mut Client, seed: Option<u64>, warehouse_id: u32) -> Result<(), String> {
+        if seed.is_none() {
+            return Ok(());
+        }
+        // setseed() expects a value between -1 and 1
+        let server_seed: f64 = stream_rng(seed, warehouse_id as u64).gen_range(-1.0..=1.0);
+        match client.execute("SELECT setseed($1)", &[&server_seed]) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
     }
 
-    pub fn populate_district(client: &mut Client, warehouse_id: u32) -> Result<(), String> {
-        // Calculate district ids interval
-        let district_start = 1;
-        let district_end = 10;
+    // Generate the rows of one table for the given warehouse
+    fn generate_table(rng: &mut StdRng, nurand: &NURand, table: &str, warehouse_id: u32, now_us: i64, emit: &mut dyn FnMut(CopyRow) -> Result<(), String>) -> Result<(), String> {
+        match table {
+            "item" => TPCC::generate_item(rng, emit),
+            "warehouse" => TPCC::generate_warehouse(rng, warehouse_id, emit),
+            "district" => TPCC::generate_district(rng, warehouse_id, emit),
+            "stock" => TPCC::generate_stock(rng, warehouse_id, emit),
+            "customer" => TPCC::generate_customer(rng, nurand, warehouse_id, now_us, emit),
+            "history" => TPCC::generate_history(rng, warehouse_id, now_us, emit),
+            "orders" => TPCC::generate_orders(rng, warehouse_id, now_us, emit),
+            "new_order" => TPCC::generate_new_order(warehouse_id, emit),
+            "order_line" => TPCC::generate_order_line(rng, warehouse_id, now_us, emit),
+            _ => Err(format!("no data generator for table {}", table)),
+        }
+    }
 
-        // Start a new copy from stdin op.
-        let mut writer = match client.copy_in("COPY district FROM stdin") {
-            Ok(w) => w,
-            Err(e) => return Err(e.to_string()),
-        };
+    // Generate the 100_000 rows of the item table
+    pub fn generate_item(rng: &mut StdRng, emit: &mut dyn FnMut(CopyRow) -> Result<(), String>) -> Result<(), String> {
+        let n_items = 100_000;
 
-        // Fill the write buffer
-        for district_id in district_start..=district_end {
+        for i_id in 1..=n_items {
             // Generate random data
-            let d_name :String = TPCC::random_alpha_string(6, 10);
-            let d_street1 :String = TPCC::random_alpha_string(10, 20);
-            let d_street2 :String = TPCC::random_alpha_string(10, 20);
-            let d_city :String = TPCC::random_alpha_string(10, 20);
-            let d_state: String = TPCC::random_state();
-            let d_zip: String = TPCC::random_zip();
-            let d_tax: f64 = rand::thread_rng()
-                .gen_range(0.10..0.20);
-
-            let line = format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.4}\t30000.00\t3001\n", district_id, warehouse_id, d_name, d_street1, d_street2, d_city, d_state, d_zip, d_tax);
-
-            match writer.write_all(line.as_bytes()) {
-                Ok(_) => (),
-                Err(e) => return Err(e.to_string()),
+            // Column i_name
+            let i_name :String = TPCC::random_alpha_string(rng, 14, 24);
+            // Column i_price
+            let i_price :f64 = rng
+                .gen_range(1.00..=100.00);
+            // Column i_im_id
+            let i_im_id :i32 = rng
+                .gen_range(1..=10_000);
+            // Column i_data
+            let mut i_data :String = TPCC::random_alpha_string(rng, 26, 50);
+            let i_data_length = i_data.len();
+            let orig :u32 = rng
+                .gen_range(1..=100);
+            if orig <= 10 {
+                let pos :usize = rng
+                    .gen_range(1..(i_data_length - 8));
+                i_data.replace_range(pos..=(pos + 8), "ORIGINAL");
             }
-        }
 
-        // Finishing the copy order
-        match writer.finish() {
-            Ok(_) => (),
-            Err(e) => return Err(e.to_string()),
+            emit(vec![
+                CopyValue::Int(i_id),
+                CopyValue::Int(i_im_id),
+                CopyValue::Text(i_name),
+                CopyValue::Real(TPCC::round(i_price, 2)),
+                CopyValue::Text(i_data),
+            ])?;
         }
 
         Ok(())
     }
 
-    pub fn populate_customer(client: &mut Client, warehouse_id: u32) -> Result<(), String> {
-        // Populate the customer table with 30_000 items per warehouse
-        let n_items = 30_000 as u32;
-        let n_customer_per_district = 3_000 as u32;
-
-        let mut customer_id :u32 = 1;
-        let mut district_id :u32 = 1;
+    pub fn generate_warehouse(rng: &mut StdRng, warehouse_id: u32, emit: &mut dyn FnMut(CopyRow) -> Result<(), String>) -> Result<(), String> {
+        // Generate random data
+        let w_name :String = TPCC::random_alpha_string(rng, 6, 10);
+        let w_street1 :String = TPCC::random_alpha_string(rng, 10, 20);
+        let w_street2 :String = TPCC::random_alpha_string(rng, 10, 20);
+        let w_city :String = TPCC::random_alpha_string(rng, 10, 20);
+        let w_state: String = TPCC::random_state(rng);
+        let w_zip: String = TPCC::random_zip(rng);
+        let w_tax: f64 = rng
+            .gen_range(0.10..0.20);
 
-        // Number of lines submitted for each COPY operation
-        let batch_size = 500 as u32;
-        // Number of batch, based on the number of items and batch size
-        let n_batch = ((n_items as f64 / batch_size as f64) as f64).ceil() as u32;
+        emit(vec![
+            CopyValue::Int(warehouse_id as i32),
+            CopyValue::Text(w_name),
+            CopyValue::Text(w_street1),
+            CopyValue::Text(w_street2),
+            CopyValue::Text(w_city),
+            CopyValue::Text(w_state),
+            CopyValue::Text(w_zip),
+            CopyValue::Real(TPCC::round(w_tax, 4)),
+            // Column w_ytd
+            CopyValue::Numeric(300_000),
+        ])
+    }
 
-        // Column since
-        let c_since: String = format!("{}", Utc::now().format("%Y-%m-%d %H:%M:%S"));
+    pub fn generate_district(rng: &mut StdRng, warehouse_id: u32, emit: &mut dyn FnMut(CopyRow) -> Result<(), String>) -> Result<(), String> {
+        for district_id in 1..=10 {
+            // Generate random data
+            let d_name :String = TPCC::random_alpha_string(rng, 6, 10);
+            let d_street1 :String = TPCC::random_alpha_string(rng, 10, 20);
+            let d_street2 :String = TPCC::random_alpha_string(rng, 10, 20);
+            let d_city :String = TPCC::random_alpha_string(rng, 10, 20);
+            let d_state: String = TPCC::random_state(rng);
+            let d_zip: String = TPCC::random_zip(rng);
+            let d_tax: f64 = rng
+                .gen_range(0.10..0.20);
 
-        for b in 1..=n_batch {
-            // Start a new copy from stdin op.
-            let mut writer = match client.copy_in("COPY customer FROM stdin") {
-                Ok(w) => w,
-                Err(e) => return Err(e.to_string()),
-            };
+            emit(vec![
+                CopyValue::Int(district_id),
+                CopyValue::Int(warehouse_id as i32),
+                CopyValue::Text(d_name),
+                CopyValue::Text(d_street1),
+                CopyValue::Text(d_street2),
+                CopyValue::Text(d_city),
+                CopyValue::Text(d_state),
+                CopyValue::Text(d_zip),
+                CopyValue::Real(TPCC::round(d_tax, 4)),
+                // Column d_ytd
+                CopyValue::Numeric(30_000),
+                // Column d_next_o_id
+                CopyValue::Int(3_001),
+            ])?;
+        }
 
-            // Calculate items interval
-            let batch_start = (b * batch_size - batch_size + 1) as u32;
-            let mut batch_end = (b * batch_size) as u32;
-            if batch_end > n_items {
-                batch_end = n_items;
-            }
+        Ok(())
+    }
 
-            // Fill the write buffer batch_size items
-            for _ in batch_start..=batch_end {
-                let c_first :String = TPCC::random_alpha_string(8, 16);
+    // Generate 30_000 customers per warehouse, 3_000 per district
+    pub fn generate_customer(rng: &mut StdRng, nurand: &NURand, warehouse_id: u32, c_since: i64, emit: &mut dyn FnMut(CopyRow) -> Result<(), String>) -> Result<(), String> {
+        for district_id in 1..=10 {
+            for customer_id in 1..=3_000 {
+                let c_first :String = TPCC::random_alpha_string(rng, 8, 16);
                 let c_middle: String = "OE".to_string();
-                let c_last: String = TPCC::gen_last(customer_id);
-                let c_street1 :String = TPCC::random_alpha_string(10, 20);
-                let c_street2 :String = TPCC::random_alpha_string(10, 20);
-                let c_city :String = TPCC::random_alpha_string(10, 20);
-                let c_state: String = TPCC::random_state();
-                let c_zip: String = TPCC::random_zip();
-                let c_phone: u64 = rand::thread_rng()
+                // The first 999 customers get a sequential last name
+                let c_last: String = match customer_id {
+                    1..=999 => TPCC::gen_last(customer_id as u32),
+                    _ => TPCC::gen_last(nurand.last_name_number(rng)),
+                };
+                let c_street1 :String = TPCC::random_alpha_string(rng, 10, 20);
+                let c_street2 :String = TPCC::random_alpha_string(rng, 10, 20);
+                let c_city :String = TPCC::random_alpha_string(rng, 10, 20);
+                let c_state: String = TPCC::random_state(rng);
+                let c_zip: String = TPCC::random_zip(rng);
+                let c_phone: u64 = rng
                     .gen_range(1000000000000000..=9999999999999999);
-                let c_discount: f64 = rand::thread_rng()
+                let c_discount: f64 = rng
                     .gen_range(0.00..=0.50);
-                let c_data: String = TPCC::random_alpha_string(300, 500);
+                let c_data: String = TPCC::random_alpha_string(rng, 300, 500);
                 // Column c_credit
                 let mut c_credit: String = "GC".to_string();
-                let i = rand::thread_rng()
+                let i = rng
                     .gen_range(1..=10);
                 if i == 1 {
                     c_credit = "BC".to_string();
                 }
 
-                let line = format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t50000.00\t{:.2}\t-10.00\t10.00\t1\t0\t{}\n", customer_id, district_id, warehouse_id, c_first, c_middle, c_last, c_street1, c_street2, c_city, c_state, c_zip, c_phone, c_since, c_credit, c_discount, c_data);
-
-                match writer.write_all(line.as_bytes()) {
-                    Ok(_) => (),
-                    Err(e) => return Err(e.to_string()),
-                }
-                // Increment customer_id
-                customer_id += 1;
-
-                // Increment district_id and reset customer_id if we have populated
-                // n_customer_per_district customers.
-                if customer_id > n_customer_per_district {
-                    district_id += 1;
-                    customer_id = 1;
-                }
-            }
-            // Finishing the copy order for the current batch
-            match writer.finish() {
-                Ok(_) => (),
-                Err(e) => return Err(e.to_string()),
+                emit(vec![
+                    CopyValue::Int(customer_id),
+                    CopyValue::Int(district_id),
+                    CopyValue::Int(warehouse_id as i32),
+                    CopyValue::Text(c_first),
+                    CopyValue::Text(c_middle),
+                    CopyValue::Text(c_last),
+                    CopyValue::Text(c_street1),
+                    CopyValue::Text(c_street2),
+                    CopyValue::Text(c_city),
+                    CopyValue::Text(c_state),
+                    CopyValue::Text(c_zip),
+                    CopyValue::Text(c_phone.to_string()),
+                    CopyValue::Timestamp(c_since),
+                    CopyValue::Text(c_credit),
+                    // Column c_credit_lim
+                    CopyValue::Numeric(50_000),
+                    CopyValue::Real(TPCC::round(c_discount, 2)),
+                    // Columns c_balance, c_ytd_payment, c_payment_cnt and c_delivery_cnt
+                    CopyValue::Numeric(-10),
+                    CopyValue::Numeric(10),
+                    CopyValue::Real(1.0),
+                    CopyValue::Real(0.0),
+                    CopyValue::Text(c_data),
+                ])?;
             }
         }
+
         Ok(())
     }
 
-    pub fn populate_orders(client: &mut Client, warehouse_id: u32, o_entry_d: String) -> Result<(), String> {
-        // Populate the orders table with 30_000 items per warehouse (1 per customer)
-        let n_items = 30_000 as u32;
-        // Number of lines submitted for each COPY operation
-        let batch_size = 500 as u32;
-        // Number of batch, based on the number of items and batch size
-        let n_batch = ((n_items as f64 / batch_size as f64) as f64).ceil() as u32;
-
-        let n_orders_per_district :u32 = 3_000;
-        let mut orders_id :u32 = 1;
-        let mut customer_id: u32 = 1;
-        let mut district_id: u32 = 1;
-
-        for b in 1..=n_batch {
-            // Start a new copy from stdin op.
-            let mut writer = match client.copy_in("COPY orders FROM stdin NULL AS ''") {
-                Ok(w) => w,
-                Err(e) => return Err(e.to_string()),
-            };
-
-            // Calculate items interval
-            let batch_start = (b * batch_size - batch_size + 1) as u32;
-            let mut batch_end = (b * batch_size) as u32;
-            if batch_end > n_items {
-                batch_end = n_items;
-            }
-
-            // Fill the write buffer batch_size items
-            for _ in batch_start..=batch_end {
-                // Column o_carrier_id
-                let carrier_id :u32 = rand::thread_rng()
+    // Generate 30_000 orders per warehouse: one per customer
+    pub fn generate_orders(rng: &mut StdRng, warehouse_id: u32, o_entry_d: i64, emit: &mut dyn FnMut(CopyRow) -> Result<(), String>) -> Result<(), String> {
+        for district_id in 1..=10 {
+            for orders_id in 1..=3_000 {
+                // Column o_carrier_id, only set for delivered orders
+                let carrier_id :i32 = rng
                     .gen_range(1..=10);
-                let mut o_carrier_id :String = "".to_string();
+                let mut o_carrier_id = CopyValue::Null;
                 if orders_id < 2_101 {
-                    o_carrier_id = format!("{}", carrier_id);
+                    o_carrier_id = CopyValue::Int(carrier_id);
                 }
-                // Generate the number of order_line entries
-                let o_ol_cnt :u32 = (orders_id * (orders_id + district_id + warehouse_id)) % 11 + 5;
 
-                let line = format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t1\n", orders_id, district_id, warehouse_id, customer_id, o_entry_d, o_carrier_id, o_ol_cnt);
-
-                match writer.write_all(line.as_bytes()) {
-                    Ok(_) => (),
-                    Err(e) => return Err(e.to_string()),
-                }
-                // Increment orders_id and customer_id
-                orders_id += 1;
-                customer_id += 1;
-                // Increment district_id and reset orders_id and customer_id if we have populated
-                // n_orders_per_district orders.
-                if orders_id > n_orders_per_district {
-                    district_id += 1;
-                    orders_id = 1;
-                    customer_id = 1;
-                }
-            }
-            // Finishing the copy order for the current batch
-            match writer.finish() {
-                Ok(_) => (),
-                Err(e) => return Err(e.to_string()),
+                emit(vec![
+                    CopyValue::Int(orders_id as i32),
+                    CopyValue::Int(district_id as i32),
+                    CopyValue::Int(warehouse_id as i32),
+                    // Column o_c_id
+                    CopyValue::Int(orders_id as i32),
+                    CopyValue::Timestamp(o_entry_d),
+                    o_carrier_id,
+                    CopyValue::Int(TPCC::order_line_count(orders_id, district_id, warehouse_id) as i32),
+                    // Column o_all_local
+                    CopyValue::Int(1),
+                ])?;
             }
         }
+
         Ok(())
     }
 
-    pub fn populate_history(client: &mut Client, warehouse_id: u32) -> Result<(), String> {
-        // Populate the history table with 30_000 items per warehouse
-        let n_items = 30_000 as u32;
-        // Number of lines submitted for each COPY operation
-        let batch_size = 500 as u32;
-        // Number of batch, based on the number of items and batch size
-        let n_batch = ((n_items as f64 / batch_size as f64) as f64).ceil() as u32;
-
-        let n_customer_per_district :u32 = 3_000;
-        let mut customer_id: u32 = 1;
-        let mut district_id: u32 = 1;
-
-        // Column date
-        let h_date: String = format!("{}", Utc::now().format("%Y-%m-%d %H:%M:%S"));
-
-        for b in 1..=n_batch {
-            // Start a new copy from stdin op.
-            let mut writer = match client.copy_in("COPY history FROM stdin") {
-                Ok(w) => w,
-                Err(e) => return Err(e.to_string()),
-            };
-
-            // Calculate items interval
-            let batch_start = (b * batch_size - batch_size + 1) as u32;
-            let mut batch_end = (b * batch_size) as u32;
-            if batch_end > n_items {
-                batch_end = n_items;
-            }
-
-            // Fill the write buffer batch_size items
-            for _ in batch_start..=batch_end {
+    // Generate 30_000 history rows per warehouse: one per customer
+    pub fn generate_history(rng: &mut StdRng, warehouse_id: u32, h_date: i64, emit: &mut dyn FnMut(CopyRow) -> Result<(), String>) -> Result<(), String> {
+        for district_id in 1..=10 {
+            for customer_id in 1..=3_000 {
                 // Column h_data
-                let h_data :String = TPCC::random_alpha_string(12, 24);
-                let line = format!("{}\t{}\t{}\t{}\t{}\t{}\t10.0\t{}\n", customer_id, district_id, warehouse_id, district_id, warehouse_id, h_date, h_data);
-
-                match writer.write_all(line.as_bytes()) {
-                    Ok(_) => (),
-                    Err(e) => return Err(e.to_string()),
-                }
-                // Increment customer_id
-                customer_id += 1;
-                // Increment district_id and reset customer_id if we have populated
-                // n_customer_per_district history.
-                if customer_id > n_customer_per_district {
-                    district_id += 1;
-                    customer_id = 1;
-                }
-            }
-            // Finishing the copy order for the current batch
-            match writer.finish() {
-                Ok(_) => (),
-                Err(e) => return Err(e.to_string()),
+                let h_data :String = TPCC::random_alpha_string(rng, 12, 24);
+
+                emit(vec![
+                    CopyValue::Int(customer_id),
+                    CopyValue::Int(district_id),
+                    CopyValue::Int(warehouse_id as i32),
+                    CopyValue::Int(district_id),
+                    CopyValue::Int(warehouse_id as i32),
+                    CopyValue::Timestamp(h_date),
+                    // Column h_amount
+                    CopyValue::Real(10.0),
+                    CopyValue::Text(h_data),
+                ])?;
             }
         }
+
         Ok(())
     }
 
-    pub fn populate_stock(client: &mut Client, warehouse_id: u32) -> Result<(), String> {
-        // Populate the stock table with 100_000 items per warehouse
-        let n_items = 100_000 as u32;
-
-        // Number of lines submitted for each COPY operation
-        let batch_size = 500 as u32;
-        // Number of batch, based on the number of items and batch size
-        let n_batch = ((n_items as f64 / batch_size as f64) as f64).ceil() as u32;
-
-        let mut item_id :u32 = 1;
-
-        for b in 1..=n_batch {
-            // Start a new copy from stdin op.
-            let mut writer = match client.copy_in("COPY stock FROM stdin") {
-                Ok(w) => w,
-                Err(e) => return Err(e.to_string()),
-            };
-
-            // Calculate items interval
-            let batch_start = (b * batch_size - batch_size + 1) as u32;
-            let mut batch_end = (b * batch_size) as u32;
-            if batch_end > n_items {
-                batch_end = n_items;
-            }
-
-            // Fill the write buffer batch_size items
-            for _ in batch_start..=batch_end {
-                let s_dist_01 :String = TPCC::random_alpha_string(24, 24);
-                let s_dist_02 :String = TPCC::random_alpha_string(24, 24);
-                let s_dist_03 :String = TPCC::random_alpha_string(24, 24);
-                let s_dist_04 :String = TPCC::random_alpha_string(24, 24);
-                let s_dist_05 :String = TPCC::random_alpha_string(24, 24);
-                let s_dist_06 :String = TPCC::random_alpha_string(24, 24);
-                let s_dist_07 :String = TPCC::random_alpha_string(24, 24);
-                let s_dist_08 :String = TPCC::random_alpha_string(24, 24);
-                let s_dist_09 :String = TPCC::random_alpha_string(24, 24);
-                let s_dist_10 :String = TPCC::random_alpha_string(24, 24);
-                let s_quantity: u32 = rand::thread_rng()
-                    .gen_range(10..=100);
-                // Column s_data
-                let mut s_data :String = TPCC::random_alpha_string(26, 50);
-                let s_data_length = s_data.len();
-                let orig :u32 = rand::thread_rng()
-                    .gen_range(1..=100);
-                if orig <= 10 {
-                    let pos :usize = rand::thread_rng()
-                        .gen_range(1..(s_data_length - 8));
-                    s_data.replace_range(pos..=(pos + 8), "ORIGINAL");
-                }
-
-                let line = format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t0\t0\t0\t{}\n", item_id, warehouse_id, s_quantity, s_dist_01, s_dist_02, s_dist_03, s_dist_04, s_dist_05, s_dist_06, s_dist_07, s_dist_08, s_dist_09, s_dist_10, s_data);
-
-                match writer.write_all(line.as_bytes()) {
-                    Ok(_) => (),
-                    Err(e) => return Err(e.to_string()),
-                }
-                // Increment item_id
-                item_id += 1;
+    // Generate 100_000 stock rows per warehouse: one per item
+    pub fn generate_stock(rng: &mut StdRng, warehouse_id: u32, emit: &mut dyn FnMut(CopyRow) -> Result<(), String>) -> Result<(), String> {
+        for item_id in 1..=100_000 {
+            let mut row = vec![
+                CopyValue::Int(item_id),
+                CopyValue::Int(warehouse_id as i32),
+                // Column s_quantity
+                CopyValue::Int(rng.gen_range(10..=100)),
+            ];
+            // Columns s_dist_01 to s_dist_10
+            for _ in 1..=10 {
+                row.push(CopyValue::Text(TPCC::random_alpha_string(rng, 24, 24)));
             }
-            // Finishing the copy order for the current batch
-            match writer.finish() {
-                Ok(_) => (),
-                Err(e) => return Err(e.to_string()),
+            // Column s_data
+            let mut s_data :String = TPCC::random_alpha_string(rng, 26, 50);
+            let s_data_length = s_data.len();
+            let orig :u32 = rng
+                .gen_range(1..=100);
+            if orig <= 10 {
+                let pos :usize = rng
+                    .gen_range(1..(s_data_length - 8));
+                s_data.replace_range(pos..=(pos + 8), "ORIGINAL");
             }
+            // Columns s_ytd, s_order_cnt and s_remote_cnt
+            row.push(CopyValue::Numeric(0));
+            row.push(CopyValue::Real(0.0));
+            row.push(CopyValue::Real(0.0));
+            row.push(CopyValue::Text(s_data));
+
+            emit(row)?;
         }
+
         Ok(())
     }
 
-    pub fn populate_new_order(client: &mut Client, warehouse_id: u32) -> Result<(), String> {
-        // Populate the new_order table with 9_000 items per warehouse
-        let n_items = 9_000 as u32;
-
-        let orders_start = 2101;
-        let orders_end = 3000;
-
-        // Number of lines submitted for each COPY operation
-        let batch_size = 500 as u32;
-        // Number of batch, based on the number of items and batch size
-        let n_batch = ((n_items as f64 / batch_size as f64) as f64).ceil() as u32;
-
-        // Initialize orders_id and district_id
-        let mut orders_id = orders_start;
-        let mut district_id = 1;
-
-        for b in 1..=n_batch {
-            // Start a new copy from stdin op.
-            let mut writer = match client.copy_in("COPY new_order FROM stdin") {
-                Ok(w) => w,
-                Err(e) => return Err(e.to_string()),
-            };
-
-            // Calculate items interval
-            let batch_start = (b * batch_size - batch_size + 1) as u32;
-            let mut batch_end = (b * batch_size) as u32;
-            if batch_end > n_items {
-                batch_end = n_items;
-            }
-
-            // Fill the write buffer batch_size items
-            for _ in batch_start..=batch_end {
-                let line = format!("{}\t{}\t{}\n", orders_id, district_id, warehouse_id);
-                match writer.write_all(line.as_bytes()) {
-                    Ok(_) => (),
-                    Err(e) => return Err(e.to_string()),
-                }
-                // Increment orders_id
-                orders_id += 1;
-
-                // Check if we have reached the "orders" limit and then move to the next district
-                if orders_id > orders_end {
-                    orders_id = orders_start;
-                    district_id += 1;
-                }
-            }
-            // Finishing the copy order for the current batch
-            match writer.finish() {
-                Ok(_) => (),
-                Err(e) => return Err(e.to_string()),
+    // Generate 9_000 new_order rows per warehouse: the last 900 orders of each district
+    pub fn generate_new_order(warehouse_id: u32, emit: &mut dyn FnMut(CopyRow) -> Result<(), String>) -> Result<(), String> {
+        for district_id in 1..=10 {
+            for orders_id in 2_101..=3_000 {
+                emit(vec![
+                    CopyValue::Int(orders_id),
+                    CopyValue::Int(district_id),
+                    CopyValue::Int(warehouse_id as i32),
+                ])?;
             }
         }
+
         Ok(())
     }
 
-    pub fn populate_order_line(client: &mut Client, warehouse_id: u32, ol_delivery_d :String) -> Result<(), String> {
-        // Populate the order_line table for 30_000 orders per warehouse
-        // Each orders has between 5 and 15 order_line entries
-        let n_orders = 30_000 as u32;
-        // Number of lines submitted for each COPY operation
-        // This is set to a low value because we expect to get *~10 more lines at each iteration of
-        // the main loop. Batch size will be actually growing to around 500 lines.
-        let batch_size = 50 as u32;
-        // Number of batch, based on the total number of orders and batch size
-        let n_batch = ((n_orders as f64 / batch_size as f64) as f64).ceil() as u32;
-
-        let n_orders_per_district :u32 = 3_000;
-
-        let mut orders_id :u32 = 1;
-        let mut district_id: u32 = 1;
-
-        for b in 1..=n_batch {
-            // Start a new copy from stdin op.
-            let mut writer = match client.copy_in("COPY order_line FROM stdin NULL AS ''") {
-                Ok(w) => w,
-                Err(e) => return Err(e.to_string()),
-            };
-
-            // Calculate items interval
-            let batch_start = (b * batch_size - batch_size + 1) as u32;
-            let mut batch_end = (b * batch_size) as u32;
-            if batch_end > n_orders {
-                batch_end = n_orders;
-            }
-
-            // Fill the write buffer batch_size items
-            for _ in batch_start..=batch_end {
+    // Generate the order_line rows of the 30_000 orders per warehouse. Each orders has between 5
+    // and 15 order_line entries.
+    pub fn generate_order_line(rng: &mut StdRng, warehouse_id: u32, ol_delivery_d: i64, emit: &mut dyn FnMut(CopyRow) -> Result<(), String>) -> Result<(), String> {
+        for district_id in 1..=10 {
+            for orders_id in 1..=3_000 {
                 // Generate the number of order_line entries
-                let ol_cnt :u32 = (orders_id * (orders_id + district_id + warehouse_id)) % 11 + 5;
+                let ol_cnt :u32 = TPCC::order_line_count(orders_id, district_id, warehouse_id);
 
                 // Generate the list of item ids we will need
-                let mut rng = rand::thread_rng();
-                let item_ids = rand::seq::index::sample(&mut rng, 100_000, ol_cnt as usize).into_vec();
+                let item_ids = rand::seq::index::sample(rng, 100_000, ol_cnt as usize).into_vec();
 
-                let mut cur_ol_amount: f64;
-                let mut cur_ol_delivery_d: String;
                 // Build one line per item id.
                 for i in 1..=ol_cnt {
                     let item_id = item_ids[(i - 1) as usize] + 1;
                     // Build ol_amount and ol_delivery_d
-                    if orders_id >= 2101 {
-                        cur_ol_amount = rand::thread_rng()
-                            .gen_range(0.01..9999.99);
-                        cur_ol_delivery_d = "".to_string();
+                    let (cur_ol_amount, cur_ol_delivery_d) = if orders_id >= 2101 {
+                        (rng.gen_range(0.01..9999.99), CopyValue::Null)
                     }
                     else {
-                        cur_ol_amount = 0.00;
-                        cur_ol_delivery_d = ol_delivery_d.clone();
-                    }
+                        (0.00, CopyValue::Timestamp(ol_delivery_d))
+                    };
                     // Column ol_dist_info
-                    let ol_dist_info :String = TPCC::random_alpha_string(24, 24);
-
-                    let line = format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t5\t{}\t{}\n", orders_id, district_id, warehouse_id, i, item_id, warehouse_id, cur_ol_delivery_d, cur_ol_amount, ol_dist_info);
-
-                    match writer.write_all(line.as_bytes()) {
-                        Ok(_) => (),
-                        Err(e) => return Err(e.to_string()),
-                    }
+                    let ol_dist_info :String = TPCC::random_alpha_string(rng, 24, 24);
+
+                    emit(vec![
+                        CopyValue::Int(orders_id as i32),
+                        CopyValue::Int(district_id as i32),
+                        CopyValue::Int(warehouse_id as i32),
+                        CopyValue::Int(i as i32),
+                        CopyValue::Int(item_id as i32),
+                        // Column ol_supply_w_id
+                        CopyValue::Int(warehouse_id as i32),
+                        cur_ol_delivery_d,
+                        // Column ol_quantity
+                        CopyValue::Int(5),
+                        CopyValue::Real(cur_ol_amount as f32),
+                        CopyValue::Text(ol_dist_info),
+                    ])?;
                 }
-                // Increment orders_id
-                orders_id += 1;
-                // Increment district_id and reset orders_id if we have populated n_orders_per_district
-                // orders.
-                if orders_id > n_orders_per_district {
-                    district_id += 1;
-                    orders_id = 1;
-                }
-            }
-            // Finishing the copy order for the current batch
-            match writer.finish() {
-                Ok(_) => (),
-                Err(e) => return Err(e.to_string()),
             }
         }
+
         Ok(())
     }
+
+    // Format sorted ids as a list of ranges, e.g. "1-3, 7, 9-12", keeping the first 10 ranges
+    fn format_id_ranges(ids: &[i32]) -> String {
+        let mut ranges: Vec<(i32, i32)> = Vec::new();
+        for id in ids.iter() {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == *id => *end = *id,
+                _ => ranges.push((*id, *id)),
+            }
+        }
+        let mut formatted: Vec<String> = ranges
+            .iter()
+            .take(10)
+            .map(|(start, end)| match start == end {
+                true => start.to_string(),
+                false => format!("{}-{}", start, end),
+            })
+            .collect();
+        if ranges.len() > 10 {
+            formatted.push("...".to_string());
+        }
+
+        formatted.join(", ")
+    }
+
+    // Number of order_line entries of one order, between 5 and 15
+    fn order_line_count(orders_id: u32, district_id: u32, warehouse_id: u32) -> u32 {
+        (orders_id * (orders_id + district_id + warehouse_id)) % 11 + 5
+    }
+
+    // Round a floating point value to the given number of decimals
+    fn round(value: f64, decimals: i32) -> f32 {
+        let factor = 10_f64.powi(decimals);
+
+        ((value * factor).round() / factor) as f32
+    }
 }
 
 #[async_trait]
 impl ReadWrite for TPCC {
-    async fn execute_rw_transaction(&self, conn :&mut PgConnection, transaction :&BenchmarkTransaction) -> Result<u128, Box<dyn std::error::Error>> {
-        // Generate the warehouse id we are going to hit
+    // Load the NURand C-load constants and derive the C-run ones
+    async fn prepare_rw(&mut self, conn: &mut PgConnection, args: &RunArgs, client_id: u32) -> Result<(), Box<dyn std::error::Error>> {
+        if args.affinity {
+            let (warehouse_id, district_id) = self.get_client_home(client_id);
+            self.home = Some((warehouse_id as i32, district_id as i32));
+        }
+        self.protocol = args.protocol;
+        if args.uniform {
+            return Ok(());
+        }
+        let row: (i32, i32, i32) = match sqlx::query_as("SELECT nu_c_last, nu_c_id, nu_ol_i_id FROM nurand")
+            .fetch_one(conn)
+            .await {
+            Ok(row) => row,
+            Err(e) => return Err(Box::new(TPCCError(format!("could not read NURand constants: {}", e)))),
+        };
+        let load = NURand {c_last: row.0 as u32, c_id: row.1 as u32, ol_i_id: row.2 as u32, uniform: false};
+        self.nurand = load.run_constants();
+
+        Ok(())
+    }
+
+    // Clients are spread over the warehouses first, and then over the districts: with 3
+    // warehouses, client 4 gets the district 2 of the warehouse 1.
+    fn get_client_home(&self, client_id: u32) -> (u32, u32) {
+        let n_warehouses = self.max_id - self.min_id + 1;
+        let i = client_id - 1;
+
+        (self.min_id + i % n_warehouses, (i / n_warehouses) % 10 + 1)
+    }
+
+    async fn execute_rw_transaction(&self, conn :&mut PgConnection, transaction :&BenchmarkTransaction, rng: &mut StdRng, timer: &mut StmtTimer) -> Result<TransactionDurations, Box<dyn std::error::Error>> {
+        // Generate the warehouse id we are going to hit, unless the client has a home warehouse
         // The used type is i32 because it matches with Postgres' int4 type.
-        let warehouse_id :i32 = rand::thread_rng()
-            .gen_range(self.min_id..=self.max_id) as i32;
+        let (warehouse_id, home_district_id) :(i32, Option<i32>) = match self.home {
+            Some((warehouse_id, district_id)) => (warehouse_id, Some(district_id)),
+            None => (rng.gen_range(self.min_id..=self.max_id) as i32, None),
+        };
 
         match transaction.id {
             1 => {
-                match TPCC::delivery(conn, warehouse_id, self.min_id, self.max_id).await {
-                    Ok(duration) => return Ok(duration),
-                    Err(e) => return Err(Box::new(TPCCError(e.to_string()))),
-                }
+                self.delivery(conn, rng, timer, warehouse_id).await
             },
             2 => {
-                match TPCC::new_order(conn, warehouse_id, self.min_id, self.max_id).await {
-                    Ok(duration) => return Ok(duration),
-                    Err(e) => return Err(Box::new(TPCCError(e.to_string()))),
-                }
+                self.new_order(conn, rng, timer, warehouse_id, home_district_id).await
             },
             3 => {
-                match TPCC::payment(conn, warehouse_id, self.min_id, self.max_id).await {
-                    Ok(duration) => return Ok(duration),
-                    Err(e) => return Err(Box::new(TPCCError(e.to_string()))),
-                }
+                self.payment(conn, rng, timer, warehouse_id, home_district_id).await
             },
             4 => {
-                match TPCC::order_status(conn, warehouse_id, self.min_id, self.max_id).await {
-                    Ok(duration) => return Ok(duration),
-                    Err(e) => return Err(Box::new(TPCCError(e.to_string()))),
-                }
+                self.order_status(conn, rng, timer, warehouse_id, home_district_id).await
             },
             5 => {
-                match TPCC::stock_level(conn, warehouse_id, self.min_id, self.max_id).await {
-                    Ok(duration) => return Ok(duration),
-                    Err(e) => return Err(Box::new(TPCCError(e.to_string()))),
-                }
+                self.stock_level(conn, rng, timer, warehouse_id, home_district_id).await
             },
             0 | 6..=u16::MAX => todo!(),
-        };
+        }
     }
 }
 
 impl Benchmark for TPCC {
-    fn initialize_schema(&self, client: &mut Client) -> Result<u128, postgres::Error> {
+    fn initialize_schema(&self, client: &mut Client, storage: &StorageArgs) -> Result<u128, postgres::Error> {
         let start = Instant::now();
 
         let mut transaction = client.transaction()?;
         // Sequentially create tables
-        for table_ddl in self.get_table_ddls().iter() {
+        for table_ddl in self.get_table_ddls(storage).iter() {
             transaction.batch_execute(&table_ddl.sql)?;
         }
         transaction.commit()?;
@@ -1698,31 +2428,59 @@ impl Benchmark for TPCC {
 
     // On TPC-C-like benchmark, we need to:
     // - populate the item table with 100k randomly generated rows
-    fn pre_load_data(&self, client: &mut Client) -> Result<u128, String> {
-        let start = Instant::now();
-
-        // Populate the item table
-        TPCC::populate_item(client)?;
+    // - choose the NURand C-load constants
+    fn pre_load_data(&self, client: &mut Client, args: &InitArgs) -> Result<Vec<LoadStats>, String> {
+        let nurand = NURand::new_load(&mut stream_rng(args.seed, NURAND_RNG_STREAM), args.uniform);
+        TPCC::save_nurand(client, &nurand)?;
 
-        Ok(start.elapsed().as_micros())
+        // The item table is loaded as the warehouse 0, without progress report
+        self.load_tables(client, &["item"], 0, args, nurand, &mut ProgressReporter::disabled())
     }
 
-    fn load_data(&self, client: &mut Client, warehouse_ids: Vec<u32>) -> Result<u128, String> {
-        let start = Instant::now();
+    fn load_data(&self, client: &mut Client, warehouse_ids: Vec<u32>, args: &InitArgs, progress: &mut ProgressReporter) -> Result<Vec<LoadStats>, String> {
+        let nurand = TPCC::get_nurand(client, args.uniform)?;
+        let seed = args.seed;
+        let mut all_stats: Vec<LoadStats> = Vec::new();
+
+        // Data generation functions only live in this session. The random pool is built from the
+        // stream 0, like the item table, so every job shares the same pool.
+        if args.generate == DataGeneration::Server {
+            TPCC::set_server_seed(client, seed, 0)?;
+            for sql in SERVER_GENERATION_SETUP.iter() {
+                match client.batch_execute(sql) {
+                    Ok(_) => (),
+                    Err(e) => return Err(e.to_string()),
+                }
+            }
+        }
+
         for warehouse_id in warehouse_ids {
-            // Orders entry date
-            let o_entry_d: String = format!("{}", Utc::now().format("%Y-%m-%d %H:%M:%S"));
-            // Populate tables
-            TPCC::populate_warehouse(client, warehouse_id)?;
-            TPCC::populate_district(client, warehouse_id)?;
-            TPCC::populate_stock(client, warehouse_id)?;
-            TPCC::populate_customer(client, warehouse_id)?;
-            TPCC::populate_history(client, warehouse_id)?;
-            TPCC::populate_orders(client, warehouse_id, o_entry_d.clone())?;
-            TPCC::populate_new_order(client, warehouse_id)?;
-            TPCC::populate_order_line(client, warehouse_id, o_entry_d.clone())?;
+            let stats = match args.generate {
+                DataGeneration::Client => self.load_tables(client, &WAREHOUSE_TABLES, warehouse_id, args, nurand, progress)?,
+                DataGeneration::Server => {
+                    // Tables not generated server side are loaded first, using COPY
+                    let mut stats = self.load_tables(client, &SERVER_COPIED_TABLES, warehouse_id, args, nurand, progress)?;
+
+                    TPCC::set_server_seed(client, seed, warehouse_id)?;
+                    // Entry and delivery dates
+                    let now_s = TPCC::load_timestamp(seed) as f64;
+                    for table in SERVER_GENERATED_TABLES.iter() {
+                        progress.start_table(warehouse_id, table);
+                        let table_stats = TPCC::generate_table_server(client, &nurand, table, warehouse_id, now_s)?;
+                        progress.add_rows(table_stats.rows);
+                        stats.push(table_stats);
+                    }
+
+                    stats
+                },
+            };
+            for table_stats in stats {
+                LoadStats::merge(&mut all_stats, table_stats);
+            }
+            progress.end_warehouse();
         }
-        Ok(start.elapsed().as_micros())
+
+        Ok(all_stats)
     }
 
     fn get_default_max_id(&self, client: &mut Client) -> Result<u32, postgres::Error> {
@@ -1736,23 +2494,283 @@ impl Benchmark for TPCC {
         self.transactions_rw.clone()
     }
 
-    fn get_table_ddls(&self) -> Vec<BenchmarkStmt> {
-        self.table_ddls.clone()
+    fn set_transaction_weights(&mut self, weights: &[(String, u16)]) -> Result<(), String> {
+        for (name, _) in weights.iter() {
+            if !self.transactions_rw.iter().any(|t| t.name.eq_ignore_ascii_case(name)) {
+                return Err(format!(
+                    "unknown transaction in mix: {}. Transactions: {}",
+                    name,
+                    self.transactions_rw.iter().map(|t| t.name.as_str()).collect::<Vec<&str>>().join(", "),
+                ));
+            }
+        }
+        for transaction in self.transactions_rw.iter_mut() {
+            if let Some((_, weight)) = weights.iter().find(|(name, _)| transaction.name.eq_ignore_ascii_case(name)) {
+                transaction.weight = *weight;
+            }
+        }
+        if self.transactions_rw.iter().all(|t| t.weight == 0) {
+            return Err("at least one transaction weight must be greater than 0".to_string());
+        }
+
+        Ok(())
+    }
+
+    fn set_transaction_mode(&mut self, mode: TransactionMode) {
+        self.mode = mode;
+    }
+
+    fn get_tables(&self) -> Vec<BenchmarkTable> {
+        self.tables.clone()
+    }
+
+    fn get_pkeys(&self) -> Vec<BenchmarkIndex> {
+        self.pkeys.clone()
+    }
+
+    fn get_indexes(&self) -> Vec<BenchmarkIndex> {
+        self.indexes.clone()
     }
 
-    fn get_pkey_ddls(&self) -> Vec<BenchmarkStmt> {
-        self.pkey_ddls.clone()
+    // Every warehouse id of the range must have its rows, a half-loaded dataset or a range going
+    // beyond the scale factor would make clients fail
+    fn check_ids(&self, client: &mut Client, min_id: u32, max_id: u32) -> Result<(), String> {
+        let (min_id, max_id) = (min_id as i32, max_id as i32);
+        for (table, column) in WAREHOUSE_ID_COLUMNS.iter().filter(|(table, _)| !UNCHECKED_WAREHOUSE_TABLES.contains(table)) {
+            let sql = format!(
+                "SELECT w FROM generate_series($1::INTEGER, $2::INTEGER) AS w WHERE NOT EXISTS (SELECT 1 FROM {} WHERE {} = w)",
+                table,
+                column,
+            );
+            let missing_ids: Vec<i32> = match client.query(sql.as_str(), &[&min_id, &max_id]) {
+                Ok(rows) => rows.iter().map(|row| row.get(0)).collect(),
+                Err(e) => return Err(e.to_string()),
+            };
+            if !missing_ids.is_empty() {
+                return Err(format!(
+                    "{} warehouse id(s) missing from the {} table: {}",
+                    missing_ids.len(),
+                    table,
+                    TPCC::format_id_ranges(&missing_ids),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    // One heap scan per table and one index scan per index, restricted to the warehouse range.
+    // Heap scans count whole rows so that pages are read even when they are all-visible, index
+    // scans follow the index order with sequential scans, bitmap scans and sorts disabled.
+    fn get_prewarm_stmts(&self, min_id: u32, max_id: u32) -> Vec<BenchmarkStmt> {
+        let mut stmts = Vec::new();
+        for table in self.tables.iter() {
+            let filter = match WAREHOUSE_ID_COLUMNS.iter().find(|(t, _)| *t == table.name) {
+                Some((_, column)) => format!(" WHERE {} BETWEEN {} AND {}", column, min_id, max_id),
+                None => String::new(),
+            };
+            stmts.push(BenchmarkStmt {
+                sql: format!("SELECT count(t) FROM {} t{}", table.name, filter),
+            });
+            for index in self.pkeys.iter().chain(self.indexes.iter()).filter(|i| i.table == table.name) {
+                let columns = index.columns().join(", ");
+                stmts.push(BenchmarkStmt {
+                    sql: format!(
+                        "SET LOCAL enable_seqscan = off; SET LOCAL enable_bitmapscan = off; SET LOCAL enable_sort = off; \
+                        SELECT count(*) FROM (SELECT {} FROM {}{} ORDER BY {}) s",
+                        columns,
+                        table.name,
+                        filter,
+                        columns,
+                    ),
+                });
+            }
+        }
+
+        stmts
+    }
+
+    fn get_table_ddls(&self, storage: &StorageArgs) -> Vec<BenchmarkStmt> {
+        let mut ddls = Vec::new();
+        for table in self.tables.iter() {
+            ddls.push(table.drop_stmt());
+            ddls.push(table.create_stmt(&storage.table(&table.name)));
+        }
+
+        ddls
+    }
+
+    fn get_pkey_ddls(&self, storage: &StorageArgs) -> Vec<BenchmarkStmt> {
+        self.pkeys
+            .iter()
+            .map(|pkey| pkey.pkey_stmt(&storage.pkeys))
+            .collect()
     }
 
     fn get_fkey_ddls(&self) -> Vec<BenchmarkStmt> {
         self.fkey_ddls.clone()
     }
 
-    fn get_index_ddls(&self) -> Vec<BenchmarkStmt> {
-        self.index_ddls.clone()
+    fn get_index_ddls(&self, storage: &StorageArgs) -> Vec<BenchmarkStmt> {
+        self.indexes
+            .iter()
+            .map(|index| index.index_stmt(&storage.indexes))
+            .collect()
     }
 
     fn get_vacuum_stmts(&self) -> Vec<BenchmarkStmt> {
         self.vacuum_stmts.clone()
     }
+
+    fn get_procedure_ddls(&self) -> Vec<BenchmarkStmt> {
+        PROCEDURES.iter().map(|(_, sql)| BenchmarkStmt { sql: sql.to_string() }).collect()
+    }
+
+    fn get_procedure_names(&self) -> Vec<String> {
+        PROCEDURES.iter().map(|(name, _)| name.to_string()).collect()
+    }
+
+    // COPY and INSERT statements are executed once per warehouse, the item table and the NURand
+    // constants being loaded first
+    fn get_load_stmts(&self, args: &InitArgs) -> Vec<BenchmarkLoadStmt> {
+        let load_stmt = |sql: String, table: &str, rows: u64| BenchmarkLoadStmt {
+            stmt: BenchmarkStmt { sql },
+            table: Some(table.to_string()),
+            rows,
+        };
+        let copy_stmt = |table: &str, rows: u64| {
+            let sql = match args.copy_format {
+                CopyFormat::Text => text_copy_sql(table),
+                CopyFormat::Binary => binary_copy_sql(table),
+            };
+            load_stmt(sql, table, rows)
+        };
+
+        let mut stmts = vec![
+            load_stmt(NURAND_INSERT.to_string(), "nurand", self.estimated_rows("nurand")),
+            copy_stmt("item", self.estimated_rows("item")),
+        ];
+        match args.generate {
+            DataGeneration::Client => {
+                for table in WAREHOUSE_TABLES.iter() {
+                    stmts.push(copy_stmt(table, self.estimated_rows(table)));
+                }
+            },
+            DataGeneration::Server => {
+                for sql in SERVER_GENERATION_SETUP.iter() {
+                    stmts.push(BenchmarkLoadStmt { stmt: BenchmarkStmt { sql: sql.to_string() }, table: None, rows: 0 });
+                }
+                for table in SERVER_COPIED_TABLES.iter() {
+                    stmts.push(copy_stmt(table, self.estimated_rows(table)));
+                }
+                for table in SERVER_GENERATED_TABLES.iter() {
+                    // Every generated table has its statement
+                    let sql = TPCC::server_generation_sql(table).unwrap_or_default();
+                    stmts.push(load_stmt(sql.to_string(), table, self.estimated_rows(table)));
+                }
+            },
+        }
+
+        stmts
+    }
+
+    fn estimate_sizes(&self, storage: &StorageArgs) -> Vec<SizeEstimate> {
+        let mut estimates = Vec::new();
+        for table in self.tables.iter() {
+            let rows = self.estimated_rows(&table.name);
+            let pkey_bytes: u64 = self.pkeys
+                .iter()
+                .filter(|pkey| pkey.table == table.name)
+                .map(|pkey| pkey.estimate_size(table, rows, &storage.pkeys, &TPCC::text_length))
+                .sum();
+            let index_bytes: u64 = self.indexes
+                .iter()
+                .filter(|index| index.table == table.name)
+                .map(|index| index.estimate_size(table, rows, &storage.indexes, &TPCC::text_length))
+                .sum();
+
+            estimates.push(SizeEstimate {
+                table: table.name.clone(),
+                rows,
+                table_bytes: table.estimate_size(rows, &storage.table(&table.name), &TPCC::text_length),
+                pkey_bytes,
+                index_bytes,
+            });
+        }
+
+        estimates
+    }
+
+    fn get_transaction_stmts(&self, transaction: &BenchmarkTransaction) -> Vec<BenchmarkStmt> {
+        if self.mode == TransactionMode::Procedure {
+            let call = match transaction.id {
+                1 => DELIVERY_CALL,
+                2 => NEW_ORDER_CALL,
+                3 => PAYMENT_CALL,
+                4 => ORDER_STATUS_CALL,
+                5 => STOCK_LEVEL_CALL,
+                _ => return Vec::new(),
+            };
+            return vec![BenchmarkStmt { sql: call.to_string() }];
+        }
+        let sqls: Vec<&str> = match transaction.id {
+            1 => vec![
+                DELIVERY_SELECT_NEW_ORDER,
+                DELIVERY_DELETE_NEW_ORDER,
+                DELIVERY_UPDATE_ORDERS,
+                DELIVERY_UPDATE_ORDER_LINE,
+                DELIVERY_SELECT_AMOUNT,
+                DELIVERY_UPDATE_CUSTOMER,
+            ],
+            2 => vec![
+                NEW_ORDER_SELECT_WAREHOUSE,
+                NEW_ORDER_UPDATE_DISTRICT,
+                NEW_ORDER_SELECT_CUSTOMER,
+                NEW_ORDER_INSERT_ORDERS,
+                NEW_ORDER_INSERT_NEW_ORDER,
+                NEW_ORDER_SELECT_ITEM,
+                NEW_ORDER_SELECT_STOCK,
+                NEW_ORDER_UPDATE_STOCK,
+                NEW_ORDER_INSERT_ORDER_LINE,
+            ],
+            3 => vec![
+                PAYMENT_UPDATE_WAREHOUSE,
+                PAYMENT_UPDATE_DISTRICT,
+                SELECT_CUSTOMER_BY_LAST_NAME,
+                PAYMENT_SELECT_CUSTOMER,
+                PAYMENT_UPDATE_CUSTOMER_BC,
+                PAYMENT_UPDATE_CUSTOMER,
+                PAYMENT_INSERT_HISTORY,
+            ],
+            4 => vec![
+                SELECT_CUSTOMER_BY_LAST_NAME,
+                ORDER_STATUS_SELECT_CUSTOMER,
+                ORDER_STATUS_SELECT_ORDERS,
+                ORDER_STATUS_SELECT_ORDER_LINE,
+            ],
+            5 => vec![
+                STOCK_LEVEL_SELECT_DISTRICT,
+                STOCK_LEVEL_SELECT_ORDER_LINE,
+                STOCK_LEVEL_SELECT_STOCK,
+            ],
+            _ => Vec::new(),
+        };
+
+        sqls.into_iter().map(|sql| BenchmarkStmt { sql: sql.to_string() }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_constants_last_name_delta() {
+        for c_last in 0..=255 {
+            let load = NURand { c_last, c_id: 0, ol_i_id: 0, uniform: false };
+            let delta = load.run_constants().c_last.abs_diff(c_last);
+            assert!((65..=119).contains(&delta), "c_last {}: delta {}", c_last, delta);
+            assert!(delta != 96 && delta != 112, "c_last {}: delta {}", c_last, delta);
+        }
+    }
 }
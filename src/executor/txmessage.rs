@@ -4,6 +4,20 @@ pub enum TXMessageKind {
     ERROR,
     TERMINATE,
     ENDOFRAMPUP,
+    #[allow(clippy::upper_case_acronyms)]
+    STARTOFSTAGE,
+    #[allow(clippy::upper_case_acronyms)]
+    ENDOFSTAGE,
+    #[allow(clippy::upper_case_acronyms)]
+    STATEMENT,
+    #[allow(clippy::upper_case_acronyms)]
+    CONNECTED,
+    #[allow(clippy::upper_case_acronyms)]
+    ACQUIRED,
+    #[allow(clippy::upper_case_acronyms)]
+    OUTAGE,
+    #[allow(clippy::upper_case_acronyms)]
+    FAULT,
 }
 
 pub struct TXMessage {
@@ -11,8 +25,27 @@ pub struct TXMessage {
     pub tx_id: u16,
     pub client_id: u32,
     pub tx_duration_us: u128,
+    // Commit time, included in tx_duration_us, for COMMITTED messages. None when the client does
+    // not issue the commit.
+    pub commit_duration_us: Option<u128>,
     pub tx_timestamp: i64,
     pub error: String,
+    // The error is a serialization failure, for ERROR messages
+    pub serialization_failure: bool,
+    // Index of the host which has run the transaction, for COMMITTED and ERROR messages
+    pub host: u16,
+    // No idle connection was left in the pool, for ACQUIRED messages
+    pub pool_saturated: bool,
+    // Label of the timed statement, for STATEMENT messages
+    pub statement: &'static str,
+    // Transactions failed during the outage, for OUTAGE messages
+    pub n_lost: u32,
+    // Name of the injected fault and number of signaled sessions, for FAULT messages
+    pub fault: &'static str,
+    pub n_sessions: u32,
+    // Stage number and number of clients, for STARTOFSTAGE messages
+    pub stage: u16,
+    pub n_client: u32,
 }
 
 impl TXMessage {
@@ -22,8 +55,18 @@ impl TXMessage {
             tx_id: 0,
             client_id: 0,
             tx_duration_us: 0,
+            commit_duration_us: None,
             tx_timestamp: 0,
             error: "".to_string(),
+            serialization_failure: false,
+            host: 0,
+            pool_saturated: false,
+            statement: "",
+            n_lost: 0,
+            fault: "",
+            n_sessions: 0,
+            stage: 0,
+            n_client: 0,
         }
     }
 
@@ -34,24 +77,91 @@ impl TXMessage {
         m
     }
 
-    pub fn error(tx_id: u16, client_id: u32, tx_timestamp: i64, error: String) -> TXMessage {
+    pub fn error(tx_id: u16, client_id: u32, tx_timestamp: i64, error: String, serialization_failure: bool, host: u16) -> TXMessage {
         let mut m = Self::default();
         m.kind = TXMessageKind::ERROR;
         m.tx_id = tx_id;
         m.client_id = client_id;
         m.tx_timestamp = tx_timestamp;
         m.error = error;
+        m.serialization_failure = serialization_failure;
+        m.host = host;
 
         m
     }
 
-    pub fn committed(tx_id: u16, client_id: u32, tx_timestamp: i64, tx_duration_us: u128) -> TXMessage {
+    pub fn committed(tx_id: u16, client_id: u32, tx_timestamp: i64, tx_duration_us: u128, commit_duration_us: Option<u128>, host: u16) -> TXMessage {
         let mut m = Self::default();
         m.kind = TXMessageKind::COMMITTED;
         m.tx_id = tx_id;
         m.client_id = client_id;
         m.tx_timestamp = tx_timestamp;
         m.tx_duration_us = tx_duration_us;
+        m.commit_duration_us = commit_duration_us;
+        m.host = host;
+
+        m
+    }
+
+    // Statement executed by a transaction: tx_duration_us holds the statement duration
+    pub fn statement(tx_id: u16, client_id: u32, tx_timestamp: i64, statement: &'static str, duration_us: u128) -> TXMessage {
+        let mut m = Self::default();
+        m.kind = TXMessageKind::STATEMENT;
+        m.tx_id = tx_id;
+        m.client_id = client_id;
+        m.tx_timestamp = tx_timestamp;
+        m.statement = statement;
+        m.tx_duration_us = duration_us;
+
+        m
+    }
+
+    // New client connection: tx_duration_us holds the connection time
+    pub fn connected(client_id: u32, tx_timestamp: i64, duration_us: u128) -> TXMessage {
+        let mut m = Self::default();
+        m.kind = TXMessageKind::CONNECTED;
+        m.client_id = client_id;
+        m.tx_timestamp = tx_timestamp;
+        m.tx_duration_us = duration_us;
+
+        m
+    }
+
+    // Connection borrowed from the pool: tx_duration_us holds the wait time
+    pub fn acquired(client_id: u32, tx_timestamp: i64, wait_us: u128, pool_saturated: bool) -> TXMessage {
+        let mut m = Self::default();
+        m.kind = TXMessageKind::ACQUIRED;
+        m.client_id = client_id;
+        m.tx_timestamp = tx_timestamp;
+        m.tx_duration_us = wait_us;
+        m.pool_saturated = pool_saturated;
+
+        m
+    }
+
+    // Client outage, from the first connection error to the first commit after the reconnection:
+    // tx_timestamp holds the start of the outage in milliseconds, tx_duration_us its duration
+    pub fn outage(client_id: u32, start_ms: i64, duration_us: u128, n_lost: u32) -> TXMessage {
+        let mut m = Self::default();
+        m.kind = TXMessageKind::OUTAGE;
+        m.client_id = client_id;
+        m.tx_timestamp = start_ms;
+        m.tx_duration_us = duration_us;
+        m.n_lost = n_lost;
+
+        m
+    }
+
+    // Injected fault: tx_timestamp holds the time of the fault in milliseconds, tx_duration_us the
+    // time taken by the fault, or the time the lock is held. error is set when the fault has failed.
+    pub fn fault(fault: &'static str, timestamp_ms: i64, duration_us: u128, n_sessions: u32, error: String) -> TXMessage {
+        let mut m = Self::default();
+        m.kind = TXMessageKind::FAULT;
+        m.fault = fault;
+        m.tx_timestamp = timestamp_ms;
+        m.tx_duration_us = duration_us;
+        m.n_sessions = n_sessions;
+        m.error = error;
 
         m
     }
@@ -62,4 +172,20 @@ impl TXMessage {
 
         m
     }
+
+    pub fn start_of_stage(stage: u16, n_client: u32) -> TXMessage {
+        let mut m = Self::default();
+        m.kind = TXMessageKind::STARTOFSTAGE;
+        m.stage = stage;
+        m.n_client = n_client;
+
+        m
+    }
+
+    pub fn end_of_stage() -> TXMessage {
+        let mut m = Self::default();
+        m.kind = TXMessageKind::ENDOFSTAGE;
+
+        m
+    }
 }
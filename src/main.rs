@@ -4,24 +4,42 @@ mod args;
 fn main() {
     // Parse command arguments
     let env = args::PgMtrArgs::new(args::get_os_username(), args::get_pg_password());
-    let dsn = args::get_dsn(&env);
+    let hosts = args::get_dsns(&env);
+    let dsn = hosts[0].1.clone();
 
     match &*env.action {
         "run" => {
-            executor::Executor::new(dsn, env.benchmark_type)
-                .run_benchmark(env.run_args)
-                .aggregate_data()
-                .print_results();
+            let mut executor = executor::Executor::new(dsn, env.benchmark_type);
+            executor
+                .set_mix(&env.run_args.mix)
+                .set_mode(env.run_args.mode);
+            // The hosts are probed to select the target host, which a dry run must not do
+            if env.run_args.dry_run {
+                executor.print_transactions();
+            }
+            else {
+                executor
+                    .set_hosts(hosts, env.target_session_attrs)
+                    .run_benchmark(env.run_args)
+                    .aggregate_data()
+                    .print_results();
+            }
         },
         "init" => {
+            let mut executor = executor::Executor::new(dsn, env.benchmark_type);
+            if env.init_args.dry_run {
+                executor.print_init_steps(&env.init_args);
+            }
+            else {
+                executor
+                    .set_hosts(hosts, env.target_session_attrs)
+                    .run_init_steps(&env.init_args)
+                    .write_init_report();
+            }
+        },
+        "estimate" => {
             executor::Executor::new(dsn, env.benchmark_type)
-                .init_db_schema()
-                .load_data(env.init_args.scalefactor, env.init_args.jobs)
-                .add_primary_keys(env.init_args.jobs)
-                .add_foreign_keys(env.init_args.jobs, env.init_args.no_fkey)
-                .add_indexes(env.init_args.jobs)
-                .vacuum(env.init_args.jobs)
-                .checkpoint();
+                .print_estimate(&env.init_args);
         },
         _ => todo!(),
     }